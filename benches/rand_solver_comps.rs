@@ -5,10 +5,37 @@ use std::{
     io::{BufRead, BufReader},
 };
 
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use pure_be::{solver::Solver, sudoku::Sudoku};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use pure_be::{
+    dfs::{CellOrdering, DfsSolver, PropagationLevel},
+    dlx::{ColumnSelection, DlxSolver, MatrixTemplate},
+    solver::{Solver, SolverType, SudokuSolver},
+    sudoku::Sudoku,
+};
 use rand::Rng;
 
+// Among the hardest puzzles for DFS in the first 16000 of the 'sudoku17' collection
+const HARD_DFS_INPUTS: [&str; 4] = [
+    "000002300100500000007000000400000017030800000000000600006470000020000900000010000",
+    "000001050830000000000000600100300000005000020000040007070020000000000308000400100",
+    "000000608900002000000000300500060070000800000000030000020007500038100000000000040",
+    "000002400560000000100000000020160000000000850000030000300000016008004000000700000",
+];
+// The hardest puzzles for DLX in the 'sudoku17' collection
+const HARD_DLX_INPUTS: [&str; 4] = [
+    "000801000000000205000000700507620000030000010000000000600000040000250000490000000",
+    "020700000000640000000000400406300000000000082500000000000050029300000600000001000",
+    "000200700501000000400060000640050000000008300000000000020300010000000046000000005",
+    "000300408520000000000000100300080050000009060000010000000602000018000000004000000",
+];
+// Solved without backtracking by DLX
+const EASY_INPUTS: [&str; 2] = [
+    "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+    "000000012700060000000000050080200000600000400000109000019000000000030800502000000",
+];
+const SMALL_INPUT: &str = "1030040220404003";
+const HEX_INPUT: &str = "008A03041F05200DF050A0080CD2070BCG00E10500B0890A00400G0209A85F1009B03000F0000200070000000000A0F000EG1F5A703008960F00698B02GE0473G0C000100080000000000A000040700060058037E00FCGD40B780D000659F100000702E180030000B80074DG0A060E2C0560980320C1GD07000000A04D703000";
+
 /// Randomly picks 3 unsolved Sudokus to use as inputs and returns them in a vector.
 fn randomized_inputs() -> Vec<String> {
     static COLLECTION_SIZE: usize = 49150;
@@ -27,25 +54,352 @@ fn randomized_inputs() -> Vec<String> {
     inputs
 }
 
+/// Asserts that the measured thread CPU time doesn't exceed the wall-clock time of the
+/// single-threaded solve (within the tolerance of the clock granularities).
+fn assert_cpu_within_wall(input: &str, solver_type: SolverType) {
+    const TOLERANCE_US: u128 = 1000;
+
+    let mut solver = Solver::new(Sudoku::new(input.to_owned()).unwrap(), solver_type).unwrap();
+    solver.solve();
+
+    assert!(solver.total_cpu_time_us() <= solver.total_wall_time_us() + TOLERANCE_US);
+}
+
 fn bench_solvers(c: &mut Criterion) {
     let mut group = c.benchmark_group("Solvers");
     let inputs = randomized_inputs();
 
+    for i in inputs.iter() {
+        assert_cpu_within_wall(i, SolverType::Dfs);
+        assert_cpu_within_wall(i, SolverType::Dlx);
+        assert_cpu_within_wall(i, SolverType::Auto);
+    }
+
     for i in inputs {
         group.bench_with_input(BenchmarkId::new("DFS", i.clone()), &i, |b, i| {
             b.iter(|| {
-                let mut solver = Solver::new(Sudoku::new(i.clone()).unwrap(), "dfs");
+                let mut solver =
+                    Solver::new(Sudoku::new(i.clone()).unwrap(), SolverType::Dfs).unwrap();
                 solver.solve();
             })
         });
         group.bench_with_input(BenchmarkId::new("DLX", i.clone()), &i, |b, i| {
             b.iter(|| {
-                let mut solver = Solver::new(Sudoku::new(i.clone()).unwrap(), "dlx");
+                let mut solver =
+                    Solver::new(Sudoku::new(i.clone()).unwrap(), SolverType::Dlx).unwrap();
                 solver.solve();
             })
         });
+        group.bench_with_input(BenchmarkId::new("Auto", i.clone()), &i, |b, i| {
+            b.iter(|| {
+                let mut solver =
+                    Solver::new(Sudoku::new(i.clone()).unwrap(), SolverType::Auto).unwrap();
+                solver.solve();
+            })
+        });
+    }
+}
+
+fn bench_is_solved(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sudoku");
+    let unsolved = Sudoku::new(randomized_inputs().remove(0)).unwrap();
+    let mut solver = Solver::new(unsolved.clone(), SolverType::Dlx).unwrap();
+    solver.solve();
+    let solved = solver.get_sudoku().clone();
+
+    // The solved grid is the worst case, the iterator only returns early on an empty cell
+    group.bench_function("is_solved/unsolved", |b| {
+        b.iter(|| black_box(&unsolved).is_solved())
+    });
+    group.bench_function("is_solved/solved", |b| {
+        b.iter(|| black_box(&solved).is_solved())
+    });
+}
+
+fn bench_clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Sudoku");
+    let unsolved = Sudoku::new(randomized_inputs().remove(0)).unwrap();
+
+    // The solvers clone the grid for every solve, and DFS for every snapshot of the search
+    group.bench_function("clone", |b| b.iter(|| black_box(&unsolved).clone()));
+    group.bench_function("clone_grid", |b| {
+        b.iter(|| black_box(&unsolved).clone_grid())
+    });
+}
+
+fn bench_constructors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Constructors");
+    let unsolved = Sudoku::new(randomized_inputs().remove(0)).unwrap();
+
+    // Only the construction of the solvers, e.g. the domains and the DLX matrix
+    for (name, solver_type) in [("DFS", SolverType::Dfs), ("DLX", SolverType::Dlx)] {
+        group.bench_function(name, |b| {
+            b.iter(|| Solver::new(black_box(&unsolved).clone(), solver_type).unwrap())
+        });
+    }
+
+    // The construction of DFS followed by the constraint propagation preceding the search
+    group.bench_function("DFS+propagate", |b| {
+        b.iter(|| DfsSolver::new(black_box(&unsolved).clone()).propagate())
+    });
+}
+
+fn bench_dlx_matrix(c: &mut Criterion) {
+    const SAMPLE_SIZE: usize = 1000;
+
+    let mut group = c.benchmark_group("DLX matrix");
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    let sample: Vec<Sudoku> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(SAMPLE_SIZE)
+        .map(|line| Sudoku::new(line).unwrap())
+        .collect();
+
+    // Only the construction itself, without the clones of the puzzles
+    let mut construct_arena = |name: &str, construct: fn(Sudoku) -> DlxSolver| {
+        group.bench_function(format!("construct_arena/sudoku17/{}", name), |b| {
+            b.iter_batched(
+                || sample[..100].to_vec(),
+                |sudokus| {
+                    for sudoku in sudokus {
+                        black_box(construct(sudoku));
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    };
+
+    // The arena reserved for the exact number of nodes, and grown on demand from 4 nodes per
+    // column as before the exact reservation (only with the `bench` feature) respectively
+    construct_arena("exact", |sudoku| DlxSolver::new(sudoku).unwrap());
+    #[cfg(feature = "bench")]
+    construct_arena("amortized", |sudoku| {
+        DlxSolver::with_amortized_arena(sudoku).unwrap()
+    });
+
+    group.bench_function("construct/sudoku17", |b| {
+        b.iter(|| {
+            for sudoku in &sample[..100] {
+                black_box(DlxSolver::new(sudoku.clone()).unwrap());
+            }
+        })
+    });
+
+    // Building the matrix of each puzzle against copying the one built once, over the whole batch
+    let template = MatrixTemplate::new(9).unwrap();
+    group.bench_function("construct_batch/new", |b| {
+        b.iter(|| {
+            for sudoku in &sample {
+                black_box(DlxSolver::new(sudoku.clone()).unwrap());
+            }
+        })
+    });
+    group.bench_function("construct_batch/template", |b| {
+        b.iter(|| {
+            for sudoku in &sample {
+                black_box(DlxSolver::with_template(&template, sudoku, 1).unwrap());
+            }
+        })
+    });
+    group.bench_function("solve/sudoku17", |b| {
+        b.iter(|| {
+            for sudoku in &sample[..100] {
+                DlxSolver::new(sudoku.clone()).unwrap().solve();
+            }
+        })
+    });
+}
+
+fn bench_dlx_sizes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DLX sizes");
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    // Fixed puzzles rather than random ones, so the 9x9 runs are comparable across the changes of
+    // the matrix construction, e.g. its generalization to the other sizes
+    let sample: Vec<Sudoku> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(100)
+        .map(|line| Sudoku::new(line).unwrap())
+        .collect();
+
+    group.bench_function("solve/9x9", |b| {
+        b.iter(|| {
+            for sudoku in &sample {
+                DlxSolver::new(sudoku.clone()).unwrap().solve();
+            }
+        })
+    });
+
+    for (name, input) in [("4x4", SMALL_INPUT), ("16x16", HEX_INPUT)] {
+        let sudoku = input.parse::<Sudoku>().unwrap();
+        group.bench_function(format!("solve/{}", name), |b| {
+            b.iter(|| DlxSolver::new(black_box(&sudoku).clone()).unwrap().solve())
+        });
+    }
+}
+
+fn bench_dlx_column_selection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DLX column selection");
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    let sample: Vec<Sudoku> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(300)
+        .map(|line| Sudoku::new(line).unwrap())
+        .collect();
+    let easy: Vec<Sudoku> = EASY_INPUTS
+        .iter()
+        .map(|&input| Sudoku::new(input.to_owned()).unwrap())
+        .collect();
+
+    let solve_all = |sample: &[Sudoku], column_selection| {
+        for sudoku in sample {
+            let mut solver = DlxSolver::new(sudoku.clone()).unwrap();
+            solver.set_column_selection(column_selection);
+            solver.solve();
+        }
+    };
+
+    for (name, column_selection) in [
+        ("min_count", ColumnSelection::MinCount),
+        ("first_available", ColumnSelection::FirstAvailable),
+        ("min_count_early_exit", ColumnSelection::MinCountEarlyExit),
+    ] {
+        group.bench_function(format!("solve/easy/{}", name), |b| {
+            b.iter(|| solve_all(&easy, column_selection))
+        });
+
+        // Without any scan, the search of the 17-clue puzzles takes far too long
+        if column_selection != ColumnSelection::FirstAvailable {
+            group.bench_function(format!("solve/sudoku17/{}", name), |b| {
+                b.iter(|| solve_all(&sample, column_selection))
+            });
+        }
+    }
+}
+
+fn bench_dlx_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DLX parallel");
+    let sample: Vec<Sudoku> = HARD_DLX_INPUTS
+        .iter()
+        .map(|&input| Sudoku::new(input.to_owned()).unwrap())
+        .collect();
+
+    for (name, parallel) in [("sequential", false), ("parallel", true)] {
+        group.bench_function(format!("solve/{}", name), |b| {
+            b.iter(|| {
+                for sudoku in &sample {
+                    let mut solver = DlxSolver::new(sudoku.clone()).unwrap();
+                    solver.set_parallel(parallel);
+                    solver.solve();
+                }
+            })
+        });
+    }
+}
+
+fn bench_dfs_backtracking(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DFS backtracking");
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    // Fixed puzzles rather than random ones, so the runs are comparable across the changes of
+    // the search, e.g. its backtracking bookkeeping
+    let sample: Vec<Sudoku> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(20)
+        .map(|line| Sudoku::new(line).unwrap())
+        .collect();
+
+    group.sample_size(10);
+    group.bench_function("solve/sudoku17", |b| {
+        b.iter(|| {
+            for sudoku in &sample {
+                DfsSolver::new(sudoku.clone()).solve();
+            }
+        })
+    });
+}
+
+fn bench_dfs_ordering(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DFS ordering");
+    let sample: Vec<Sudoku> = HARD_DFS_INPUTS
+        .iter()
+        .map(|grid| grid.parse().unwrap())
+        .collect();
+    let orderings = [
+        ("MRV", CellOrdering::Mrv),
+        ("MRV+degree", CellOrdering::MrvDegree),
+        ("dom/deg", CellOrdering::DomOverDegree),
+    ];
+
+    group.sample_size(10);
+    for (name, ordering) in orderings {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for sudoku in &sample {
+                    let mut solver = DfsSolver::new(sudoku.clone());
+                    solver.set_ordering(ordering);
+                    solver.solve();
+                }
+            })
+        });
+    }
+}
+
+fn bench_dfs_propagation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DFS propagation");
+    let hard: Vec<Sudoku> = HARD_DFS_INPUTS
+        .iter()
+        .map(|grid| grid.parse().unwrap())
+        .collect();
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    let easy: Vec<Sudoku> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(100)
+        .map(|line| Sudoku::new(line).unwrap())
+        .collect();
+    let levels = [
+        ("FC", PropagationLevel::ForwardChecking),
+        ("MAC", PropagationLevel::Mac),
+    ];
+
+    // The hard puzzles for the pruning of MAC, the first ones of the collection for its overhead
+    group.sample_size(10);
+    for (sample_name, sample) in [("hard", &hard), ("sudoku17", &easy)] {
+        for (name, level) in levels {
+            group.bench_function(format!("{}/{}", name, sample_name), |b| {
+                b.iter(|| {
+                    for sudoku in sample.iter() {
+                        let mut solver = DfsSolver::new(sudoku.clone());
+                        solver.set_propagation_level(level);
+                        solver.solve();
+                    }
+                })
+            });
+        }
     }
 }
 
-criterion_group!(benches, bench_solvers);
+criterion_group!(
+    benches,
+    bench_solvers,
+    bench_is_solved,
+    bench_clone,
+    bench_constructors,
+    bench_dlx_matrix,
+    bench_dlx_sizes,
+    bench_dlx_column_selection,
+    bench_dlx_parallel,
+    bench_dfs_backtracking,
+    bench_dfs_ordering,
+    bench_dfs_propagation
+);
 criterion_main!(benches);
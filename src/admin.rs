@@ -0,0 +1,79 @@
+use actix_web::{get, http::header::AUTHORIZATION, put, web, HttpRequest, HttpResponse};
+use tracing::{debug, info};
+
+use crate::{
+    error::ApiError,
+    limiter::{RateLimitState, RateLimits},
+};
+
+/// Static token authenticating the admin endpoints, sent by the clients as the bearer token of
+/// the `Authorization` header. The admin endpoints reject every request if it's not configured.
+#[derive(Debug, Clone)]
+pub struct AdminToken(String);
+
+impl AdminToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// Compares the `bearer` token with the configured one in constant time, i.e. without
+    /// stopping at the first differing byte, so that the timing doesn't leak the matching prefix.
+    /// Only the length of the token is revealed.
+    fn matches(&self, bearer: &str) -> bool {
+        let (token, bearer) = (self.0.as_bytes(), bearer.as_bytes());
+
+        token.len() == bearer.len()
+            && token
+                .iter()
+                .zip(bearer)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    fn authorize(token: Option<&web::Data<AdminToken>>, req: &HttpRequest) -> Result<(), ApiError> {
+        let bearer = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match (token, bearer) {
+            (Some(token), Some(bearer)) if token.matches(bearer) => Ok(()),
+            _ => {
+                debug!("Admin request rejected due to a missing or invalid token");
+                Err(ApiError::Unauthorized)
+            }
+        }
+    }
+}
+
+/// Reports the current limits of the rate limiter and the live statistics of its buckets.
+#[get("/admin/rate-limit")]
+pub async fn get_rate_limit(
+    req: HttpRequest,
+    token: Option<web::Data<AdminToken>>,
+    state: web::Data<RateLimitState>,
+) -> Result<HttpResponse, ApiError> {
+    AdminToken::authorize(token.as_ref(), &req)?;
+
+    Ok(HttpResponse::Ok().json(state.stats()))
+}
+
+/// Replaces the limits of the rate limiter, taking effect on the subsequent requests.
+#[put("/admin/rate-limit")]
+pub async fn put_rate_limit(
+    req: HttpRequest,
+    token: Option<web::Data<AdminToken>>,
+    state: web::Data<RateLimitState>,
+    limits: web::Json<RateLimits>,
+) -> Result<HttpResponse, ApiError> {
+    AdminToken::authorize(token.as_ref(), &req)?;
+    state.update(*limits)?;
+    info!(
+        interval_s = limits.interval_s,
+        burst_size = limits.burst_size,
+        "Rate limits updated"
+    );
+
+    Ok(HttpResponse::Ok().json(state.stats()))
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    hash::Hash,
+};
+
+use crate::sudoku::{Conflict, Pos, Sudoku, Unit};
+
+/// Checks whether all of the items yielded by `iter` are distinct.
+pub fn has_unique_items<T>(iter: T) -> bool
+where
+    T: IntoIterator,
+    T::Item: Eq + Hash,
+{
+    let mut uniq = HashSet::new();
+    iter.into_iter().all(move |x| uniq.insert(x))
+}
+
+/// Checks that the `index`th `unit` of the grid doesn't contain any repeated digits, the empty
+/// cells aside.
+pub fn check_unit(sudoku: &Sudoku, unit: Unit, index: usize) -> bool {
+    has_unique_items(
+        sudoku
+            .unit_values(unit, index)
+            .map(|(_, value)| value)
+            .filter(|&value| value != 0),
+    )
+}
+
+/// Iterates over the `(unit, index)` pairs of `units` violating the default Sudoku constraints,
+/// in the order they were given.
+pub fn violations<'a>(
+    sudoku: &'a Sudoku,
+    units: impl IntoIterator<Item = (Unit, usize)> + 'a,
+) -> impl Iterator<Item = (Unit, usize)> + 'a {
+    units
+        .into_iter()
+        .filter(move |&(unit, index)| !check_unit(sudoku, unit, index))
+}
+
+/// Lists every digit repeated within the `index`th `unit` of the grid, ordered by the value.
+pub fn unit_conflicts(sudoku: &Sudoku, unit: Unit, index: usize) -> Vec<Conflict> {
+    let mut cells_by_value: BTreeMap<u8, Vec<Pos>> = BTreeMap::new();
+
+    for (pos, value) in sudoku.unit_values(unit, index) {
+        if value != 0 {
+            cells_by_value.entry(value).or_default().push(pos);
+        }
+    }
+
+    cells_by_value
+        .into_iter()
+        .filter(|(_, cells)| cells.len() > 1)
+        .map(|(value, cells)| Conflict {
+            unit,
+            index,
+            value,
+            cells,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVALID_GRID: &str =
+        "830070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    #[test]
+    fn test_has_unique_items() {
+        assert!(has_unique_items([1, 2, 3]));
+        assert!(has_unique_items(Vec::<u8>::new()));
+        assert!(!has_unique_items([1, 2, 1]));
+    }
+
+    #[test]
+    fn test_violations() {
+        let sudoku = INVALID_GRID.parse::<Sudoku>().unwrap();
+        let units = [Unit::Row, Unit::Column, Unit::Square]
+            .into_iter()
+            .flat_map(|unit| (0..9).map(move |index| (unit, index)));
+
+        assert_eq!(
+            violations(&sudoku, units).collect::<Vec<_>>(),
+            vec![(Unit::Column, 0), (Unit::Square, 0)]
+        );
+        assert!(check_unit(&sudoku, Unit::Row, 0));
+        assert!(!check_unit(&sudoku, Unit::Column, 0));
+
+        let conflicts = unit_conflicts(&sudoku, Unit::Square, 0);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].value, 8);
+        assert_eq!(conflicts[0].cells, [(0, 0), (2, 2)].map(Pos::from));
+        assert!(unit_conflicts(&sudoku, Unit::Row, 0).is_empty());
+    }
+}
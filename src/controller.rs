@@ -1,243 +1,2041 @@
-use core::fmt;
-use std::str::FromStr;
+use std::{collections::BTreeMap, fmt, ops::Deref, str::FromStr, sync::OnceLock};
 
-use actix_web::{error::HttpError, http::StatusCode, post, web, HttpResponse, Responder};
-use log::{debug, error, info};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
+use actix_web::{
+    error::JsonPayloadError,
+    http::{header::RETRY_AFTER, StatusCode},
+    post, web, HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tracing::{debug, error, field, info, info_span, Span};
 
-use crate::{solver::Solver, sudoku::Sudoku};
+use crate::{
+    dfs::SearchStats,
+    dlx::{ColumnSelection, DlxStats, ExactCoverError, MatrixTemplate},
+    error::ApiError,
+    quota::{QuotaUsage, Quotas},
+    rating::{rating, Difficulty, Effort},
+    shutdown::Shutdown,
+    solver::{NodeCounts, Outcome, SolveOptions, Solver, SolverType, Uniqueness},
+    stats::PuzzleStats,
+    sudoku::{CellDiff, Conflict, OutputFormat, Sudoku, SudokuError, Variant},
+};
 
-static RE_FLAT_GRID: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\d{81}").expect("Invalid regex pattern in the validator"));
+// Number of cells of the flat grid strings accepted by the API
+const FLAT_GRID_CELLS: usize = 81;
+
+// Server-side caps of the per-entry solve options
+const MAX_SOLUTIONS: usize = 16;
+const MAX_NODE_LIMIT: u64 = 100_000_000;
+const MAX_TIMEOUT_MS: u64 = 60_000;
+
+// Node limit of the entries selecting the first available column without giving one, the search
+// of a hard puzzle being practically endless without the scans for the most constrained column
+const FIRST_AVAILABLE_NODE_LIMIT: u64 = 1_000_000;
+
+// Fewest clues of any 9x9 puzzle with a unique solution (McGuire et al., 2012)
+const MIN_UNIQUE_CLUES: usize = 17;
+
+// Bitmask of the digits 1-9 allowed in the cells without pencil marks
+const ALL_CANDIDATES: u16 = 0b11_1111_1110;
+
+// Unpruned exact cover matrix of the classic 9x9 grids, built once by the first request and copied
+// for every entry solved by DLX instead of building its matrix from scratch
+static CLASSIC_TEMPLATE: OnceLock<Option<MatrixTemplate>> = OnceLock::new();
+
+/// Optional per-entry solve options, the missing fields fall back to the `SolveOptions` defaults.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryOptions {
+    pub max_solutions: Option<usize>,
+    pub node_limit: Option<u64>,
+    pub timeout_ms: Option<u64>,
+    pub include_propagated: Option<bool>,
+    #[serde(alias = "return_candidates")]
+    pub include_candidates: Option<bool>,
+    pub check_unique: Option<bool>,
+    pub pretty: Option<bool>,
+    pub include_diff: Option<bool>,
+    pub output_format: Option<OutputFormat>,
+    pub require_unique: Option<bool>,
+    pub include_stats: Option<bool>,
+    pub seed: Option<u64>,
+    pub propagate_only: Option<bool>,
+    pub detect_multiple: Option<bool>,
+    pub parallel: Option<bool>,
+    pub column_selection: Option<ColumnSelection>,
+}
+
+impl EntryOptions {
+    /// Validates the ranges of the given options and converts them into `SolveOptions`, the
+    /// missing fields falling back to `defaults` (i.e. the batch level options).
+    pub fn to_solve_options(&self, defaults: SolveOptions) -> Result<SolveOptions, ApiError> {
+        let max_solutions = self.max_solutions.unwrap_or(defaults.max_solutions);
+        if !(1..=MAX_SOLUTIONS).contains(&max_solutions) {
+            return Err(ApiError::InvalidOptions(format!(
+                "Option 'max_solutions' must be between 1 and {}, got {}",
+                MAX_SOLUTIONS, max_solutions
+            )));
+        }
+
+        if let Some(node_limit) = self.node_limit {
+            if !(1..=MAX_NODE_LIMIT).contains(&node_limit) {
+                return Err(ApiError::InvalidOptions(format!(
+                    "Option 'node_limit' must be between 1 and {}, got {}",
+                    MAX_NODE_LIMIT, node_limit
+                )));
+            }
+        }
+
+        if let Some(timeout_ms) = self.timeout_ms {
+            if !(1..=MAX_TIMEOUT_MS).contains(&timeout_ms) {
+                return Err(ApiError::InvalidOptions(format!(
+                    "Option 'timeout_ms' must be between 1 and {}, got {}",
+                    MAX_TIMEOUT_MS, timeout_ms
+                )));
+            }
+        }
+
+        let column_selection = self.column_selection.unwrap_or(defaults.column_selection);
+        let node_limit = match self.node_limit.or(defaults.node_limit) {
+            None if column_selection == ColumnSelection::FirstAvailable => {
+                Some(FIRST_AVAILABLE_NODE_LIMIT)
+            }
+            node_limit => node_limit,
+        };
+
+        Ok(SolveOptions {
+            max_solutions,
+            node_limit,
+            timeout_ms: self.timeout_ms.or(defaults.timeout_ms),
+            include_propagated: self
+                .include_propagated
+                .unwrap_or(defaults.include_propagated),
+            include_candidates: self
+                .include_candidates
+                .unwrap_or(defaults.include_candidates),
+            check_unique: self.check_unique.unwrap_or(defaults.check_unique),
+            pretty: self.pretty.unwrap_or(defaults.pretty),
+            include_diff: self.include_diff.unwrap_or(defaults.include_diff),
+            output_format: self.output_format.unwrap_or(defaults.output_format),
+            require_unique: self.require_unique.unwrap_or(defaults.require_unique),
+            include_stats: self.include_stats.unwrap_or(defaults.include_stats),
+            seed: self.seed.or(defaults.seed),
+            propagate_only: self.propagate_only.unwrap_or(defaults.propagate_only),
+            detect_multiple: self.detect_multiple.unwrap_or(defaults.detect_multiple),
+            parallel: self.parallel.unwrap_or(defaults.parallel),
+            column_selection,
+        })
+    }
+}
+
+/// Flat grid string of 81 digits, validated when parsed or deserialized so that a malformed
+/// string never reaches the solvers. Empty cells can be given as either '0', '.', or '-', and are
+/// normalized to '0'.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct GridString(String);
+
+impl GridString {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for GridString {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s.chars().count();
+
+        if cells != FLAT_GRID_CELLS {
+            return Err(ApiError::InvalidFormat(format!(
+                "The grid string must contain {} cells, found {}",
+                FLAT_GRID_CELLS, cells
+            )));
+        }
+
+        s.chars()
+            .enumerate()
+            .map(|(index, ch)| match ch {
+                '0'..='9' => Ok(ch),
+                '.' | '-' => Ok('0'),
+                _ => Err(ApiError::InvalidFormat(format!(
+                    "Invalid character '{}' at index {} of the grid string, the cells must be digits with the empty ones given as '0', '.', or '-'",
+                    ch, index
+                ))),
+            })
+            .collect::<Result<String, _>>()
+            .map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for GridString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(de::Error::custom)
+    }
+}
+
+impl fmt::Display for GridString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Grid of an entry, given either as a 1D string of 81 cells or as a 9x9 array of integers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum GridInput {
+    Flat(GridString),
+    Nested(Vec<Vec<u8>>),
+}
+
+// Dispatches on the JSON type directly instead of deriving the untagged representation, which
+// would replace the validation errors of `GridString` with a generic message.
+impl<'de> Deserialize<'de> for GridInput {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct GridInputVisitor;
+
+        impl<'de> de::Visitor<'de> for GridInputVisitor {
+            type Value = GridInput;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a string of {} cells or a 9x9 array of integers",
+                    FLAT_GRID_CELLS
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map(GridInput::Flat).map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                Vec::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(GridInput::Nested)
+            }
+        }
+
+        deserializer.deserialize_any(GridInputVisitor)
+    }
+}
+
+impl From<GridString> for GridInput {
+    fn from(value: GridString) -> Self {
+        GridInput::Flat(value)
+    }
+}
+
+impl From<Vec<Vec<u8>>> for GridInput {
+    fn from(value: Vec<Vec<u8>>) -> Self {
+        GridInput::Nested(value)
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Entry {
-    grid: String,
+    grid: GridInput,
     solver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    options: Option<EntryOptions>,
+    /// Pencil marks of the client, i.e. the allowed digits of the cells keyed by their 1-based
+    /// position (e.g. `r4c7`) like the `candidates` of the results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    candidates: Option<BTreeMap<String, Vec<u8>>>,
+    /// Rules of the puzzle, classic unless `x` for an X-Sudoku.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variant: Option<Variant>,
 }
 
 impl Entry {
     #[allow(dead_code)]
-    pub fn new(grid: String, solver: Option<String>) -> Self {
+    pub fn new(grid: impl Into<GridInput>, solver: Option<String>) -> Self {
         // Manual Entry creation should only be utilized in the unit and integration tests
-        Self { grid, solver }
+        Self {
+            grid: grid.into(),
+            solver,
+            options: None,
+            candidates: None,
+            variant: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_options(
+        grid: impl Into<GridInput>,
+        solver: Option<String>,
+        options: EntryOptions,
+    ) -> Self {
+        Self {
+            grid: grid.into(),
+            solver,
+            options: Some(options),
+            candidates: None,
+            variant: None,
+        }
+    }
+
+    /// Sets the pencil marks of the entry keyed by the 1-based positions of the cells.
+    #[allow(dead_code)]
+    pub fn with_candidates(mut self, candidates: BTreeMap<String, Vec<u8>>) -> Self {
+        self.candidates = Some(candidates);
+        self
+    }
+
+    /// Sets the rules of the puzzle of the entry.
+    #[allow(dead_code)]
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
+    pub fn grid(&self) -> &GridInput {
+        &self.grid
     }
 
     /// Simultaneously converts the `Entry` into a new `Sudoku` and validates the input format
     /// and predefined puzzle constraints. Returns `Ok(Sudoku)` if the conversion and validation
-    /// is successful, and `std::error::Error` if the either of the steps fail.
-    pub fn to_sudoku(&self) -> Result<Sudoku, ErrorResponse> {
-        if !RE_FLAT_GRID.is_match(&self.grid) {
-            debug!("Incoming request entry validation failed due to the input not matching the grid regex");
-
-            return Err(ErrorResponse::new(
-                "400",
-                String::from("The entry grid does not pass the regex validation, check the input format constraints"),
-            ));
+    /// is successful, and `ApiError` if the either of the steps fail.
+    pub fn to_sudoku(&self) -> Result<Sudoku, ApiError> {
+        let sudoku = match &self.grid {
+            GridInput::Flat(grid) => Sudoku::new(grid.as_str().to_owned()),
+            GridInput::Nested(grid) => Sudoku::from_grid(grid.clone()),
+        }
+        .map(|sudoku| sudoku.with_variant(self.variant.unwrap_or_default()))
+        .and_then(|sudoku| match sudoku.size() {
+            // The library supports the other n² sizes as well, but the API is limited to 9x9 grids,
+            // e.g. the clue bound is specific to them
+            9 => sudoku.check_constraints().map(|_| sudoku),
+            rows => Err(SudokuError::WrongRowCount { rows }),
+        })
+        .map_err(|e| {
+            debug!("Incoming request entry validation failed due to {}", e);
+
+            match e {
+                SudokuError::WrongCellCount { .. }
+                | SudokuError::WrongRowCount { .. }
+                | SudokuError::WrongRowLength { .. }
+                | SudokuError::SizeMismatch { .. }
+                | SudokuError::Matrix(ExactCoverError::MatrixTooLarge { .. }) => {
+                    ApiError::InvalidDimensions(e.to_string())
+                }
+                SudokuError::ConstraintViolation(conflicts) => {
+                    ApiError::ConstraintViolation(conflicts)
+                }
+                SudokuError::InvalidCharacter { .. }
+                | SudokuError::ValueOutOfRange { .. }
+                | SudokuError::PositionOutOfBounds { .. }
+                | SudokuError::InvalidDigit { .. }
+                | SudokuError::GivenCell { .. }
+                | SudokuError::InvalidPermutation { .. }
+                | SudokuError::UnsupportedVariant(_)
+                | SudokuError::Matrix(_) => ApiError::InvalidFormat(e.to_string()),
+            }
+        })?;
+
+        Ok(sudoku)
+    }
+
+    /// Converts the `Entry` into a new `Sudoku` like `to_sudoku`, and if `require_unique` is set,
+    /// additionally rejects the puzzles without a unique solution before they reach the solver:
+    /// ones with fewer than 17 clues outright with `ApiError::TooFewClues`, and the rest with
+    /// `ApiError::NotUnique` if a bounded probe finds a second solution. Puzzles the probe can't
+    /// decide within its budget are let through. The uniqueness is relative to the pencil marks
+    /// of the entry parsed by `candidate_masks` if any, which the probe is restricted to, and the
+    /// clues aren't counted then.
+    pub fn validate(
+        &self,
+        options: SolveOptions,
+        candidates: Option<&[u16; FLAT_GRID_CELLS]>,
+    ) -> Result<Sudoku, ApiError> {
+        let sudoku = self.to_sudoku()?;
+
+        if !options.require_unique {
+            return Ok(sudoku);
+        }
+
+        let clues = sudoku.clue_count();
+
+        if candidates.is_none() && clues < MIN_UNIQUE_CLUES {
+            debug!(
+                clues,
+                "Incoming request entry validation failed due to too few clues"
+            );
+            return Err(ApiError::TooFewClues { clues });
+        }
+
+        if Uniqueness::probe(&sudoku, candidates, options.node_limit) == Uniqueness::Multiple {
+            debug!("Incoming request entry validation failed due to multiple solutions");
+            return Err(ApiError::NotUnique);
+        }
+
+        Ok(sudoku)
+    }
+
+    /// Parses the requested solver type, falling back to `default` (i.e. the batch level solver)
+    /// if the field isn't included. Returns `ApiError::UnknownSolver` listing the valid options
+    /// if the name is not recognized.
+    pub fn solver_type(&self, default: SolverType) -> Result<SolverType, ApiError> {
+        match &self.solver {
+            Some(solver) => solver.parse::<SolverType>().map_err(|e| {
+                debug!("Incoming request entry validation failed due to an unknown solver");
+                ApiError::UnknownSolver(e)
+            }),
+            None => Ok(default),
+        }
+    }
+
+    /// Validates the solve options of the entry, falling back to `defaults` (i.e. the batch level
+    /// options) if the field isn't included.
+    pub fn solve_options(&self, defaults: SolveOptions) -> Result<SolveOptions, ApiError> {
+        match &self.options {
+            Some(options) => options.to_solve_options(defaults).inspect_err(|_| {
+                debug!("Incoming request entry validation failed due to invalid options")
+            }),
+            None => Ok(defaults),
+        }
+    }
+
+    /// Parses the pencil marks of the entry into the bitmasks of the allowed digits of every cell
+    /// in row-major order (see `Sudoku::candidates_for`), the cells without marks allowing any
+    /// digit. Returns `ApiError::InvalidFormat` if a position or a digit is outside of the grid.
+    pub fn candidate_masks(&self) -> Result<Option<[u16; FLAT_GRID_CELLS]>, ApiError> {
+        let Some(candidates) = &self.candidates else {
+            return Ok(None);
+        };
+        let mut masks = [ALL_CANDIDATES; FLAT_GRID_CELLS];
+
+        for (cell, digits) in candidates {
+            let (row, col) = parse_cell(cell).ok_or_else(|| {
+                debug!("Incoming request entry validation failed due to an invalid candidate cell");
+                ApiError::InvalidFormat(format!(
+                    "Invalid candidate cell '{}', expected its 1-based position such as 'r4c7'",
+                    cell
+                ))
+            })?;
+            let mut mask = 0;
+
+            for &digit in digits {
+                if !(1..=9).contains(&digit) {
+                    debug!("Incoming request entry validation failed due to an invalid candidate");
+                    return Err(ApiError::InvalidFormat(format!(
+                        "Invalid candidate {} of the cell '{}', the candidates must be digits 1-9",
+                        digit, cell
+                    )));
+                }
+
+                mask |= 1 << digit;
+            }
+
+            masks[row * 9 + col] = mask;
+        }
+
+        Ok(Some(masks))
+    }
+
+    /// Validates the entry and constructs a new `Solver` for it, `default_solver` and
+    /// `default_options` being used if the entry doesn't specify its own.
+    pub(crate) fn to_solver(
+        &self,
+        default_solver: SolverType,
+        default_options: SolveOptions,
+    ) -> Result<Solver, ApiError> {
+        let solver_type = self.solver_type(default_solver)?;
+        let options = self.solve_options(default_options)?;
+        let candidates = self.candidate_masks()?;
+
+        let sudoku = self.validate(options, candidates.as_ref())?;
+
+        let template = CLASSIC_TEMPLATE
+            .get_or_init(|| MatrixTemplate::new(9).ok())
+            .as_ref();
+
+        Solver::with_template(sudoku, solver_type, options, candidates, template).map_err(|e| {
+            error!(
+                "Internal error: Constructing the solver failed due to {}",
+                e
+            );
+            ApiError::Internal(String::from("Internal error while constructing the solver"))
+        })
+    }
+}
+
+/// Parses the 1-based position of a cell (e.g. `r4c7`) into its 0-based row and column, `None` if
+/// it's malformed or outside of the 9x9 grid.
+fn parse_cell(cell: &str) -> Option<(usize, usize)> {
+    let (row, col) = cell.strip_prefix('r')?.split_once('c')?;
+    let (row, col): (usize, usize) = (row.parse().ok()?, col.parse().ok()?);
+
+    ((1..=9).contains(&row) && (1..=9).contains(&col)).then(|| (row - 1, col - 1))
+}
+
+/// Entries of a batch request, deserialized from a plain JSON array. The error of an invalid entry
+/// is prefixed with its index, so that the JSON error handler can report which one was rejected.
+pub struct Entries(Vec<Entry>);
+
+impl Deref for Entries {
+    type Target = [Entry];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Entries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> de::Visitor<'de> for EntriesVisitor {
+            type Value = Entries;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an array of entries")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+
+                // Buffered as values first, the errors of which don't contain a position that
+                // would be repeated by the one appended to the outermost error
+                while let Some(value) = seq.next_element::<serde_json::Value>()? {
+                    let entry = Entry::deserialize(value).map_err(|e| {
+                        de::Error::custom(format_args!("invalid entry {}: {}", entries.len(), e))
+                    })?;
+                    entries.push(entry);
+                }
+
+                Ok(Entries(entries))
+            }
         }
 
-        let sudoku = match Sudoku::new(self.grid.clone()) {
-            Ok(sudoku) => sudoku,
-            Err(e) => return Err(ErrorResponse::new("400", e.to_string())),
-        };
+        deserializer.deserialize_seq(EntriesVisitor)
+    }
+}
+
+/// Aggregated statistics of a single metric over all the entries of a batch.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    total: u128,
+    avg: u128,
+    min: u128,
+    max: u128,
+    median: u128,
+}
+
+impl Summary {
+    /// Computes the statistics from the per-entry values. The average and median (mean of the two
+    /// middle values for even-sized batches) are rounded down to the nearest integer.
+    fn new(values: &[u128]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+
+        let len = sorted.len();
+        let total = sorted.iter().sum();
+        let median = match len {
+            0 => 0,
+            _ if len.is_multiple_of(2) => (sorted[len / 2 - 1] + sorted[len / 2]) / 2,
+            _ => sorted[len / 2],
+        };
+
+        Self {
+            total,
+            avg: total.checked_div(len as u128).unwrap_or(0),
+            min: sorted.first().copied().unwrap_or(0),
+            max: sorted.last().copied().unwrap_or(0),
+            median,
+        }
+    }
+}
+
+/// Per-batch statistics of the solver processes.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    cpu_time_us: Summary,
+    wall_time_us: Summary,
+    visited_nodes: Summary,
+}
+
+impl Stats {
+    fn new(cpu_times_us: &[u128], wall_times_us: &[u128], visited_nodes: &[u64]) -> Self {
+        let visited_nodes = visited_nodes
+            .iter()
+            .map(|&n| u128::from(n))
+            .collect::<Vec<u128>>();
+
+        Self {
+            cpu_time_us: Summary::new(cpu_times_us),
+            wall_time_us: Summary::new(wall_times_us),
+            visited_nodes: Summary::new(&visited_nodes),
+        }
+    }
+
+    /// Computes the statistics of the per-entry metrics of the solved entries.
+    fn from_results(results: &[EntryResult]) -> Self {
+        let cpu_times_us = results.iter().map(|r| r.cpu_time_us).collect::<Vec<u128>>();
+        let wall_times_us = results
+            .iter()
+            .map(|r| r.wall_time_us)
+            .collect::<Vec<u128>>();
+        let visited_nodes = results
+            .iter()
+            .map(|r| r.visited_nodes)
+            .collect::<Vec<u64>>();
+
+        Self::new(&cpu_times_us, &wall_times_us, &visited_nodes)
+    }
+}
+
+/// Solution(s) of a single solved entry, `solutions` replacing `solved` if the entry requested
+/// more than one solution with the `max_solutions` option. The entries left incomplete by the
+/// `propagate_only` option have `solved` set to `false` and the `partial` grid instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EntrySolution {
+    Single { solved: String },
+    Multiple { solutions: Vec<String> },
+    Partial { solved: bool, partial: String },
+}
+
+/// Result of a single solved entry of the batch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryResult {
+    /// Index of the entry in the request payload.
+    index: usize,
+    /// Original grid as it was given in the request, with the empty cells of a string as '0'.
+    grid: GridInput,
+    /// Stable digest of the puzzle as 16 hex digits, see `Sudoku::digest`.
+    digest: String,
+    #[serde(flatten)]
+    solution: EntrySolution,
+    /// Solver used for solving the entry, `auto` already resolved into the chosen backend.
+    solver: SolverType,
+    /// Solver requested by the entry or the batch, may differ from `solver` if it was `auto` or
+    /// `propagate_only` is set.
+    requested_solver: SolverType,
+    /// Solve options applied to the entry after validation and defaults.
+    options: SolveOptions,
+    cpu_time_us: u128,
+    wall_time_us: u128,
+    /// Decisions of the solver, i.e. the same as `node_counts.decisions`.
+    visited_nodes: u64,
+    /// Breakdown of the work done by the solver in the units shared by both of them.
+    node_counts: NodeCounts,
+    /// Whether the grid was already solved, returned as is without running the solver.
+    already_solved: bool,
+    /// Whether the puzzle has a unique solution, `unknown` unless `check_unique` is set.
+    unique: Uniqueness,
+    /// Difficulty estimate derived from the effort of the puzzle, regardless of the used solver.
+    difficulty: Difficulty,
+    /// Grid after the constraint propagation in the `output_format`, only included if
+    /// `include_propagated` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    propagated: Option<String>,
+    /// Remaining candidates of the empty cells after the constraint propagation keyed by their
+    /// 1-based position (e.g. `r4c7`), only included if `include_candidates` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    candidates: Option<BTreeMap<String, Vec<u8>>>,
+    /// Pretty-printed (first) solution for human consumption, only included if `pretty` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pretty: Option<String>,
+    /// Cells filled in by the solver in the (first) solution, only included if `include_diff` is
+    /// set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    diff: Option<Vec<CellDiff>>,
+    /// Structural statistics of the puzzle, only included if `include_stats` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stats: Option<PuzzleStats>,
+    /// Counters of the backtracking search, only included for DFS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    search: Option<SearchStats>,
+    /// Counters of the dancing links and the size of the exact cover matrix, only included for
+    /// DLX.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dlx: Option<DlxStats>,
+}
+
+impl EntryResult {
+    pub(crate) fn new(index: usize, grid: GridInput, solver: &Solver) -> Self {
+        let options = solver.options();
+        let propagation = solver.propagation();
+        let format = options.output_format.options();
+
+        Self {
+            index,
+            grid,
+            digest: format!("{:016x}", solver.puzzle().digest()),
+            solution: match options.max_solutions {
+                _ if solver.is_incomplete() => EntrySolution::Partial {
+                    solved: false,
+                    partial: solver.get_sudoku().to_string_with(format),
+                },
+                1 => EntrySolution::Single {
+                    solved: solver.get_sudoku().to_string_with(format),
+                },
+                _ => EntrySolution::Multiple {
+                    solutions: solver
+                        .solutions()
+                        .iter()
+                        .map(|solution| match options.output_format {
+                            OutputFormat::FlatZero => solution.clone(),
+                            // Solutions are always written by the solvers in the canonical form
+                            _ => solution
+                                .parse::<Sudoku>()
+                                .map_or_else(|_| solution.clone(), |s| s.to_string_with(format)),
+                        })
+                        .collect(),
+                },
+            },
+            solver: solver.solver_type(),
+            requested_solver: solver.requested_solver_type(),
+            options: solver.options(),
+            cpu_time_us: solver.total_cpu_time_us(),
+            wall_time_us: solver.total_wall_time_us(),
+            visited_nodes: solver.total_visited_nodes(),
+            node_counts: solver.metadata().node_counts(),
+            already_solved: solver.metadata().already_solved(),
+            unique: solver.uniqueness(),
+            // Only computed for the results, as it propagates the puzzle once more
+            difficulty: rating(
+                solver.metadata(),
+                solver.clues(),
+                &Effort::of(solver.puzzle()),
+            ),
+            propagated: propagation
+                .filter(|_| options.include_propagated)
+                .map(|p| p.grid.to_string_with(format)),
+            candidates: propagation.filter(|_| options.include_candidates).map(|p| {
+                p.candidates
+                    .iter()
+                    .map(|(pos, c)| (format!("r{}c{}", pos.row() + 1, pos.col() + 1), c.clone()))
+                    .collect()
+            }),
+            pretty: options
+                .pretty
+                .then(|| solver.get_sudoku().to_pretty_string()),
+            diff: options
+                .include_diff
+                .then(|| Sudoku::filled_by_solver(solver.get_sudoku(), solver.puzzle())),
+            stats: options.include_stats.then(|| solver.puzzle().statistics()),
+            search: solver.metadata().search_stats(),
+            dlx: solver.metadata().dlx_stats(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl EntryResult {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn grid(&self) -> &GridInput {
+        &self.grid
+    }
+
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+
+    /// Returns the (first) solution of the entry, empty for an incomplete entry.
+    pub fn solved(&self) -> &str {
+        self.solutions().first().map_or("", String::as_str)
+    }
+
+    /// Returns all the distinct solutions of the entry, i.e. a single one unless more were
+    /// requested with the `max_solutions` option, and none for an incomplete entry.
+    pub fn solutions(&self) -> &[String] {
+        match &self.solution {
+            EntrySolution::Single { solved } => std::slice::from_ref(solved),
+            EntrySolution::Multiple { solutions } => solutions,
+            EntrySolution::Partial { .. } => &[],
+        }
+    }
+
+    /// Returns the partially filled grid of an entry left incomplete by the `propagate_only`
+    /// option, `None` for the solved ones.
+    pub fn partial(&self) -> Option<&str> {
+        match &self.solution {
+            EntrySolution::Partial { partial, .. } => Some(partial),
+            _ => None,
+        }
+    }
+
+    pub fn solver(&self) -> SolverType {
+        self.solver
+    }
+
+    pub fn requested_solver(&self) -> SolverType {
+        self.requested_solver
+    }
+
+    pub fn options(&self) -> SolveOptions {
+        self.options
+    }
+
+    pub fn cpu_time_us(&self) -> u128 {
+        self.cpu_time_us
+    }
+
+    pub fn wall_time_us(&self) -> u128 {
+        self.wall_time_us
+    }
+
+    pub fn visited_nodes(&self) -> u64 {
+        self.visited_nodes
+    }
+
+    pub fn node_counts(&self) -> NodeCounts {
+        self.node_counts
+    }
+
+    pub fn already_solved(&self) -> bool {
+        self.already_solved
+    }
+
+    pub fn unique(&self) -> Uniqueness {
+        self.unique
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn propagated(&self) -> Option<&str> {
+        self.propagated.as_deref()
+    }
+
+    pub fn candidates(&self) -> Option<&BTreeMap<String, Vec<u8>>> {
+        self.candidates.as_ref()
+    }
+
+    pub fn pretty(&self) -> Option<&str> {
+        self.pretty.as_deref()
+    }
+
+    pub fn diff(&self) -> Option<&[CellDiff]> {
+        self.diff.as_deref()
+    }
+
+    pub fn stats(&self) -> Option<&PuzzleStats> {
+        self.stats.as_ref()
+    }
+
+    pub fn search(&self) -> Option<SearchStats> {
+        self.search
+    }
+
+    pub fn dlx(&self) -> Option<DlxStats> {
+        self.dlx
+    }
+}
+
+/// Error of a single failed entry of the batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryError {
+    /// Index of the entry in the request payload.
+    index: usize,
+    /// Original grid as it was given in the request, with the empty cells of a string as '0'.
+    grid: GridInput,
+    error: ErrorResponse,
+}
+
+impl EntryError {
+    fn new(index: usize, grid: GridInput, error: &ApiError) -> Self {
+        Self {
+            index,
+            grid,
+            error: ErrorResponse::from(error),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl EntryError {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn grid(&self) -> &GridInput {
+        &self.grid
+    }
+
+    pub fn error(&self) -> &ErrorResponse {
+        &self.error
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SuccessResponse {
+    solved: Vec<String>,
+    results: Vec<EntryResult>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<EntryError>,
+    total_cpu_us: u128,
+    avg_cpu_us: u128,
+    // Deprecated in favor of `stats`, kept for backwards compatibility until the next release
+    total_cpu_ms: u128,
+    avg_cpu_ms: u128,
+    avg_visited_nodes: u64,
+    stats: Stats,
+}
+
+impl SuccessResponse {
+    fn new(results: Vec<EntryResult>, errors: Vec<EntryError>) -> Self {
+        let stats = Stats::from_results(&results);
+        let total_cpu_us = stats.cpu_time_us.total;
+        let avg_cpu_us = stats.cpu_time_us.avg;
+        let avg_visited_nodes = stats.visited_nodes.avg as u64;
+
+        Self {
+            solved: results
+                .iter()
+                .filter(|r| r.partial().is_none())
+                .map(|r| r.solved().to_owned())
+                .collect(),
+            results,
+            errors,
+            total_cpu_us,
+            avg_cpu_us,
+            // Derived from the microseconds, so sub-millisecond solves legitimately read as zero
+            total_cpu_ms: total_cpu_us / 1000,
+            avg_cpu_ms: avg_cpu_us / 1000,
+            avg_visited_nodes,
+            stats,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_solved(&self) -> Vec<String> {
+        self.solved.clone()
+    }
+
+    #[allow(dead_code)]
+    pub fn get_results(&self) -> &[EntryResult] {
+        &self.results
+    }
+
+    #[allow(dead_code)]
+    pub fn get_errors(&self) -> &[EntryError] {
+        &self.errors
+    }
+
+    #[allow(dead_code)]
+    pub fn get_stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    #[allow(dead_code)]
+    pub fn get_total_cpu_us(&self) -> u128 {
+        self.total_cpu_us
+    }
+
+    #[allow(dead_code)]
+    pub fn get_avg_cpu_us(&self) -> u128 {
+        self.avg_cpu_us
+    }
+
+    #[allow(dead_code)]
+    pub fn get_total_cpu_ms(&self) -> u128 {
+        self.total_cpu_ms
+    }
+}
+
+/// Response schema of the `/v1/solve` endpoint. Compared to the legacy `SuccessResponse` the top
+/// level `solved` array and the per-batch timing fields are dropped in favor of the per-entry
+/// `results` and the `stats`.
+#[derive(Serialize, Deserialize)]
+pub struct V1Response {
+    results: Vec<EntryResult>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<EntryError>,
+    stats: Stats,
+}
+
+impl V1Response {
+    fn new(results: Vec<EntryResult>, errors: Vec<EntryError>) -> Self {
+        Self {
+            stats: Stats::from_results(&results),
+            results,
+            errors,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn get_results(&self) -> &[EntryResult] {
+        &self.results
+    }
+
+    #[allow(dead_code)]
+    pub fn get_errors(&self) -> &[EntryError] {
+        &self.errors
+    }
+
+    #[allow(dead_code)]
+    pub fn get_stats(&self) -> &Stats {
+        &self.stats
+    }
+}
+
+/// Structured form of a single solved entry for the library consumers and tests, exposing the
+/// (first) solution as a 9x9 grid of digits alongside its string form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolvedPuzzle {
+    /// Index of the entry in the request payload.
+    index: usize,
+    solved: String,
+    grid: Vec<Vec<u8>>,
+}
+
+impl From<&EntryResult> for SolvedPuzzle {
+    fn from(result: &EntryResult) -> Self {
+        let solved = result.solved().to_owned();
+
+        Self {
+            index: result.index,
+            // Solutions are always written by the solvers in the canonical string form
+            grid: solved
+                .parse::<Sudoku>()
+                .map(|sudoku| sudoku.clone_grid())
+                .unwrap_or_default(),
+            solved,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl SolvedPuzzle {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn solved(&self) -> &str {
+        &self.solved
+    }
+
+    pub fn grid(&self) -> &[Vec<u8>] {
+        &self.grid
+    }
+}
 
-        if !sudoku.is_valid(None) {
-            debug!("Incoming request entry validation failed due to the puzzle not meeting the default Sudoku constraints");
+/// Solved puzzles of a batch response in their structured form, convertible from the responses
+/// of either endpoint version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Response {
+    data: Vec<SolvedPuzzle>,
+}
 
-            return Err(ErrorResponse::new(
-                "400",
-                String::from("Default Sudoku constraints not met"),
-            ));
+impl Response {
+    fn from_results(results: &[EntryResult]) -> Self {
+        Self {
+            data: results
+                .iter()
+                .filter(|r| r.partial().is_none())
+                .map(SolvedPuzzle::from)
+                .collect(),
         }
+    }
 
-        Ok(sudoku)
+    #[allow(dead_code)]
+    pub fn data(&self) -> &[SolvedPuzzle] {
+        &self.data
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SuccessResponse {
-    solved: Vec<String>,
-    total_cpu_ms: u128,
-    avg_cpu_ms: u128,
-    avg_visited_nodes: u64,
+impl From<&SuccessResponse> for Response {
+    fn from(res: &SuccessResponse) -> Self {
+        Self::from_results(&res.results)
+    }
 }
 
-impl SuccessResponse {
-    fn new(solved_grids: Vec<Vec<Vec<u8>>>, cpu_times: Vec<u128>, visited_nodes: Vec<u64>) -> Self {
-        let total_cpu_ms = cpu_times.iter().sum();
-        let avg_cpu_ms = total_cpu_ms / cpu_times.len() as u128;
-        let avg_visited_nodes = visited_nodes.iter().sum();
+impl From<&V1Response> for Response {
+    fn from(res: &V1Response) -> Self {
+        Self::from_results(&res.results)
+    }
+}
 
+/// Valid entry of a `/validate` request with the structural statistics of its puzzle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatedEntry {
+    /// Index of the entry in the request payload.
+    index: usize,
+    /// Original grid as it was given in the request, with the empty cells of a string as '0'.
+    grid: GridInput,
+    /// Stable digest of the puzzle as 16 hex digits, see `Sudoku::digest`.
+    digest: String,
+    stats: PuzzleStats,
+}
+
+impl ValidatedEntry {
+    fn new(index: usize, grid: GridInput, sudoku: &Sudoku) -> Self {
         Self {
-            solved: solved_grids.into_iter().map(Self::grid_to_string).collect(),
-            total_cpu_ms,
-            avg_cpu_ms,
-            avg_visited_nodes,
+            index,
+            grid,
+            digest: format!("{:016x}", sudoku.digest()),
+            stats: sudoku.statistics(),
         }
     }
+}
+
+#[allow(dead_code)]
+impl ValidatedEntry {
+    pub fn index(&self) -> usize {
+        self.index
+    }
 
-    /// Converts the `Vec<Vec<u8>>` grid into a 1D `String` to be consistent with the input format.
-    fn grid_to_string(grid: Vec<Vec<u8>>) -> String {
-        grid.iter()
-            .flat_map(|row| row.iter())
-            .map(|&num| num.to_string())
-            .collect()
+    pub fn digest(&self) -> &str {
+        &self.digest
     }
 
-    #[allow(dead_code)]
-    pub fn get_solved(&self) -> Vec<String> {
-        self.solved.clone()
+    pub fn stats(&self) -> &PuzzleStats {
+        &self.stats
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Response of the `/validate` endpoint, i.e. the valid entries with their statistics and the
+/// rejected ones with the same errors as the solver endpoints would report.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationResponse {
+    results: Vec<ValidatedEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<EntryError>,
+}
+
+#[allow(dead_code)]
+impl ValidationResponse {
+    pub fn results(&self) -> &[ValidatedEntry] {
+        &self.results
+    }
+
+    pub fn errors(&self) -> &[EntryError] {
+        &self.errors
+    }
+}
+
+/// JSON body of the error responses, constructed from an `ApiError`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorResponse {
-    code: String,
+    /// HTTP status code of the error, serialized as a string for backwards compatibility.
+    #[serde(
+        serialize_with = "serialize_status",
+        deserialize_with = "deserialize_status"
+    )]
+    code: StatusCode,
+    kind: String,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_s: Option<u64>,
+    /// Usage of the API key if the batch was rejected due to an exceeded quota.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quota: Option<QuotaUsage>,
+    /// Repeated digits of the grid if the entry violates the default Sudoku constraints.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    conflicts: Vec<Conflict>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<EntryError>,
 }
 
-impl fmt::Debug for ErrorResponse {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{{ file: {}, line: {} }}", file!(), line!())
-    }
+fn serialize_status<S: Serializer>(status: &StatusCode, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(status.as_str())
 }
 
-impl ErrorResponse {
-    fn new(code: &str, message: String) -> Self {
-        Self {
-            code: code.to_owned(),
-            message,
-        }
-    }
+fn deserialize_status<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StatusCode, D::Error> {
+    let code = String::deserialize(deserializer)?;
+    StatusCode::from_str(&code).map_err(de::Error::custom)
+}
 
-    fn status_str(&self) -> &str {
-        &self.code
+impl ErrorResponse {
+    pub fn status(&self) -> StatusCode {
+        self.code
     }
 
-    pub fn status(&self) -> Result<StatusCode, HttpError> {
-        Ok(StatusCode::from_str(&self.code)?)
+    #[allow(dead_code)]
+    pub fn kind(&self) -> &str {
+        &self.kind
     }
 
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    #[allow(dead_code)]
+    pub fn retry_after_s(&self) -> Option<u64> {
+        self.retry_after_s
+    }
+
+    #[allow(dead_code)]
+    pub fn quota(&self) -> Option<QuotaUsage> {
+        self.quota
+    }
+
+    #[allow(dead_code)]
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    #[allow(dead_code)]
+    pub fn errors(&self) -> &[EntryError] {
+        &self.errors
+    }
+
+    /// Finalizes the response builder with the JSON body of the error, setting the `Retry-After`
+    /// header if the error contains a retry interval.
+    pub fn respond_with(self, mut builder: HttpResponseBuilder) -> HttpResponse {
+        if let Some(retry_after_s) = self.retry_after_s {
+            builder.insert_header((RETRY_AFTER, retry_after_s));
+        }
+
+        builder.json(self)
+    }
+}
+
+impl From<&ApiError> for ErrorResponse {
+    fn from(value: &ApiError) -> Self {
+        let retry_after_s = match value {
+            ApiError::RateLimited { retry_after_s } => Some(*retry_after_s),
+            ApiError::QuotaExceeded(usage) => Some(usage.reset_s),
+            _ => None,
+        };
+        let quota = match value {
+            ApiError::QuotaExceeded(usage) => Some(*usage),
+            _ => None,
+        };
+        // Single entry batches surface the conflicts of the entry like its message
+        let conflicts = match value {
+            ApiError::ConstraintViolation(conflicts) => conflicts.clone(),
+            ApiError::BatchFailed(errors) if errors.len() == 1 => errors[0].error.conflicts.clone(),
+            _ => Vec::new(),
+        };
+        let mut errors = match value {
+            ApiError::BatchFailed(errors) => errors.clone(),
+            _ => Vec::new(),
+        };
+        errors.sort_by_key(|e| e.index);
+
+        // Failed batches inherit the kind of the entry errors if all of them share the same kind
+        let kind = match errors.split_first() {
+            Some((first, rest)) if rest.iter().all(|e| e.error.kind == first.error.kind) => {
+                first.error.kind.clone()
+            }
+            _ => value.kind().to_owned(),
+        };
+
+        Self {
+            code: actix_web::ResponseError::status_code(value),
+            kind,
+            message: value.to_string(),
+            retry_after_s,
+            quota,
+            conflicts,
+            errors,
+        }
+    }
+}
+
+/// Query parameters of the solver endpoint.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SolveQuery {
+    /// Default solver of the batch, overridden by the `solver` field of the individual entries.
+    solver: Option<String>,
+    /// Defaults of the batch, overridden by the `options` of the individual entries.
+    include_propagated: Option<bool>,
+    include_candidates: Option<bool>,
+    pretty: Option<bool>,
+    include_diff: Option<bool>,
+    output_format: Option<OutputFormat>,
+    require_unique: Option<bool>,
+    include_stats: Option<bool>,
 }
 
-impl From<ErrorResponse> for HttpResponse {
-    fn from(value: ErrorResponse) -> Self {
-        match value.status_str() {
-            "400" => HttpResponse::BadRequest().json(value),
-            "500" => HttpResponse::InternalServerError().json(value),
-            "501" => HttpResponse::NotImplemented().json(value),
-            _ => HttpResponse::InternalServerError().json(value),
+impl SolveQuery {
+    /// Parses the batch level solver type, defaulting to `SolverType::default()` if the parameter
+    /// isn't included.
+    fn solver_type(&self) -> Result<SolverType, ApiError> {
+        match &self.solver {
+            Some(solver) => solver.parse::<SolverType>().map_err(|e| {
+                debug!("Incoming request validation failed due to an unknown batch solver");
+                ApiError::UnknownSolver(e)
+            }),
+            None => Ok(SolverType::default()),
+        }
+    }
+
+    /// Returns the batch level solve options, i.e. `SolveOptions::default()` with the flags of
+    /// the query parameters applied.
+    fn default_options(&self) -> SolveOptions {
+        let defaults = SolveOptions::default();
+
+        SolveOptions {
+            include_propagated: self
+                .include_propagated
+                .unwrap_or(defaults.include_propagated),
+            include_candidates: self
+                .include_candidates
+                .unwrap_or(defaults.include_candidates),
+            pretty: self.pretty.unwrap_or(defaults.pretty),
+            include_diff: self.include_diff.unwrap_or(defaults.include_diff),
+            output_format: self.output_format.unwrap_or(defaults.output_format),
+            require_unique: self.require_unique.unwrap_or(defaults.require_unique),
+            include_stats: self.include_stats.unwrap_or(defaults.include_stats),
+            ..defaults
         }
     }
 }
 
-#[post("/solve")]
-pub async fn solve(entries: web::Json<Vec<Entry>>) -> impl Responder {
-    let mut solvers = Vec::new();
+/// Default limit of the JSON request payloads in bytes, same as the default of actix.
+pub const DEFAULT_PAYLOAD_LIMIT: usize = 2_097_152;
 
-    for e in entries.iter() {
-        let default_type_str = String::from("dfs");
-        let solver_type_str = e.solver.as_ref().unwrap_or(&default_type_str);
+/// Creates the JSON extractor config of the request payloads with the given `limit` in bytes,
+/// the payload errors being converted into `ErrorResponse` bodies: `413` for payloads exceeding
+/// the limit and `400` for malformed ones.
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(move |err, _req| {
+            let err = match err {
+                JsonPayloadError::Overflow { .. }
+                | JsonPayloadError::OverflowKnownLength { .. } => {
+                    debug!("Incoming request rejected due to the payload exceeding the limit");
+                    ApiError::PayloadTooLarge { limit }
+                }
+                JsonPayloadError::Deserialize(err) if err.is_data() => {
+                    debug!("Incoming request rejected due to an invalid entry");
+                    ApiError::InvalidFormat(format!("Invalid JSON payload: {}", err))
+                }
+                err => {
+                    debug!("Incoming request rejected due to a malformed payload");
+                    ApiError::InvalidFormat(format!("Malformed JSON payload: {}", err))
+                }
+            };
 
-        match e.to_sudoku() {
-            Ok(sudoku) => solvers.push(Solver::new(sudoku, solver_type_str)),
-            Err(e) => {
-                return e.into();
-            }
+            err.into()
+        })
+}
+
+/// Creates the span of a single puzzle solve, the `outcome` field being recorded once the solver
+/// process finishes.
+pub(crate) fn solve_span(index: usize, solver: &Solver) -> Span {
+    info_span!(
+        "solve",
+        index,
+        solver = %solver.solver_type(),
+        clues = solver.clues(),
+        outcome = field::Empty,
+    )
+}
+
+/// Internal result model of a solved batch, serialized by the versioned response types.
+struct SolvedBatch {
+    results: Vec<EntryResult>,
+    errors: Vec<EntryError>,
+}
+
+/// Validates and solves the batch, shared by all the versions of the solver endpoint. Returns
+/// `ApiError::BatchFailed` if none of the entries could be solved. Only the valid entries are
+/// charged to the quota of the caller if the `quotas` are enabled, once the validation is over.
+/// Once the `shutdown` is triggered, the running solve is aborted and the queued entries are
+/// skipped, each reported as `ApiError::Cancelled` alongside the partial results.
+fn solve_batch(
+    req: &HttpRequest,
+    query: &SolveQuery,
+    entries: &[Entry],
+    shutdown: Option<&Shutdown>,
+    quotas: Option<&web::Data<Quotas>>,
+) -> Result<SolvedBatch, ApiError> {
+    let default_solver = query.solver_type()?;
+    let default_options = query.default_options();
+
+    if entries.is_empty() {
+        debug!("Incoming request validation failed due to an empty batch");
+        return Err(ApiError::EmptyBatch);
+    }
+
+    let mut solvers = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, e) in entries.iter().enumerate() {
+        match e.to_solver(default_solver, default_options) {
+            Ok(solver) => solvers.push((i, solver)),
+            Err(err) => errors.push(EntryError::new(i, e.grid.clone(), &err)),
         };
     }
 
+    // The rejected entries are never solved, so they don't count against the quota
+    Quotas::consume_if_enabled(quotas, req, solvers.len())?;
+
     info!("Starting the synchronous solvers");
-    let mut solved = Vec::new();
-    let mut cpu_times = Vec::new();
-    let mut visited_nodes = Vec::new();
+    let mut results = Vec::new();
+
+    for (i, mut s) in solvers {
+        let span = solve_span(i, &s);
+        let _entered = span.enter();
+
+        if let Some(shutdown) = shutdown {
+            if shutdown.is_triggered() {
+                debug!("Skipping the entry due to the server shutting down");
+                span.record("outcome", field::debug(Outcome::Cancelled));
+                errors.push(EntryError::new(
+                    i,
+                    entries[i].grid.clone(),
+                    &ApiError::Cancelled { index: i },
+                ));
+                continue;
+            }
+
+            s.set_cancel_flag(shutdown.flag());
+        }
+
+        let outcome = s.solve();
+        span.record("outcome", field::debug(outcome));
+
+        let err = match outcome {
+            Outcome::Solved => {
+                info!(
+                    cpu_time_us = s.total_cpu_time_us(),
+                    visited_nodes = s.total_visited_nodes(),
+                    "Solver found a solution"
+                );
 
-    for mut s in solvers {
-        match s.solve() {
-            true => {
-                let total_cpu_time = s.total_cpu_time_ms();
-                info!("Solver found a solution in {} ms", total_cpu_time);
+                results.push(EntryResult::new(i, entries[i].grid.clone(), &s));
+                continue;
+            }
+            Outcome::Incomplete => {
+                info!("Propagation left the puzzle incomplete");
 
-                solved.push(s.get_inner_grid());
-                cpu_times.push(total_cpu_time);
-                visited_nodes.push(s.total_visited_nodes());
+                results.push(EntryResult::new(i, entries[i].grid.clone(), &s));
+                continue;
+            }
+            Outcome::Unsolvable => {
+                match s.contradiction() {
+                    Some(contradiction) => debug!(
+                        cell = %contradiction.pos,
+                        "Propagation proved the puzzle unsolvable without searching"
+                    ),
+                    None => debug!("Solver exhausted the search space without a solution"),
+                }
+                ApiError::Unsolvable { index: i }
+            }
+            Outcome::InternalError => {
+                error!("Internal error: Solver returned an invalid solution");
+                ApiError::Internal(format!(
+                    "Internal error while solving the puzzle of entry {}",
+                    i
+                ))
+            }
+            Outcome::Cancelled => {
+                info!("Solver was cancelled");
+                ApiError::Cancelled { index: i }
+            }
+            Outcome::LimitReached(limit) => {
+                info!(%limit, "Solver reached its limit");
+                ApiError::LimitReached { index: i, limit }
             }
-            false => error!("Internal error: Solver failed despite the input Sudoku being valid"),
         };
+
+        errors.push(EntryError::new(i, entries[i].grid.clone(), &err));
+    }
+
+    if results.is_empty() {
+        info!("All entries of the batch failed, responding to client with an error");
+        return Err(ApiError::BatchFailed(errors));
+    }
+
+    errors.sort_by_key(|e| e.index);
+
+    Ok(SolvedBatch { results, errors })
+}
+
+/// Legacy solver endpoint responding with the `SuccessResponse` schema.
+#[post("/solve")]
+pub async fn solve(
+    req: HttpRequest,
+    query: web::Query<SolveQuery>,
+    entries: web::Json<Entries>,
+    shutdown: Option<web::Data<Shutdown>>,
+    quotas: Option<web::Data<Quotas>>,
+) -> Result<HttpResponse, ApiError> {
+    let batch = solve_batch(
+        &req,
+        &query,
+        &entries,
+        shutdown.as_ref().map(|s| s.get_ref()),
+        quotas.as_ref(),
+    )?;
+
+    Ok(HttpResponse::Ok().json(SuccessResponse::new(batch.results, batch.errors)))
+}
+
+/// Solver endpoint of the `/v1` scope responding with the `V1Response` schema.
+#[post("/solve")]
+pub async fn solve_v1(
+    req: HttpRequest,
+    query: web::Query<SolveQuery>,
+    entries: web::Json<Entries>,
+    shutdown: Option<web::Data<Shutdown>>,
+    quotas: Option<web::Data<Quotas>>,
+) -> Result<HttpResponse, ApiError> {
+    let batch = solve_batch(
+        &req,
+        &query,
+        &entries,
+        shutdown.as_ref().map(|s| s.get_ref()),
+        quotas.as_ref(),
+    )?;
+
+    Ok(HttpResponse::Ok().json(V1Response::new(batch.results, batch.errors)))
+}
+
+/// Legacy DFS endpoint, i.e. `/solve` with the `dfs` solver as the default of the batch,
+/// responding with the structured `Response` schema of the solved puzzles.
+#[post("/sdfs")]
+pub async fn solve_dfs(
+    req: HttpRequest,
+    query: web::Query<SolveQuery>,
+    entries: web::Json<Entries>,
+    shutdown: Option<web::Data<Shutdown>>,
+    quotas: Option<web::Data<Quotas>>,
+) -> Result<HttpResponse, ApiError> {
+    let mut query = query.into_inner();
+    query.solver.get_or_insert_with(|| String::from("dfs"));
+
+    let batch = solve_batch(
+        &req,
+        &query,
+        &entries,
+        shutdown.as_ref().map(|s| s.get_ref()),
+        quotas.as_ref(),
+    )?;
+
+    Ok(HttpResponse::Ok().json(Response::from_results(&batch.results)))
+}
+
+/// Validates the entries of the batch like the solver endpoints without solving them, responding
+/// with the structural statistics of the valid puzzles. The entries aren't charged to any quota.
+#[post("/validate")]
+pub async fn validate(
+    query: web::Query<SolveQuery>,
+    entries: web::Json<Entries>,
+) -> Result<HttpResponse, ApiError> {
+    let default_options = query.default_options();
+
+    if entries.is_empty() {
+        debug!("Incoming request validation failed due to an empty batch");
+        return Err(ApiError::EmptyBatch);
     }
 
-    if solved.is_empty() {
-        error!("All solver iterations failed internally, responding to client with status 500");
-        return HttpResponse::InternalServerError().finish();
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, e) in entries.iter().enumerate() {
+        let validated = e.solve_options(default_options).and_then(|options| {
+            let candidates = e.candidate_masks()?;
+            e.validate(options, candidates.as_ref())
+        });
+
+        match validated {
+            Ok(sudoku) => results.push(ValidatedEntry::new(i, e.grid.clone(), &sudoku)),
+            Err(err) => errors.push(EntryError::new(i, e.grid.clone(), &err)),
+        }
     }
 
-    HttpResponse::Ok().json(SuccessResponse::new(solved, cpu_times, visited_nodes))
+    Ok(HttpResponse::Ok().json(ValidationResponse { results, errors }))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use actix_web::ResponseError;
 
-    #[test]
-    #[should_panic]
-    fn test_alphanumeric_grid() {
-        let valid = Entry {
-            grid: String::from(
-                "00080905160020000C30000000001000003008A90000000000040040003060B000051000000000000",
-            ),
-            solver: None,
-        };
-        valid.to_sudoku().unwrap();
+    use crate::sudoku::{Pos, Unit};
+
+    fn flat(raw: &str) -> GridInput {
+        GridInput::Flat(raw.parse().unwrap())
+    }
+
+    fn flat_zeros() -> GridInput {
+        flat(&"0".repeat(81))
     }
 
     #[test]
-    #[should_panic]
-    fn test_short_grid() {
-        let valid = Entry {
-            grid: String::from(
-                "0008051600200000300000000010000030080900000000000400400030600000051000000000",
-            ),
-            solver: None,
-        };
-        valid.to_sudoku().unwrap();
+    fn test_grid_string() {
+        let cases = [
+            // Alphanumeric
+            "00080905160020000C30000000001000003008A90000000000040040003060B000051000000000000",
+            // Short
+            "0008051600200000300000000010000030080900000000000400400030600000051000000000",
+            // Long
+            "0000000370020000500100000000002001040000016003004000007000630000000002000000800001",
+            // Embedded
+            "grid=000000037002000050010000000000200104000001600300400000700063000000000200000080000;",
+        ];
+
+        for raw in cases {
+            let e = raw.parse::<GridString>().unwrap_err();
+            assert!(matches!(e, ApiError::InvalidFormat(_)));
+        }
+
+        let e = "1".repeat(80).parse::<GridString>().unwrap_err();
+        assert!(e.to_string().contains("found 80"));
     }
 
     #[test]
-    #[should_panic]
     fn test_invalid_constraints() {
-        let valid = Entry {
-            grid: String::from(
+        let invalid = Entry {
+            grid: flat(
                 "830070000600195000098000060800060003400803001700020006060000280000419005000080079",
             ),
             solver: None,
+            options: None,
+            candidates: None,
+            variant: None,
         };
-        valid.to_sudoku().unwrap();
+        let ApiError::ConstraintViolation(conflicts) = invalid.to_sudoku().unwrap_err() else {
+            panic!("Expected a constraint violation");
+        };
+        let units = conflicts
+            .iter()
+            .map(|c| (c.unit, c.index, c.value, c.cells.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            units,
+            vec![
+                (Unit::Column, 0, 8, [(0, 0), (3, 0)].map(Pos::from).to_vec()),
+                (Unit::Square, 0, 8, [(0, 0), (2, 2)].map(Pos::from).to_vec()),
+            ]
+        );
     }
 
     #[test]
     fn test_nonexistent_solver() {
         let malformed = Entry {
-            grid: String::from(
+            grid: flat(
                 "000000037002000050010000000000200104000001600300400000700063000000000200000080000",
             ),
             solver: Some(String::from("nonexistent")),
+            options: None,
+            candidates: None,
+            variant: None,
+        };
+        let e = malformed.solver_type(SolverType::default()).unwrap_err();
+
+        assert!(matches!(e, ApiError::UnknownSolver(_)));
+        assert!(e.to_string().contains("'dfs'") && e.to_string().contains("'dlx'"));
+    }
+
+    #[test]
+    fn test_case_insensitive_solver() {
+        let valid = Entry {
+            grid: flat(
+                "000000037002000050010000000000200104000001600300400000700063000000000200000080000",
+            ),
+            solver: Some(String::from("DFS")),
+            options: None,
+            candidates: None,
+            variant: None,
         };
-        malformed.to_sudoku().unwrap();
+
+        assert_eq!(
+            valid.solver_type(SolverType::default()).unwrap(),
+            SolverType::Dfs
+        );
     }
 
     #[test]
     fn test_valid_grid() {
         let valid = Entry {
-            grid: String::from(
+            grid: flat(
                 "000000037002000050010000000000200104000001600300400000700063000000000200000080000",
             ),
             solver: None,
+            options: None,
+            candidates: None,
+            variant: None,
         };
         valid.to_sudoku().unwrap();
     }
+
+    #[test]
+    fn test_require_unique() {
+        let proper =
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+        let sparse =
+            "000000037002000050010000000000200104000001600300400000700063000000000200000000000";
+        let improper =
+            "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+        let strict = SolveOptions {
+            require_unique: true,
+            ..SolveOptions::default()
+        };
+
+        // Permissive by default
+        for raw in [proper, sparse, improper] {
+            assert!(Entry::new(flat(raw), None)
+                .validate(SolveOptions::default(), None)
+                .is_ok());
+        }
+
+        assert!(Entry::new(flat(proper), None)
+            .validate(strict, None)
+            .is_ok());
+
+        let e = Entry::new(flat(sparse), None)
+            .validate(strict, None)
+            .unwrap_err();
+        assert!(matches!(e, ApiError::TooFewClues { clues: 16 }));
+        assert_eq!(e.status_code(), StatusCode::BAD_REQUEST);
+        assert!(e.to_string().contains("at least 17"));
+
+        let e = Entry::new(flat(improper), None)
+            .validate(strict, None)
+            .unwrap_err();
+        assert!(matches!(e, ApiError::NotUnique));
+        assert_eq!(e.kind(), "not_unique");
+        assert_eq!(e.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        // Unique relative to the pencil marks, which rule out the other digit of the rectangle
+        let mut marked = Entry::new(flat(improper), None);
+        marked.candidates = Some(BTreeMap::from([(String::from("r1c1"), vec![5])]));
+        let masks = marked.candidate_masks().unwrap();
+        assert!(marked.validate(strict, masks.as_ref()).is_ok());
+        // The entry itself is never parsed for the marks again
+        assert!(matches!(
+            marked.validate(strict, None).unwrap_err(),
+            ApiError::NotUnique
+        ));
+
+        marked.candidates = Some(BTreeMap::from([(String::from("r1c1"), vec![5, 6])]));
+        let masks = marked.candidate_masks().unwrap();
+        assert!(matches!(
+            marked.validate(strict, masks.as_ref()).unwrap_err(),
+            ApiError::NotUnique
+        ));
+
+        // Not rejected for the few clues, as the pencil marks may still make it unique
+        let marked = Entry::new(flat(sparse), None);
+        assert!(!matches!(
+            marked.validate(strict, Some(&[ALL_CANDIDATES; FLAT_GRID_CELLS])),
+            Err(ApiError::TooFewClues { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dot_grid() {
+        let dotted = Entry {
+            grid: flat(
+                ".......37..2....5..1..........2..1.4.....16..3..4.....7...63.........2......8....",
+            ),
+            solver: None,
+            options: None,
+            candidates: None,
+            variant: None,
+        };
+        let dashed = Entry {
+            grid: flat(
+                "-------37--2----5--1----------2--1-4-----16--3--4-----7---63---------2------8----",
+            ),
+            solver: None,
+            options: None,
+            candidates: None,
+            variant: None,
+        };
+        let expected =
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+
+        assert_eq!(dotted.grid(), &flat(expected));
+        assert_eq!(dotted.to_sudoku().unwrap().grid_to_string(), expected);
+        assert_eq!(dashed.to_sudoku().unwrap().grid_to_string(), expected);
+    }
+
+    #[test]
+    fn test_nested_grid() {
+        let raw =
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+        let nested = Sudoku::new(raw.to_owned()).unwrap().clone_grid();
+        let valid = Entry::new(nested, None);
+
+        assert_eq!(valid.to_sudoku().unwrap().grid_to_string(), raw);
+    }
+
+    #[test]
+    fn test_ragged_nested_grid() {
+        let mut ragged = vec![vec![0; 9]; 9];
+        ragged[3].push(0);
+        let short = vec![vec![0; 9]; 8];
+
+        for grid in [ragged, short] {
+            let e = Entry::new(grid, None).to_sudoku().unwrap_err();
+            assert!(matches!(e, ApiError::InvalidDimensions(_)));
+        }
+    }
+
+    #[test]
+    fn test_hex_nested_grid() {
+        let e = Entry::new(vec![vec![0; 16]; 16], None)
+            .to_sudoku()
+            .unwrap_err();
+        assert!(matches!(e, ApiError::InvalidDimensions(_)));
+    }
+
+    #[test]
+    fn test_out_of_range_nested_grid() {
+        let mut grid = vec![vec![0; 9]; 9];
+        grid[8][8] = 10;
+
+        let e = Entry::new(grid, None).to_sudoku().unwrap_err();
+        assert!(matches!(e, ApiError::InvalidFormat(_)));
+    }
+
+    #[test]
+    fn test_grid_input_deserialization() {
+        let raw = "-".repeat(81);
+        let flat: GridInput = serde_json::from_str(&format!(r#""{}""#, raw)).unwrap();
+        let nested: GridInput = serde_json::from_str("[[1, 2], [3]]").unwrap();
+
+        assert_eq!(flat, flat_zeros());
+        assert_eq!(nested, GridInput::Nested(vec![vec![1, 2], vec![3]]));
+
+        let e = serde_json::from_str::<GridInput>(r#""123""#).unwrap_err();
+        assert!(e.to_string().contains("found 3"));
+        assert!(serde_json::from_str::<GridInput>("123").is_err());
+    }
+
+    #[test]
+    fn test_entries_deserialization() {
+        let valid = format!(r#"[{{"grid": "{}"}}, {{"grid": [[0]]}}]"#, "0".repeat(81));
+        let entries: Entries = serde_json::from_str(&valid).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].grid(), &flat_zeros());
+
+        let invalid = format!(r#"[{{"grid": "{}"}}, {{"grid": "123"}}]"#, "0".repeat(81));
+        let Err(e) = serde_json::from_str::<Entries>(&invalid) else {
+            panic!("Expected the invalid entry to be rejected");
+        };
+
+        assert!(e.is_data());
+        assert!(e.to_string().starts_with("invalid entry 1: "));
+    }
+
+    #[test]
+    fn test_candidate_masks() {
+        let json = format!(
+            r#"{{"grid": "{}", "candidates": {{"r1c1": [1, 9], "r9c8": []}}}}"#,
+            "0".repeat(81)
+        );
+        let entry: Entry = serde_json::from_str(&json).unwrap();
+        let masks = entry.candidate_masks().unwrap().unwrap();
+
+        assert_eq!(masks[0], 0b10_0000_0010);
+        assert_eq!(masks[79], 0);
+        assert!(masks[1..79].iter().all(|&mask| mask == ALL_CANDIDATES));
+        assert!(matches!(
+            Entry::new(flat_zeros(), None).candidate_masks(),
+            Ok(None)
+        ));
+
+        for (cell, digits) in [("r0c1", vec![1]), ("c1r1", vec![1]), ("r1c1", vec![10])] {
+            let entry = Entry::new(flat_zeros(), None)
+                .with_candidates(BTreeMap::from([(cell.to_owned(), digits)]));
+            assert!(matches!(
+                entry.candidate_masks(),
+                Err(ApiError::InvalidFormat(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = EntryOptions::default()
+            .to_solve_options(SolveOptions::default())
+            .unwrap();
+        assert_eq!(options, SolveOptions::default());
+
+        let partial = EntryOptions {
+            node_limit: Some(5000),
+            ..Default::default()
+        };
+        let options = partial.to_solve_options(SolveOptions::default()).unwrap();
+
+        assert_eq!(options.max_solutions, 1);
+        assert_eq!(options.node_limit, Some(5000));
+        assert_eq!(options.timeout_ms, None);
+        assert!(!options.include_propagated);
+        assert!(!options.include_candidates);
+
+        // The first available column is never searched without a node limit
+        let mut first_available = EntryOptions {
+            column_selection: Some(ColumnSelection::FirstAvailable),
+            ..Default::default()
+        };
+        let options = first_available
+            .to_solve_options(SolveOptions::default())
+            .unwrap();
+        assert_eq!(options.node_limit, Some(FIRST_AVAILABLE_NODE_LIMIT));
+
+        first_available.node_limit = Some(5000);
+        let options = first_available
+            .to_solve_options(SolveOptions::default())
+            .unwrap();
+        assert_eq!(options.node_limit, Some(5000));
+    }
+
+    #[test]
+    fn test_options_ranges() {
+        let invalid = [
+            EntryOptions {
+                max_solutions: Some(0),
+                ..Default::default()
+            },
+            EntryOptions {
+                max_solutions: Some(MAX_SOLUTIONS + 1),
+                ..Default::default()
+            },
+            EntryOptions {
+                node_limit: Some(0),
+                ..Default::default()
+            },
+            EntryOptions {
+                timeout_ms: Some(MAX_TIMEOUT_MS + 1),
+                ..Default::default()
+            },
+        ];
+
+        for options in invalid {
+            let e = options
+                .to_solve_options(SolveOptions::default())
+                .unwrap_err();
+            assert!(matches!(e, ApiError::InvalidOptions(_)));
+        }
+
+        let valid = EntryOptions {
+            max_solutions: Some(MAX_SOLUTIONS),
+            node_limit: Some(MAX_NODE_LIMIT),
+            timeout_ms: Some(1),
+            include_propagated: Some(true),
+            include_candidates: Some(true),
+            check_unique: Some(true),
+            pretty: Some(true),
+            include_diff: Some(true),
+            output_format: Some(OutputFormat::Multiline),
+            require_unique: Some(true),
+            include_stats: Some(true),
+            seed: Some(u64::MAX),
+            propagate_only: Some(true),
+            detect_multiple: Some(true),
+            parallel: Some(true),
+            column_selection: Some(ColumnSelection::MinCountEarlyExit),
+        };
+        assert!(valid.to_solve_options(SolveOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_batch_stats() {
+        let stats = Stats::new(&[120, 30, 4500, 75], &[130, 40, 4600, 90], &[7, 1, 9, 3]);
+
+        assert_eq!(
+            stats.cpu_time_us,
+            Summary {
+                total: 4725,
+                avg: 1181,
+                min: 30,
+                max: 4500,
+                median: 97
+            }
+        );
+        assert_eq!(
+            stats.wall_time_us,
+            Summary {
+                total: 4860,
+                avg: 1215,
+                min: 40,
+                max: 4600,
+                median: 110
+            }
+        );
+        assert_eq!(
+            stats.visited_nodes,
+            Summary {
+                total: 20,
+                avg: 5,
+                min: 1,
+                max: 9,
+                median: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_batch_stats_odd() {
+        let stats = Stats::new(&[10, 50, 20], &[10, 60, 30], &[300, 100, 200]);
+
+        assert_eq!(stats.cpu_time_us.median, 20);
+        assert_eq!(stats.wall_time_us.median, 30);
+        assert_eq!(stats.visited_nodes.median, 200);
+        assert_eq!(stats.visited_nodes.avg, 200);
+    }
+
+    #[test]
+    fn test_batch_stats_single_entry() {
+        let res = SuccessResponse::new(
+            vec![EntryResult {
+                index: 0,
+                grid: flat_zeros(),
+                digest: format!("{:016x}", 0),
+                solution: EntrySolution::Single {
+                    solved: String::from("1").repeat(81),
+                },
+                solver: SolverType::Dlx,
+                requested_solver: SolverType::Dlx,
+                options: SolveOptions::default(),
+                cpu_time_us: 2500,
+                wall_time_us: 2600,
+                visited_nodes: 42,
+                node_counts: NodeCounts::default(),
+                already_solved: false,
+                unique: Uniqueness::Unknown,
+                difficulty: Difficulty::default(),
+                propagated: None,
+                candidates: None,
+                pretty: None,
+                diff: None,
+                stats: None,
+                search: None,
+                dlx: None,
+            }],
+            Vec::new(),
+        );
+        let single = Summary {
+            total: 42,
+            avg: 42,
+            min: 42,
+            max: 42,
+            median: 42,
+        };
+
+        assert_eq!(res.get_stats().visited_nodes, single);
+        assert_eq!(res.get_stats().cpu_time_us.median, 2500);
+        assert_eq!(res.total_cpu_us, 2500);
+        assert_eq!(res.avg_cpu_us, 2500);
+        assert_eq!(res.total_cpu_ms, 2);
+        assert_eq!(res.avg_cpu_ms, 2);
+        assert_eq!(res.avg_visited_nodes, 42);
+    }
+
+    #[test]
+    fn test_batch_stats_empty() {
+        let res = SuccessResponse::new(Vec::new(), Vec::new());
+        let empty = Summary {
+            total: 0,
+            avg: 0,
+            min: 0,
+            max: 0,
+            median: 0,
+        };
+
+        assert!(res.get_solved().is_empty());
+        assert_eq!(res.get_stats().cpu_time_us, empty);
+        assert_eq!(res.get_stats().visited_nodes, empty);
+        assert_eq!(res.total_cpu_ms, 0);
+        assert_eq!(res.avg_cpu_ms, 0);
+        assert_eq!(res.avg_visited_nodes, 0);
+    }
 }
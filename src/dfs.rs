@@ -1,21 +1,296 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
-    fmt::Debug,
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+    fmt::{self, Debug},
+    iter, mem,
+    time::{Duration, Instant},
 };
 
-use log::debug;
+use bitflags::bitflags;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
 
 use crate::{
-    solver::{macros::skip_fail_option, SudokuSolver},
-    sudoku::Sudoku,
+    solver::{macros::skip_fail_option, NodeCounts, ProgressReporter, SudokuSolver, Uniqueness},
+    sudoku::{Pos, Sudoku, Unit},
 };
 
+/// Failure of the constraint propagation, proving the puzzle unsolvable without any search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    /// Empty cell whose domain was emptied first, or the first empty cell of a unit left without
+    /// any place for one of its missing digits.
+    pub pos: Pos,
+}
+
+// Number of visited nodes between the checks of the deadline
+pub(crate) const DEADLINE_CHECK_INTERVAL_NODES: u64 = 256;
+
+/// Limit of `DfsOptions` which stopped the search before it finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchLimit {
+    Nodes,
+    Time,
+}
+
+impl fmt::Display for SearchLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchLimit::Nodes => write!(f, "node"),
+            SearchLimit::Time => write!(f, "time"),
+        }
+    }
+}
+
+/// Bounds of the work done by a single `DfsSolver::solve` call, unlimited by default, and the
+/// randomization of the search.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DfsOptions {
+    /// Maximum number of nodes to visit during the search, counted across the restarts.
+    pub max_nodes: Option<u64>,
+    /// Maximum duration of the solving process, the deadline being set once it starts.
+    pub timeout: Option<Duration>,
+    /// Seed of the RNG shuffling the order in which the values of each cell are tried, the values
+    /// being tried in ascending order without it. The search is deterministic given the seed.
+    pub seed: Option<u64>,
+    /// Restarts of the shuffled search, ignored without a `seed`.
+    pub restarts: Option<RestartPolicy>,
+    /// Whether to stop after the propagation without any search, leaving the grid partially
+    /// filled unless the propagation alone solves it.
+    pub propagate_only: bool,
+    /// Node budget of the search for a second solution continued after the first one, which
+    /// isn't searched for if `None`. The grid keeps the first solution either way.
+    pub second_solution_budget: Option<u64>,
+}
+
+/// Policy of abandoning the shuffled search after visiting `nodes` nodes without a solution and
+/// starting it over from the root, the RNG continuing with a different shuffle. The search is
+/// left to finish after `max_restarts` restarts, so it stays complete.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub nodes: u64,
+    pub max_restarts: u32,
+}
+
+/// Counters of the work done by the search besides the visited nodes, reported in the metadata.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchStats {
+    /// Value assignments undone on backtracking, the ones rolled back by a failed forward
+    /// checking included, but not the ones abandoned by a stopped search or a restart.
+    pub backtracks: u64,
+    /// Deepest level of the search reached, i.e. the most cells assigned by the search at once.
+    pub max_depth: usize,
+    /// Arcs revised by AC-3, during the propagation as well as by MAC.
+    pub revisions: u64,
+    /// Values removed from the domains of the peers of the assigned cells by forward checking.
+    pub fc_prunings: u64,
+}
+
+/// Number of applications of each of the propagation techniques besides AC-3, reported in the
+/// metadata for rating the difficulty.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TechniqueCounts {
+    /// Digits placed as the only cell of a unit able to hold them.
+    pub hidden_singles: usize,
+    /// Pairs of cells of a unit sharing the same two candidates, which eliminated them from the
+    /// other cells of the unit.
+    pub naked_pairs: usize,
+    /// Triples of cells of a unit holding only three candidates between them, which eliminated
+    /// them from the other cells of the unit.
+    pub naked_triples: usize,
+    /// Digits confined to a single row or column within a square, which eliminated them from the
+    /// rest of the line.
+    pub pointing_pairs: usize,
+    /// Digits confined to a single square within a row or column, which eliminated them from the
+    /// rest of the square.
+    pub box_line_reductions: usize,
+}
+
+bitflags! {
+    /// Propagation techniques applied by `DfsSolver` besides AC-3, which can be toggled
+    /// individually to probe which of them a puzzle requires. All of them are enabled by default.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Techniques: u8 {
+        const HIDDEN_SINGLES = 1;
+        const NAKED_PAIRS = 1 << 1;
+        const NAKED_TRIPLES = 1 << 2;
+        const POINTING_PAIRS = 1 << 3;
+        const BOX_LINE_REDUCTION = 1 << 4;
+    }
+}
+
+impl Default for Techniques {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Heuristic picking the next cell to branch on, all of them preferring the smallest domains
+/// (MRV) and differing in how the ties are broken.
+#[allow(dead_code)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CellOrdering {
+    /// Picks the first of the tied cells in row-major order.
+    Mrv,
+    /// Picks the tied cell with the most empty peers, the first one in row-major order among
+    /// those.
+    #[default]
+    MrvDegree,
+    /// Picks the cell with the smallest ratio of the domain size to the number of empty peers
+    /// instead, a cell without any empty peers having an infinite one.
+    DomOverDegree,
+}
+
+/// Propagation following each assignment of the search. MAC prunes far more branches on the hard
+/// puzzles, but the revisions of every node cost more than they save on the easy ones, most of
+/// which the propagation before the search already resolves.
+#[allow(dead_code)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationLevel {
+    /// Only removes the value from the domains of the empty peers of the assigned cell.
+    #[default]
+    ForwardChecking,
+    /// Maintains arc consistency by continuing the forward checking with the AC-3 worklist of
+    /// the arcs into the pruned peers until the fixpoint.
+    Mac,
+}
+
+/// Candidate digits of a single cell as a bitmask in the layout of `Sudoku::candidates_for`, i.e.
+/// bit `d` being set for the digit `d`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Domain(u32);
+
+impl Domain {
+    /// Constructs the domain from a `Sudoku::candidates_for` bitmask, bit `0` (the empty value)
+    /// being ignored.
+    pub fn from_mask(mask: u32) -> Self {
+        Self(mask & !1)
+    }
+
+    pub fn contains(self, digit: u8) -> bool {
+        self.0 & (1 << digit) != 0
+    }
+
+    /// Adds the digit to the domain, returning `true` if it wasn't included yet.
+    pub fn insert(&mut self, digit: u8) -> bool {
+        let inserted = self.0 & (1 << digit) == 0;
+        self.0 |= 1 << digit;
+        inserted
+    }
+
+    /// Removes the digit from the domain, returning `true` if it was included.
+    pub fn remove(&mut self, digit: u8) -> bool {
+        let removed = self.0 & (1 << digit) != 0;
+        self.0 &= !(1 << digit);
+        removed
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the digits included in either of the domains.
+    pub fn union(self, other: Domain) -> Domain {
+        Self(self.0 | other.0)
+    }
+
+    /// Returns the only digit of the domain, `None` if it's empty or has more than one.
+    pub fn single(self) -> Option<u8> {
+        (self.len() == 1).then(|| self.0.trailing_zeros() as u8)
+    }
+
+    /// Iterates over the digits of the domain in ascending order.
+    pub fn iter(self) -> impl Iterator<Item = u8> {
+        let mut mask = self.0;
+
+        iter::from_fn(move || {
+            let digit = (mask != 0).then(|| mask.trailing_zeros() as u8)?;
+            mask &= mask - 1;
+            Some(digit)
+        })
+    }
+}
+
+impl FromIterator<u8> for Domain {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        let mut domain = Self::default();
+
+        for digit in iter {
+            domain.insert(digit);
+        }
+
+        domain
+    }
+}
+
+/// Node of the explicit DFS stack, branching on the values of a single cell.
+#[derive(Debug)]
+struct Frame {
+    pos: Pos,
+    /// Values of the cell left to try, in ascending order.
+    remaining: Domain,
+    /// Value being explored below the frame and the trail mark to undo its assignment with.
+    assigned: Option<(u8, usize)>,
+}
+
 #[derive(Debug)]
 pub struct DfsSolver {
     sudoku: Sudoku,
-    related_cells: BTreeMap<(usize, usize), BTreeSet<(usize, usize)>>,
-    possible_values: BTreeMap<(usize, usize), BTreeSet<u8>>,
+    /// Domains of the cells in row-major order, `None` for the filled ones.
+    possible_values: Vec<Option<Domain>>,
+    /// Number of the empty peers of each cell in row-major order, i.e. the degrees used to break
+    /// the MRV ties. Kept up to date as the domains are taken and restored.
+    empty_peers: Vec<u8>,
+    ordering: CellOrdering,
+    level: PropagationLevel,
+    /// Values removed by MAC during the search on top of the forward checking.
+    mac_prunings: u64,
+    /// Undo trail of the `(cell, value)` pairs removed from the domains during the search, each
+    /// assignment popping back to its mark on backtracking.
+    trail: Vec<(Pos, u8)>,
+    /// Peak length of the trail, reported in the metadata.
+    max_trail_len: usize,
+    /// Contradiction found by the propagation of the last `solve` call, if any.
+    contradiction: Option<Contradiction>,
+    /// Techniques enabled in the propagation.
+    enabled: Techniques,
+    techniques: TechniqueCounts,
+    stats: SearchStats,
+    /// Value assignments tried during the search, i.e. the decisions of `NodeCounts`.
     visited_nodes: u64,
+    propagations: u64,
+    scans: u64,
+    /// Current depth of the DFS, reported to the progress hook.
+    depth: usize,
+    reporter: ProgressReporter,
+    max_nodes: Option<u64>,
+    timeout: Option<Duration>,
+    /// Deadline of the running `solve` call derived from the `timeout`.
+    deadline: Option<Instant>,
+    /// Limit which stopped the search of the last `solve` call, if any.
+    limit_reached: Option<SearchLimit>,
+    seed: Option<u64>,
+    /// RNG of the value order, reseeded from the `seed` by each `solve` call.
+    rng: Option<StdRng>,
+    restart_policy: Option<RestartPolicy>,
+    /// Number of restarts done by the last `solve` call.
+    restarts: u32,
+    propagate_only: bool,
+    /// Whether the last `solve` call stopped after the propagation with empty cells left.
+    incomplete: bool,
+    second_solution_budget: Option<u64>,
+    /// Whether the search for a second solution was stopped by its budget or a limit.
+    budget_exceeded: bool,
+    /// Uniqueness of the solution found by the last `solve` call, only determined if the second
+    /// solution was searched for.
+    uniqueness: Uniqueness,
 }
 
 impl SudokuSolver for DfsSolver {
@@ -27,8 +302,17 @@ impl SudokuSolver for DfsSolver {
     /// https://en.wikipedia.org/wiki/Depth-first_search
     /// https://en.wikipedia.org/wiki/Look-ahead_(backtracking)
     fn solve(&mut self) -> (bool, u64) {
-        self.ac3();
-        (self.dfs(Self::init_unseen()), self.visited_nodes)
+        let budget = self.second_solution_budget;
+        let found = self.search(if budget.is_some() { 2 } else { 1 }, budget);
+        self.uniqueness = match found {
+            2 => Uniqueness::Multiple,
+            1 if budget.is_some() && !self.budget_exceeded && !self.is_cancelled() => {
+                Uniqueness::Unique
+            }
+            _ => Uniqueness::Unknown,
+        };
+
+        (found > 0, self.visited_nodes)
     }
 
     /// Returns the inner grid. Notably doesn't check whether the solving process has finished and
@@ -37,224 +321,1498 @@ impl SudokuSolver for DfsSolver {
         self.sudoku.clone_grid()
     }
 
+    fn get_sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
     /// Returns the inner grid as a 1D `String`. Notably doesn't check whether the solving process
     /// has finished and might return unexpected results.
     fn grid_to_string(&self) -> String {
         self.sudoku.grid_to_string()
     }
+
+    fn set_progress_reporter(&mut self, reporter: ProgressReporter) {
+        self.reporter = reporter;
+    }
+
+    fn node_counts(&self) -> NodeCounts {
+        NodeCounts {
+            decisions: self.visited_nodes,
+            propagations: self.propagations,
+            scans: self.scans,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.reporter.is_cancelled()
+    }
+
+    fn max_trail_len(&self) -> usize {
+        self.max_trail_len
+    }
+
+    fn contradiction(&self) -> Option<Contradiction> {
+        self.contradiction
+    }
+
+    fn techniques(&self) -> TechniqueCounts {
+        self.techniques
+    }
+
+    fn mac_prunings(&self) -> u64 {
+        self.mac_prunings
+    }
+
+    fn limit_reached(&self) -> Option<SearchLimit> {
+        self.limit_reached
+    }
+
+    fn search_stats(&self) -> Option<SearchStats> {
+        Some(self.stats)
+    }
+
+    fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    fn uniqueness(&self) -> Uniqueness {
+        self.uniqueness
+    }
 }
 
 impl DfsSolver {
     pub fn new(sudoku: Sudoku) -> Self {
-        let grid = sudoku.clone_grid();
-        let possible_values = Self::init_domains(&grid);
-        let related_cells = Self::calculate_relations();
+        Self::with_options(sudoku, DfsOptions::default())
+    }
+
+    /// Constructs the solver with the search bounded by the `options`.
+    pub fn with_options(sudoku: Sudoku, options: DfsOptions) -> Self {
+        let possible_values = Self::init_domains(&sudoku);
+        let empty_peers = Pos::all(sudoku.size())
+            .map(|pos| {
+                sudoku
+                    .peers(pos)
+                    .iter()
+                    .filter(|peer| possible_values[peer.index(sudoku.size())].is_some())
+                    .count() as u8
+            })
+            .collect();
 
         Self {
             sudoku,
-            related_cells,
             possible_values,
+            empty_peers,
+            ordering: CellOrdering::default(),
+            level: PropagationLevel::default(),
+            mac_prunings: 0,
+            trail: Vec::new(),
+            max_trail_len: 0,
+            contradiction: None,
+            enabled: Techniques::default(),
+            techniques: TechniqueCounts::default(),
+            stats: SearchStats::default(),
+            propagations: 0,
+            scans: 0,
             visited_nodes: 0,
+            depth: 0,
+            reporter: ProgressReporter::default(),
+            max_nodes: options.max_nodes,
+            timeout: options.timeout,
+            deadline: None,
+            limit_reached: None,
+            seed: options.seed,
+            rng: None,
+            restart_policy: options.restarts,
+            restarts: 0,
+            propagate_only: options.propagate_only,
+            incomplete: false,
+            second_solution_budget: options.second_solution_budget,
+            budget_exceeded: false,
+            uniqueness: Uniqueness::Unknown,
         }
     }
 
+    /// Applies only the constraint propagation without continuing with the search, the
+    /// resulting grid and domains can be inspected with `get_inner_grid` and `candidates`. AC-3
+    /// and the enabled techniques are interleaved until none of them makes progress, each of them
+    /// being only applied once the ones before it are exhausted. Fails with the `Contradiction`
+    /// if the domain of a cell was emptied.
+    pub fn propagate(&mut self) -> Result<(), Contradiction> {
+        loop {
+            self.ac3()?;
+
+            if self.hidden_singles()? == 0 && self.naked_subsets() == 0 && self.intersections() == 0
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Counts the distinct solutions of the Sudoku up to `limit` by continuing the search after
+    /// each solution, the grid ending up holding the first one found. The count covers only the
+    /// part of the search done if it's stopped by a limit of the `DfsOptions` or cancelled.
+    #[allow(dead_code)]
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        self.search(limit, None)
+    }
+
+    /// Runs the propagation and the search for up to `limit` solutions, the search being given
+    /// up after `second_budget` nodes past the first solution if set.
+    fn search(&mut self, limit: usize, second_budget: Option<u64>) -> usize {
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        self.limit_reached = None;
+        self.budget_exceeded = false;
+        self.rng = self.seed.map(StdRng::seed_from_u64);
+        self.restarts = 0;
+        self.incomplete = false;
+
+        if limit == 0 {
+            return 0;
+        }
+
+        // A cell without any legal digit left makes the puzzle unsolvable without searching
+        if let Err(contradiction) = self.propagate() {
+            debug!(cell = ?contradiction.pos, "Propagation proved the puzzle unsolvable");
+            self.contradiction = Some(contradiction);
+            return 0;
+        }
+
+        if self.propagate_only {
+            self.incomplete = !self.sudoku.is_solved();
+            return usize::from(!self.incomplete);
+        }
+
+        self.dfs(limit, second_budget)
+    }
+
+    /// Sets the techniques applied by the propagation besides AC-3.
+    #[allow(dead_code)]
+    pub fn set_techniques(&mut self, techniques: Techniques) {
+        self.enabled = techniques;
+    }
+
+    /// Sets the heuristic picking the next cell to branch on.
+    #[allow(dead_code)]
+    pub fn set_ordering(&mut self, ordering: CellOrdering) {
+        self.ordering = ordering;
+    }
+
+    /// Sets the propagation following each assignment of the search.
+    #[allow(dead_code)]
+    pub fn set_propagation_level(&mut self, level: PropagationLevel) {
+        self.level = level;
+    }
+
+    /// Returns the remaining candidates of the empty cells keyed by their position.
+    pub fn candidates(&self) -> BTreeMap<Pos, Vec<u8>> {
+        self.domains()
+            .map(|(pos, domain)| (pos, domain.iter().collect()))
+            .collect()
+    }
+
+    /// Iterates over the domains of the empty cells in row-major order.
+    fn domains(&self) -> impl Iterator<Item = (Pos, Domain)> + '_ {
+        let size = self.sudoku.size();
+
+        self.possible_values
+            .iter()
+            .enumerate()
+            .filter_map(move |(k, domain)| Some((Pos::from_index(k, size), (*domain)?)))
+    }
+
+    fn domain(&self, pos: Pos) -> Option<Domain> {
+        self.possible_values[pos.index(self.sudoku.size())]
+    }
+
+    fn domain_mut(&mut self, pos: Pos) -> Option<&mut Domain> {
+        let k = pos.index(self.sudoku.size());
+        self.possible_values[k].as_mut()
+    }
+
+    /// Removes the domain of the cell once it's assigned, returning the removed one. The cell
+    /// no longer counts towards the degrees of its peers.
+    fn take_domain(&mut self, pos: Pos) -> Option<Domain> {
+        let size = self.sudoku.size();
+        let domain = self.possible_values[pos.index(size)].take();
+
+        if domain.is_some() {
+            for peer in self.sudoku.peers(pos) {
+                self.empty_peers[peer.index(size)] -= 1;
+            }
+        }
+
+        domain
+    }
+
+    fn degree(&self, pos: Pos) -> usize {
+        self.empty_peers[pos.index(self.sudoku.size())] as usize
+    }
+
     /// Performs the Arc Consistency Algorithm #3 (AC-3) to reduce the domain D(X) of possible
     /// values for a specific grid cell X iteratively for all cells of the Sudoku grid. This
     /// implementation only applies the most basic constraints of Sudoku (i.e. checks the
     /// rows, columns, and squares for duplicates), and doesn't delve into more sophisticated
     /// constraints like naked twins, single candidates, and so on.
-    fn ac3(&mut self) {
-        let mut empty_pos_vec = self
-            .possible_values
-            .keys()
-            .cloned()
-            .collect::<Vec<(usize, usize)>>();
-
-        while let Some(cur_pos) = empty_pos_vec.pop() {
-            let binding = self.related_cells.clone();
-            let r_all = binding.get(&cur_pos).unwrap();
-
-            if self.arc_reduce(&cur_pos, r_all) {
-                // Update all the related cells if any pruning was done
-                let unsolved = r_all
+    ///
+    /// The worklist holds the directed arcs (X, Y) between the empty peers, the domains being
+    /// already seeded consistent with the filled cells. Revising X against Y re-enqueues the arcs
+    /// (Z, X) of the other peers Z of X whenever the domain of X shrinks. Once the fixpoint is
+    /// reached, the cells left with a single candidate are assigned to the grid. Fails if a
+    /// domain is emptied, in which case the domains are left as they were at that point.
+    fn ac3(&mut self) -> Result<(), Contradiction> {
+        if let Some((pos, _)) = self.domains().find(|(_, domain)| domain.is_empty()) {
+            return Err(Contradiction { pos });
+        }
+
+        let arcs = self
+            .domains()
+            .flat_map(|(x, _)| self.arcs_into(x, None))
+            .collect::<VecDeque<(Pos, Pos)>>();
+        self.revise_arcs(arcs, false)?;
+
+        let singles = self
+            .domains()
+            .filter_map(|(pos, domain)| Some((pos, domain.single()?)))
+            .collect::<Vec<(Pos, u8)>>();
+
+        for (pos, value) in singles {
+            debug!(cell = ?pos, value, "Assigned the single candidate of the cell with AC-3");
+            self.sudoku.set_grid_value(pos, value);
+            self.take_domain(pos);
+        }
+
+        Ok(())
+    }
+
+    /// Narrows the domain of a cell down to a digit if it's the only cell of a row, column, or
+    /// square (or diagonal of an X-Sudoku) able to hold the digit, leaving the pruning of its
+    /// peers and the assignment to the next AC-3 pass. Returns the number of the narrowed cells,
+    /// or fails if a unit has no place left for one of its missing digits.
+    fn hidden_singles(&mut self) -> Result<usize, Contradiction> {
+        let size = self.sudoku.size();
+        let mut placed = 0;
+
+        if !self.enabled.contains(Techniques::HIDDEN_SINGLES) {
+            return Ok(0);
+        }
+
+        for (unit, index) in self.sudoku.all_units(self.sudoku.variant()) {
+            let cells = self.sudoku.unit_values(unit, index).collect::<Vec<_>>();
+            let present = cells
+                .iter()
+                .fold(0u32, |mask, &(_, value)| mask | 1 << value);
+
+            for digit in (1..=size as u8).filter(|d| present & (1 << d) == 0) {
+                let mut holders = cells
                     .iter()
-                    .filter(|r| self.possible_values.contains_key(r))
-                    .collect::<Vec<&(usize, usize)>>();
-                empty_pos_vec.extend(unsolved);
+                    .map(|&(pos, _)| pos)
+                    .filter(|&pos| self.domain(pos).is_some_and(|d| d.contains(digit)));
+
+                match (holders.next(), holders.next()) {
+                    (None, _) => {
+                        let (pos, _) = cells
+                            .iter()
+                            .copied()
+                            .find(|&(_, value)| value == 0)
+                            .unwrap_or(cells[0]);
+                        debug!(%unit, index, digit, "No place left for the digit in the unit");
+                        return Err(Contradiction { pos });
+                    }
+                    (Some(pos), None) => {
+                        let domain = self.domain_mut(pos).unwrap();
+
+                        if domain.len() > 1 {
+                            debug!(cell = ?pos, digit, "Found a hidden single");
+                            *domain = Domain::from_iter([digit]);
+                            placed += 1;
+                        }
+                    }
+                    _ => {}
+                }
             }
         }
+
+        self.techniques.hidden_singles += placed;
+
+        Ok(placed)
     }
 
-    /// Handles the pruning of a single cell's domain. Returns `true` if any pruning was done and
-    /// `false` if not.
-    fn arc_reduce(&mut self, pos: &(usize, usize), r_all: &BTreeSet<(usize, usize)>) -> bool {
-        let mut change = false;
+    /// Removes the candidates of the naked pairs and triples, i.e. two (three) empty cells of a
+    /// row, column, or square (or diagonal of an X-Sudoku) whose domains hold only two (three)
+    /// digits between them, from the other cells of the unit. Returns the number of the subsets
+    /// that eliminated at least one candidate, an emptied domain being left to the next AC-3 pass
+    /// to report.
+    fn naked_subsets(&mut self) -> usize {
+        let (mut pairs, mut triples) = (0, 0);
 
-        for r_pos in r_all.iter() {
-            // Skip further processing if there's no possible values left for the current position
-            let possible = skip_fail_option!(self.possible_values.get_mut(pos));
-            let value = self.sudoku.get_grid_value(r_pos);
+        for (unit, index) in self.sudoku.all_units(self.sudoku.variant()) {
+            let cells = self
+                .sudoku
+                .unit_values(unit, index)
+                .filter_map(|(pos, _)| Some((pos, self.domain(pos)?)))
+                .collect::<Vec<(Pos, Domain)>>();
 
-            if possible.contains(&value) {
-                // Prune the domain if duplicate is found
-                possible.remove(&value);
+            for (i, &(a, da)) in cells.iter().enumerate() {
+                for (j, &(b, db)) in cells.iter().enumerate().skip(i + 1) {
+                    if self.enabled.contains(Techniques::NAKED_PAIRS)
+                        && da.len() == 2
+                        && da == db
+                        && self.eliminate(&cells, &[a, b], da)
+                    {
+                        debug!(cells = ?[a, b], %unit, index, "Found a naked pair");
+                        pairs += 1;
+                    }
 
-                if possible.len() == 1 {
-                    // Set the cell value if pruned up to a single possibility
-                    debug!("Eliminated whole domain of cell {:?} with AC-3", r_pos);
-                    let last = possible.iter().cloned().collect::<Vec<u8>>().pop().unwrap();
-                    self.sudoku.set_grid_value(*pos, last);
-                    self.possible_values.remove(pos);
+                    for &(c, dc) in &cells[j + 1..] {
+                        let digits = da.union(db).union(dc);
 
-                    change = true;
+                        if self.enabled.contains(Techniques::NAKED_TRIPLES)
+                            && digits.len() == 3
+                            && self.eliminate(&cells, &[a, b, c], digits)
+                        {
+                            debug!(cells = ?[a, b, c], %unit, index, "Found a naked triple");
+                            triples += 1;
+                        }
+                    }
                 }
             }
         }
 
-        change
+        self.techniques.naked_pairs += pairs;
+        self.techniques.naked_triples += triples;
+
+        pairs + triples
     }
 
-    /// Handles the backtracking DFS: MRV heuristic picks the next variable (cell in the Sudoku)
-    /// to assign a value based on the least number of remaining legal values & after assigning a
-    /// value to the cell FC immediately eliminates that value from the neighboring cells' domains.
-    fn dfs(&mut self, mut seen: BTreeMap<(usize, usize), BTreeSet<u8>>) -> bool {
-        let is_valid = self.sudoku.is_valid(None);
-        let is_solved = self.sudoku.is_solved();
+    /// Applies the intersections of the squares and the lines: a digit whose candidates within a
+    /// square all lie on a single row or column is eliminated from the rest of the line (pointing
+    /// pairs), and conversely a digit confined to a single square within a line is eliminated
+    /// from the rest of the square (box-line reduction). Returns the number of the eliminating
+    /// intersections.
+    fn intersections(&mut self) -> usize {
+        let size = self.sudoku.size();
+        let (mut pointing, mut claiming) = (0, 0);
+        let pairs = [
+            (Unit::Square, Unit::Row, Techniques::POINTING_PAIRS),
+            (Unit::Square, Unit::Column, Techniques::POINTING_PAIRS),
+            (Unit::Row, Unit::Square, Techniques::BOX_LINE_REDUCTION),
+            (Unit::Column, Unit::Square, Techniques::BOX_LINE_REDUCTION),
+        ];
+
+        for (unit, target, technique) in pairs {
+            if !self.enabled.contains(technique) {
+                continue;
+            }
+
+            for index in 0..size {
+                let cells = self
+                    .sudoku
+                    .unit_values(unit, index)
+                    .filter_map(|(pos, _)| Some((pos, self.domain(pos)?)))
+                    .collect::<Vec<(Pos, Domain)>>();
+
+                for digit in 1..=size as u8 {
+                    let mut targets = cells
+                        .iter()
+                        .filter(|(_, domain)| domain.contains(digit))
+                        .map(|&(pos, _)| self.sudoku.unit_index(target, pos));
 
-        if !is_valid {
-            return false;
+                    let Some(Some(first)) = targets.next() else {
+                        continue;
+                    };
+
+                    if !targets.all(|other| other == Some(first)) {
+                        continue;
+                    }
+
+                    let peers = self
+                        .sudoku
+                        .unit_values(target, first)
+                        .filter_map(|(pos, _)| Some((pos, self.domain(pos)?)))
+                        .collect::<Vec<(Pos, Domain)>>();
+                    let confined = cells.iter().map(|&(pos, _)| pos).collect::<Vec<Pos>>();
+
+                    if self.eliminate(&peers, &confined, Domain::from_iter([digit])) {
+                        debug!(%unit, index, %target, first, digit, "Found an intersection");
+
+                        match technique {
+                            Techniques::POINTING_PAIRS => pointing += 1,
+                            _ => claiming += 1,
+                        }
+                    }
+                }
+            }
+        }
+
+        self.techniques.pointing_pairs += pointing;
+        self.techniques.box_line_reductions += claiming;
+
+        pointing + claiming
+    }
+
+    /// Removes the digits from the domains of the unit cells outside of the subset, returning
+    /// `true` if any of them was included.
+    fn eliminate(&mut self, cells: &[(Pos, Domain)], subset: &[Pos], digits: Domain) -> bool {
+        let mut eliminated = false;
+
+        for &(pos, _) in cells.iter().filter(|(pos, _)| !subset.contains(pos)) {
+            let domain = self.domain_mut(pos).unwrap();
+
+            for digit in digits.iter() {
+                eliminated |= domain.remove(digit);
+            }
         }
 
-        if is_solved && is_valid {
-            return true;
+        eliminated
+    }
+
+    /// Revises the arcs of the worklist until the fixpoint, re-enqueueing the arcs into every
+    /// revised cell. The removed values are recorded on the trail if `backtrackable`. Fails if a
+    /// domain is emptied, in which case the domains are left as they were at that point.
+    fn revise_arcs(
+        &mut self,
+        mut arcs: VecDeque<(Pos, Pos)>,
+        backtrackable: bool,
+    ) -> Result<(), Contradiction> {
+        while let Some((x, y)) = arcs.pop_front() {
+            self.stats.revisions += 1;
+            let value = match self.revise(x, y) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            if backtrackable {
+                self.trail.push((x, value));
+                self.mac_prunings += 1;
+            }
+
+            if self.domain(x).is_some_and(Domain::is_empty) {
+                debug!(cell = ?x, "Emptied the whole domain of the cell with AC-3");
+                return Err(Contradiction { pos: x });
+            }
+
+            arcs.extend(self.arcs_into(x, Some(y)));
         }
 
-        if self.possible_values.is_empty() {
-            return false;
+        Ok(())
+    }
+
+    /// Returns the arcs (Z, X) from the empty peers Z of the cell X, except for `skip`.
+    fn arcs_into(&self, x: Pos, skip: Option<Pos>) -> impl Iterator<Item = (Pos, Pos)> + '_ {
+        self.sudoku
+            .peers(x)
+            .iter()
+            .filter(move |&&z| Some(z) != skip && self.domain(z).is_some())
+            .map(move |&z| (z, x))
+    }
+
+    /// Removes the values without support in the domain of Y from the domain of X, which for the
+    /// inequality of the peers is only the value of Y once it's down to a single candidate.
+    /// Returns the removed value if the domain of X shrank.
+    fn revise(&mut self, x: Pos, y: Pos) -> Option<u8> {
+        let value = self.domain(y).and_then(Domain::single)?;
+
+        self.domain_mut(x).unwrap().remove(value).then_some(value)
+    }
+
+    /// Handles the backtracking DFS: MRV heuristic picks the next variable (cell in the Sudoku)
+    /// to assign a value based on the least number of remaining legal values & after assigning a
+    /// value to the cell FC immediately eliminates that value from the neighboring cells' domains.
+    ///
+    /// The search runs on an explicit stack of frames rather than recursing once per assignment,
+    /// so its depth is only bounded by the number of the empty cells. Only the units of the cell
+    /// assigned last need to be checked for validity on top of the parent grid, the whole grid
+    /// being checked at the root.
+    ///
+    /// Returns the number of the solutions found up to `limit`, backtracking from each of them
+    /// like from a failed branch until the limit is reached. The first solution is left in the
+    /// grid, restored at the end if the search went on past it. Restarts are only done before
+    /// the first solution, so that none of them is counted twice.
+    fn dfs(&mut self, limit: usize, second_budget: Option<u64>) -> usize {
+        if !self.sudoku.is_valid(None) {
+            return 0;
         }
 
-        // Pop the smallest domain from the min-heap (MRV)
-        // The conversion from `BTreeMap` to `BinaryHeap` is linear anyway, so
-        // basically no performance is lost by iterating through the map instead
-        let (pos, domain) = Self::mrv_domain(&self.possible_values).unwrap();
+        let mut stack = match self.next_frame() {
+            Some(frame) => vec![frame],
+            None => return 1,
+        };
+        let root = self.trail.len();
+        let mut restarted_at = 0;
+        let mut found = 0;
+        let mut first = None;
+        let mut first_at = 0;
 
-        for d_value in domain {
-            if seen.get(&pos).unwrap().contains(&d_value) {
+        while let Some(frame) = stack.last_mut() {
+            // Backtrack if the branch of the previous value doesn't return a solution, the failed
+            // value staying pruned until the parent frame backtracks past it in turn
+            if let Some((value, mark)) = frame.assigned.take() {
+                self.depth -= 1;
+                self.stats.backtracks += 1;
+                self.undo(mark);
+                self.domain_mut(frame.pos).unwrap().remove(value);
+                self.trail.push((frame.pos, value));
+                self.sudoku.set_grid_value(frame.pos, 0);
+            }
+
+            // Trigger backtrack if the current depth is explored and no solution is found
+            let value = match self.next_value(frame.remaining) {
+                Some(value) => value,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            frame.remaining.remove(value);
+
+            // Start over with the values of the root reinstated and a different shuffle
+            if found == 0 && self.should_restart(restarted_at) {
+                self.unwind(mem::take(&mut stack));
+                self.undo(root);
+                self.restarts += 1;
+                restarted_at = self.visited_nodes;
+                debug!(
+                    restarts = self.restarts,
+                    visited_nodes = self.visited_nodes,
+                    "Search restarted"
+                );
+                stack.extend(self.next_frame());
                 continue;
             }
 
-            seen.get_mut(&pos).unwrap().insert(d_value);
+            // Stop searching for the second solution once its budget or a limit is spent, which
+            // leaves the first one regardless
+            if first.is_some()
+                && second_budget.is_some_and(|budget| {
+                    self.visited_nodes >= first_at + budget || self.exceeded_limit().is_some()
+                })
+            {
+                debug!(
+                    visited_nodes = self.visited_nodes,
+                    "Second solution budget spent"
+                );
+                self.budget_exceeded = true;
+                self.unwind(mem::take(&mut stack));
+                break;
+            }
+
+            // Give up on the whole search once a limit is reached, the grid not being a solution
+            if let Some(limit) = self.exceeded_limit() {
+                debug!(%limit, visited_nodes = self.visited_nodes, "Search limit reached");
+                self.limit_reached = Some(limit);
+                self.unwind(mem::take(&mut stack));
+                break;
+            }
+
             self.visited_nodes += 1;
 
+            // Abort the whole search if cancelled by the progress hook
+            if !self.reporter.tick(self.visited_nodes, self.depth) {
+                self.unwind(mem::take(&mut stack));
+                break;
+            }
+
             // Assign new and prune related domains (FC)
-            let old_domains = skip_fail_option!(self.fc_pruning(pos, &d_value));
+            let pos = frame.pos;
+            let mark = match self.fc_pruning(pos, value) {
+                Some(mark) => mark,
+                None => {
+                    self.stats.backtracks += 1;
+                    continue;
+                }
+            };
+            frame.assigned = Some((value, mark));
+            self.depth += 1;
+            self.stats.max_depth = self.stats.max_depth.max(self.depth);
 
             // Branch with pruned domains (DFS)
-            if self.dfs(seen.clone()) {
-                return true;
+            if !self.sudoku.is_valid(Some(pos)) {
+                continue;
             }
 
-            // Backtrack if the branch doesn't return a solution
-            self.possible_values = old_domains;
-            self.possible_values.get_mut(&pos).unwrap().remove(&d_value);
-            self.sudoku.set_grid_value(pos, 0);
+            match self.next_frame() {
+                Some(frame) => stack.push(frame),
+                None => {
+                    debug_assert!(self.sudoku.is_solved());
+                    found += 1;
+
+                    if found == limit && first.is_none() {
+                        return found;
+                    }
+
+                    if first.is_none() {
+                        first = Some(self.sudoku.clone());
+                        first_at = self.visited_nodes;
+                    }
+
+                    if found == limit {
+                        self.unwind(mem::take(&mut stack));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(solution) = first {
+            self.restore_solution(solution);
         }
 
-        // Trigger backtrack if the current depth is explored and no solution is found
-        false
+        found
     }
 
-    /// Prunes the domains of all (empty) neighboring cells (Forward Checking).
-    fn fc_pruning(
-        &mut self,
-        pos: (usize, usize),
-        new: &u8,
-    ) -> Option<BTreeMap<(usize, usize), BTreeSet<u8>>> {
-        let domains = self.possible_values.clone();
-        self.sudoku.set_grid_value(pos, *new);
-        self.possible_values.remove(&pos);
-
-        for r_pos in self.related_cells.get(&pos).unwrap().iter() {
+    /// Puts back the solution found earlier in the search, every cell being assigned again.
+    fn restore_solution(&mut self, solution: Sudoku) {
+        self.sudoku = solution;
+        self.possible_values.fill(None);
+        self.empty_peers.fill(0);
+    }
+
+    /// Picks the smallest domain (MRV) for the next frame, the domains being kept for exactly the
+    /// empty cells, so `None` means a full grid. The frame iterates a snapshot of the domain, as
+    /// the failed values are pruned from it on the way. The cell stays assigned for the whole
+    /// subtree, so none of the frames above it can pick it again and each value is tried exactly
+    /// once.
+    fn next_frame(&mut self) -> Option<Frame> {
+        let mut scanned = 0;
+        let found = self.mrv_domain(&mut scanned);
+        self.scans += scanned;
+        let (pos, domain) = found?;
+
+        Some(Frame {
+            pos,
+            remaining: domain,
+            assigned: None,
+        })
+    }
+
+    /// Picks the value of the cell to try next out of the `remaining` ones, a random one if the
+    /// order is shuffled or the lowest one otherwise.
+    fn next_value(&mut self, remaining: Domain) -> Option<u8> {
+        match &mut self.rng {
+            Some(rng) if !remaining.is_empty() => {
+                remaining.iter().nth(rng.gen_range(0..remaining.len()))
+            }
+            _ => remaining.iter().next(),
+        }
+    }
+
+    /// Whether the shuffled search should be restarted, having visited the number of nodes of
+    /// the `RestartPolicy` since the last restart at `restarted_at` nodes.
+    fn should_restart(&self, restarted_at: u64) -> bool {
+        self.rng.is_some()
+            && self.restart_policy.is_some_and(|policy| {
+                self.restarts < policy.max_restarts
+                    && self.visited_nodes - restarted_at >= policy.nodes
+            })
+    }
+
+    /// Returns the limit which the next node would exceed, the deadline only being checked every
+    /// `DEADLINE_CHECK_INTERVAL_NODES` nodes.
+    fn exceeded_limit(&self) -> Option<SearchLimit> {
+        if self.max_nodes.is_some_and(|max| self.visited_nodes >= max) {
+            return Some(SearchLimit::Nodes);
+        }
+
+        match self.deadline {
+            Some(deadline)
+                if self
+                    .visited_nodes
+                    .is_multiple_of(DEADLINE_CHECK_INTERVAL_NODES)
+                    && Instant::now() >= deadline =>
+            {
+                Some(SearchLimit::Time)
+            }
+            _ => None,
+        }
+    }
+
+    /// Undoes the assignments of the frames from the top of the stack down to the root, leaving
+    /// the grid and the domains as they were before the search.
+    fn unwind(&mut self, stack: Vec<Frame>) {
+        for frame in stack.into_iter().rev() {
+            if let Some((_, mark)) = frame.assigned {
+                self.undo(mark);
+                self.sudoku.set_grid_value(frame.pos, 0);
+            }
+        }
+
+        self.depth = 0;
+    }
+
+    /// Prunes the domains of all (empty) neighboring cells (Forward Checking), continued with
+    /// the arcs into the pruned ones if the `PropagationLevel` is MAC. Returns the length of the
+    /// trail before the assignment to undo it with, the removed domain of the cell itself
+    /// included. Fails if the domain of a neighbor is emptied, in which case the grid and the
+    /// domains are left unchanged.
+    fn fc_pruning(&mut self, pos: Pos, new: u8) -> Option<usize> {
+        debug_assert!(
+            !self.sudoku.is_given(pos),
+            "Assigning the given cell at {}",
+            pos
+        );
+        let mark = self.trail.len();
+        let mut arcs = VecDeque::new();
+        self.sudoku.set_grid_value(pos, new);
+
+        if let Some(domain) = self.take_domain(pos) {
+            self.trail.extend(domain.iter().map(|value| (pos, value)));
+        }
+
+        for r_pos in self.sudoku.peers(pos) {
             // Prune the cell's domain if the cell is empty
-            match self.possible_values.get_mut(r_pos) {
-                Some(r_domain) => {
-                    r_domain.remove(new);
+            let r_domain = skip_fail_option!(self.domain_mut(*r_pos));
+            let removed = r_domain.remove(new);
+            let emptied = r_domain.is_empty();
 
-                    if r_domain.is_empty() {
-                        self.sudoku.set_grid_value(pos, 0);
-                        return None;
-                    }
+            if removed {
+                self.trail.push((*r_pos, new));
+                self.stats.fc_prunings += 1;
+
+                if self.level == PropagationLevel::Mac {
+                    arcs.extend(self.arcs_into(*r_pos, None));
                 }
-                None => continue,
+            }
+
+            // Roll back the whole assignment, so the next value starts from the same domains as
+            // this one did
+            if emptied {
+                self.propagations += (self.trail.len() - mark) as u64;
+                self.undo(mark);
+                self.sudoku.set_grid_value(pos, 0);
+                return None;
             }
         }
 
-        Some(domains)
-    }
+        let failed = self.revise_arcs(arcs, true).is_err();
+        self.propagations += (self.trail.len() - mark) as u64;
+
+        if failed {
+            self.undo(mark);
+            self.sudoku.set_grid_value(pos, 0);
+            return None;
+        }
+
+        self.max_trail_len = self.max_trail_len.max(self.trail.len());
 
-    /// Iteratively finds the smallest domain from a `BTreeMap` and returns a clone of it.
-    fn mrv_domain(
-        map: &BTreeMap<(usize, usize), BTreeSet<u8>>,
-    ) -> Option<((usize, usize), BTreeSet<u8>)> {
-        map.iter()
-            .min_by(|a, b| a.1.len().cmp(&b.1.len()))
-            .map(|(k, v)| (*k, v.clone()))
+        Some(mark)
     }
 
-    fn init_domains(grid: &[Vec<u8>]) -> BTreeMap<(usize, usize), BTreeSet<u8>> {
-        let mut possible = BTreeMap::new();
+    /// Reinserts the pairs recorded on the trail after `mark` into the domains, the cells whose
+    /// domains are restored counting towards the degrees of their peers again.
+    fn undo(&mut self, mark: usize) {
+        let size = self.sudoku.size();
 
-        for (i, row) in grid.iter().enumerate() {
-            for (j, value) in row.iter().enumerate() {
-                if *value == 0 {
-                    possible.insert((i, j), BTreeSet::from_iter(0..10));
+        for (pos, value) in self.trail.drain(mark..) {
+            let domain = &mut self.possible_values[pos.index(size)];
+
+            if domain.is_none() {
+                for peer in self.sudoku.peers(pos) {
+                    self.empty_peers[peer.index(size)] += 1;
                 }
             }
+
+            domain.get_or_insert_with(Domain::default).insert(value);
+        }
+    }
+
+    /// Finds the smallest domain with a linear scan, breaking the ties by the `CellOrdering`, and
+    /// returns a copy of it, `None` if all of the cells are filled. The compared domains are
+    /// added to `scanned`.
+    fn mrv_domain(&self, scanned: &mut u64) -> Option<(Pos, Domain)> {
+        let domains = self.domains().inspect(|_| *scanned += 1);
+
+        match self.ordering {
+            CellOrdering::Mrv => domains.min_by_key(|(_, domain)| domain.len()),
+            CellOrdering::MrvDegree => {
+                domains.min_by_key(|&(pos, domain)| (domain.len(), Reverse(self.degree(pos))))
+            }
+            // Compares the ratios by cross-multiplying to stay in integers
+            CellOrdering::DomOverDegree => domains.min_by(|&(a, da), &(b, db)| {
+                (da.len() * self.degree(b)).cmp(&(db.len() * self.degree(a)))
+            }),
         }
+    }
 
-        possible
+    /// Seeds the domains of the empty cells with their legal digits given the initial grid, as
+    /// looked up by `Sudoku::candidate_masks`.
+    fn init_domains(sudoku: &Sudoku) -> Vec<Option<Domain>> {
+        sudoku
+            .as_flat()
+            .iter()
+            .zip(sudoku.candidate_masks())
+            .map(|(&value, mask)| (value == 0).then(|| Domain::from_mask(mask)))
+            .collect()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::dlx::DlxSolver;
 
-    fn init_unseen() -> BTreeMap<(usize, usize), BTreeSet<u8>> {
-        let mut unseen = BTreeMap::new();
+    // Among the hardest puzzles for DFS in the first 16000 of the 'sudoku17' collection
+    const HARD_INPUTS: [&str; 4] = [
+        "000002300100500000007000000400000017030800000000000600006470000020000900000010000",
+        "000001050830000000000000600100300000005000020000040007070020000000000308000400100",
+        "000000608900002000000000300500060070000800000000030000020007500038100000000000040",
+        "000002400560000000100000000020160000000000850000030000300000016008004000000700000",
+    ];
 
-        for i in 0..9 {
-            for j in 0..9 {
-                unseen.insert((i, j), BTreeSet::new());
+    #[test]
+    fn test_domain() {
+        // Every subset of the digits 1-9, and bit 0 for the ones constructed from a mask
+        for mask in 0u32..1 << 10 {
+            let domain = Domain::from_mask(mask);
+            let digits = (1..=9)
+                .filter(|d| mask & (1 << d) != 0)
+                .collect::<Vec<u8>>();
+
+            assert_eq!(domain.iter().collect::<Vec<_>>(), digits);
+            assert_eq!(domain.len(), digits.len());
+            assert_eq!(domain.is_empty(), digits.is_empty());
+            assert_eq!(domain, digits.iter().copied().collect());
+            assert_eq!(
+                domain.single(),
+                (digits.len() == 1).then(|| digits[0]),
+                "{:b}",
+                mask
+            );
+            assert!(!domain.contains(0));
+
+            for d in 1..=9 {
+                assert_eq!(domain.contains(d), digits.contains(&d));
+
+                let mut removed = domain;
+                assert_eq!(removed.remove(d), digits.contains(&d));
+                assert!(!removed.contains(d) && !removed.remove(d));
+                assert_eq!(removed.len(), digits.len() - digits.contains(&d) as usize);
+
+                let mut inserted = removed;
+                assert!(inserted.insert(d) && !inserted.insert(d));
+                assert_eq!(inserted.len(), removed.len() + 1);
+                assert_eq!(inserted == domain, digits.contains(&d));
             }
         }
 
-        unseen
+        // The 16x16 grids use bits 1-16
+        let full = Domain::from_mask(((1 << 16) - 1) << 1);
+        assert_eq!(full.len(), 16);
+        assert_eq!(
+            full.iter().collect::<Vec<_>>(),
+            (1..=16).collect::<Vec<_>>()
+        );
+        assert_eq!(Domain::from_mask(1 << 16).single(), Some(16));
+        assert!(Domain::default().is_empty() && Domain::from_mask(1).is_empty());
     }
 
-    fn calculate_relations() -> BTreeMap<(usize, usize), BTreeSet<(usize, usize)>> {
-        let mut relations = BTreeMap::new();
+    #[test]
+    fn test_domains_exclude_zero() {
+        for raw in [
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+            "000000012000035000000600070700000300000400800100000000000120000080000040050000600",
+            "1000000000000003",
+        ] {
+            let sudoku = raw.parse::<Sudoku>().unwrap();
+            let mut solver = DfsSolver::new(sudoku.clone());
+            let digits = 1..=sudoku.size() as u8;
 
-        for i in 0..9 {
-            for j in 0..9 {
-                relations.insert((i, j), Self::get_related(i, j));
+            for (pos, domain) in solver.candidates() {
+                assert!(domain.iter().all(|d| digits.contains(d)), "{}", pos);
+                assert_eq!(
+                    domain.iter().fold(0, |mask, d| mask | 1 << d),
+                    sudoku.candidates_for(pos)
+                );
             }
+
+            assert_eq!(solver.propagate(), Ok(()));
+            assert!(solver
+                .candidates()
+                .values()
+                .flatten()
+                .all(|d| digits.contains(d)));
+
+            assert!(solver.solve().0);
+            assert!(solver.get_sudoku().is_solution_of(&sudoku));
         }
+    }
+
+    #[test]
+    fn test_ac3_cascade() {
+        // Every empty cell is resolved by a cascade of single candidates
+        let sudoku =
+            "509003407001547893473910560057030684102860309836704105390076201010382040204000730"
+                .parse::<Sudoku>()
+                .unwrap();
+        let mut solver = DfsSolver::new(sudoku.clone());
+
+        assert_eq!(solver.propagate(), Ok(()));
+        assert!(solver.candidates().is_empty());
+        assert!(solver.get_sudoku().is_solution_of(&sudoku));
+        assert_eq!(solver.solve(), (true, 0));
+    }
+
+    #[test]
+    fn test_ac3_contradiction() {
+        // Both of the last two cells of the top row are down to 8, as columns 7 and 8 contain 9
+        let sudoku = format!(
+            "{}{}{}{}",
+            "123456700",
+            "0".repeat(18),
+            "000000090000000000000000000000000009",
+            "0".repeat(18)
+        )
+        .parse::<Sudoku>()
+        .unwrap();
+        assert!(sudoku.is_valid(None));
 
-        relations
+        let mut solver = DfsSolver::new(sudoku.clone());
+        assert!(solver
+            .candidates()
+            .values()
+            .all(|domain| !domain.is_empty()));
+        let contradiction = solver.propagate().unwrap_err();
+        assert!([(0, 7), (0, 8)].map(Pos::from).contains(&contradiction.pos));
+        assert!(solver.candidates()[&contradiction.pos].is_empty());
+
+        let mut solver = DfsSolver::new(sudoku);
+        assert_eq!(solver.solve(), (false, 0));
+        assert_eq!(solver.contradiction(), Some(contradiction));
+    }
+
+    #[test]
+    fn test_fc_pruning_rollback() {
+        // Both of the last two cells of the top row are seeded with only 8 as the candidate
+        let sudoku = format!(
+            "{}{}{}{}",
+            "123456700",
+            "0".repeat(18),
+            "000000090000000000000000000000000009",
+            "0".repeat(18)
+        )
+        .parse::<Sudoku>()
+        .unwrap();
+        let mut solver = DfsSolver::new(sudoku.clone());
+        let domains = solver.possible_values.clone();
+
+        assert_eq!(solver.fc_pruning(Pos::from_row_col(0, 7), 8), None);
+        assert_eq!(solver.possible_values, domains);
+        assert!(solver.trail.is_empty());
+        assert_eq!(solver.get_sudoku(), &sudoku);
+
+        // A successful assignment is undone the same way by the caller
+        let mark = solver.fc_pruning(Pos::from_row_col(1, 0), 4).unwrap();
+        assert_ne!(solver.possible_values, domains);
+        solver.undo(mark);
+        assert_eq!(solver.possible_values, domains);
     }
 
-    fn get_related(i: usize, j: usize) -> BTreeSet<(usize, usize)> {
-        let mut related: BTreeSet<(usize, usize)> = BTreeSet::new();
+    #[test]
+    fn test_mac_rollback() {
+        // The last two cells of the top row are seeded with 7 and 8 as the candidates
+        let sudoku = format!(
+            "{}{}{}{}{}",
+            "123456000",
+            "0".repeat(18),
+            "000000009",
+            "000000090",
+            "0".repeat(36)
+        )
+        .parse::<Sudoku>()
+        .unwrap();
+        let mut solver = DfsSolver::new(sudoku.clone());
+        let domains = solver.possible_values.clone();
+
+        // Leaves both of them with 8, which only the arcs between them rule out
+        let mark = solver.fc_pruning(Pos::from_row_col(1, 6), 7).unwrap();
+        solver.undo(mark);
+        solver.sudoku.set_grid_value(Pos::from_row_col(1, 6), 0);
 
-        for x in 0..9 {
-            related.insert((x, j)); // Vertical
-            related.insert((i, x)); // Horizontal
+        solver.set_propagation_level(PropagationLevel::Mac);
+        assert_eq!(solver.fc_pruning(Pos::from_row_col(1, 6), 7), None);
+        assert_eq!(solver.possible_values, domains);
+        assert!(solver.trail.is_empty());
+        assert_eq!(solver.get_sudoku(), &sudoku);
+        assert!(solver.mac_prunings > 0);
+    }
+
+    #[test]
+    fn test_mac_solve() {
+        let sudoku =
+            "000000015020060000000000408003000900000100000000008000150400000000070300800000060"
+                .parse::<Sudoku>()
+                .unwrap();
+        let mut fc = DfsSolver::new(sudoku.clone());
+        let mut mac = DfsSolver::new(sudoku.clone());
+        mac.set_propagation_level(PropagationLevel::Mac);
+
+        let (fc_solved, fc_nodes) = fc.solve();
+        let (mac_solved, mac_nodes) = mac.solve();
+
+        assert!(fc_solved && mac_solved);
+        assert_eq!(mac.get_sudoku(), fc.get_sudoku());
+        assert!(mac.get_sudoku().is_solution_of(&sudoku));
+        assert!(mac_nodes < fc_nodes);
+        assert_eq!(fc.mac_prunings(), 0);
+        assert!(mac.mac_prunings() > 0);
+    }
+
+    #[test]
+    fn test_mac_hard() {
+        for grid in HARD_INPUTS {
+            let sudoku = grid.parse::<Sudoku>().unwrap();
+            let [(fc_solved, fc_nodes), (mac_solved, mac_nodes)] =
+                [PropagationLevel::ForwardChecking, PropagationLevel::Mac].map(|level| {
+                    let mut solver = DfsSolver::new(sudoku.clone());
+                    solver.set_propagation_level(level);
+                    solver.solve()
+                });
+
+            // MAC at least halves the search on each of them
+            assert!(fc_solved && mac_solved);
+            assert!(2 * mac_nodes < fc_nodes);
         }
+    }
+
+    #[test]
+    fn test_empty_grid_stack() {
+        // Every cell of an empty grid is assigned by the search, one frame per cell, which has
+        // to fit into a small thread stack regardless of the depth
+        let solve = || {
+            for size in [9, 25] {
+                let sudoku = "0".repeat(size * size).parse::<Sudoku>().unwrap();
+                let mut solver = DfsSolver::new(sudoku.clone());
+                let (solved, visited_nodes) = solver.solve();
 
-        for x in 0..3 {
-            for y in 0..3 {
-                related.insert(((i / 3) * 3 + x, (j / 3 * 3 + y))); // Square
+                assert!(solved);
+                assert!(visited_nodes >= (size * size) as u64);
+                assert!(solver.get_sudoku().is_solution_of(&sudoku));
+                assert_eq!(solver.depth, size * size);
             }
+        };
+
+        thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(solve)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_empty_domain_unsolvable() {
+        // The top right cell sees 1-8 on its row and 9 on its column
+        let sudoku = format!("{}{}{}", "123456780", "000000009", "0".repeat(63))
+            .parse::<Sudoku>()
+            .unwrap();
+        assert!(sudoku.is_valid(None));
+
+        let mut solver = DfsSolver::new(sudoku);
+        assert_eq!(
+            solver.candidates()[&Pos::from_row_col(0, 8)],
+            Vec::<u8>::new()
+        );
+        assert_eq!(solver.solve(), (false, 0));
+        assert_eq!(
+            solver.contradiction(),
+            Some(Contradiction {
+                pos: Pos::from_row_col(0, 8)
+            })
+        );
+    }
+
+    #[test]
+    fn test_seeded_value_order() {
+        let empty = "0".repeat(81).parse::<Sudoku>().unwrap();
+        let solve_with = |seed| {
+            let options = DfsOptions {
+                seed,
+                ..Default::default()
+            };
+            let mut solver = DfsSolver::with_options(empty.clone(), options);
+            assert!(solver.solve().0);
+            assert!(solver.sudoku.is_solution_of(&empty));
+            solver.sudoku.grid_to_string()
+        };
+
+        assert_eq!(solve_with(None), solve_with(None));
+        assert_eq!(solve_with(Some(1)), solve_with(Some(1)));
+        assert_ne!(solve_with(Some(1)), solve_with(Some(2)));
+        assert_ne!(solve_with(Some(1)), solve_with(None));
+    }
+
+    #[test]
+    fn test_restarts() {
+        let sudoku =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let solve_with = |seed, max_restarts| {
+            let options = DfsOptions {
+                seed,
+                restarts: Some(RestartPolicy {
+                    nodes: 10,
+                    max_restarts,
+                }),
+                ..Default::default()
+            };
+            let mut solver = DfsSolver::with_options(sudoku.clone(), options);
+            let (solved, visited_nodes) = solver.solve();
+            assert!(solved);
+            assert!(solver.sudoku.is_solution_of(&sudoku));
+            (solver.restarts, visited_nodes)
+        };
+
+        // The search is left to finish after the last restart, reproducibly given the seed
+        let (restarts, visited_nodes) = solve_with(Some(7), 5);
+        assert_eq!(restarts, 5);
+        assert!(visited_nodes > 5 * 10);
+        assert_eq!(solve_with(Some(7), 5), (restarts, visited_nodes));
+        assert_eq!(solve_with(None, 5).0, 0);
+    }
+
+    #[test]
+    fn test_search_stats() {
+        let empty = "0".repeat(16).parse::<Sudoku>().unwrap();
+        let mut solver = DfsSolver::new(empty.clone());
+        assert_eq!(solver.solve(), (true, 18));
+        assert_eq!(
+            solver.search_stats(),
+            Some(SearchStats {
+                backtracks: 2,
+                max_depth: 16,
+                revisions: 112,
+                fc_prunings: 44,
+            })
+        );
+
+        let mut solver = DfsSolver::new(empty);
+        solver.set_propagation_level(PropagationLevel::Mac);
+        assert_eq!(solver.solve(), (true, 17));
+        assert_eq!(
+            solver.search_stats(),
+            Some(SearchStats {
+                backtracks: 1,
+                max_depth: 16,
+                revisions: 295,
+                fc_prunings: 33,
+            })
+        );
+
+        // Every visited node is either backtracked from or on the path to the solution, which is
+        // as deep as the search ever gets
+        let sudoku =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let mut solver = DfsSolver::new(sudoku);
+        let (solved, visited_nodes) = solver.solve();
+        let stats = solver.stats;
+        assert!(solved);
+        assert_eq!(visited_nodes, stats.backtracks + stats.max_depth as u64);
+    }
+
+    #[test]
+    fn test_count_solutions_matches_dlx() {
+        const SUDOKU17: [&str; 3] = [
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+            "000000010400000000020000000000050604008000300001090000300400200050100000000807000",
+            "000000012000035000000600070700000300000400800100000000000120000080000040050000600",
+        ];
+        let mut corpus = vec![("0".repeat(81), 20)];
+
+        for puzzle in SUDOKU17 {
+            let mut solver = DfsSolver::new(puzzle.parse().unwrap());
+            assert_eq!(solver.count_solutions(2), 1);
+            let solution = solver.sudoku.grid_to_string();
+            let first_clue = puzzle.find(|c| c != '0').unwrap();
+            let first_empty = puzzle.find('0').unwrap();
+            let wrong_digit = (1..=9)
+                .map(|d| char::from(b'0' + d))
+                .find(|&d| {
+                    let mut grid = puzzle.to_string();
+                    grid.replace_range(first_empty..=first_empty, &d.to_string());
+                    d != solution.as_bytes()[first_empty] as char
+                        && grid.parse::<Sudoku>().unwrap().is_valid(None)
+                })
+                .unwrap();
+
+            // Proper, improper without the first clue, and unsolvable with a wrong extra clue
+            let mut improper = puzzle.to_string();
+            improper.replace_range(first_clue..=first_clue, "0");
+            let mut unsolvable = puzzle.to_string();
+            unsolvable.replace_range(first_empty..=first_empty, &wrong_digit.to_string());
+            corpus.extend([(puzzle.to_string(), 10), (improper, 10), (unsolvable, 10)]);
         }
 
-        related.remove(&(i, j));
+        for (grid, limit) in corpus {
+            let sudoku = grid.parse::<Sudoku>().unwrap();
+            let expected = DlxSolver::with_max_solutions(sudoku.clone(), limit)
+                .unwrap()
+                .count_solutions(limit, None)
+                .unwrap();
+            let mut solver = DfsSolver::new(sudoku.clone());
+            let count = solver.count_solutions(limit);
+            assert_eq!(count, expected, "{}", grid);
+
+            // The grid holds the first solution, i.e. the one a plain solve stops at
+            let mut first = DfsSolver::new(sudoku.clone());
+            assert_eq!(first.solve().0, count > 0, "{}", grid);
+            if count > 0 {
+                assert!(solver.sudoku.is_solution_of(&sudoku), "{}", grid);
+                assert_eq!(
+                    solver.sudoku.grid_to_string(),
+                    first.sudoku.grid_to_string()
+                );
+            }
+        }
+    }
+
+    /// Propagates only AC-3 and the given techniques, returning the number of the unresolved cells.
+    fn unresolved_with(sudoku: &Sudoku, techniques: Techniques) -> usize {
+        let mut solver = DfsSolver::new(sudoku.clone());
+        solver.set_techniques(techniques);
+        solver.propagate().unwrap();
+
+        solver.candidates().len()
+    }
+
+    #[test]
+    fn test_naked_pair() {
+        let sudoku =
+            "000000026080003000000070000100400800605200000007000300030000900000050000000600000"
+                .parse::<Sudoku>()
+                .unwrap();
+        assert_eq!(unresolved_with(&sudoku, Techniques::HIDDEN_SINGLES), 25);
+
+        // A single naked pair unblocks the singles again, resolving the rest of the grid
+        let mut solver = DfsSolver::new(sudoku.clone());
+        assert_eq!(solver.propagate(), Ok(()));
+        assert!(solver.candidates().is_empty());
+        assert!(solver.get_sudoku().is_solution_of(&sudoku));
+        assert_eq!(solver.techniques().naked_pairs, 1);
+        assert_eq!(solver.techniques().naked_triples, 0);
+    }
+
+    #[test]
+    fn test_naked_triple() {
+        let sudoku =
+            "000000012050400000000000030700600400001000000000080000920000800000510700000003000"
+                .parse::<Sudoku>()
+                .unwrap();
+        assert_eq!(unresolved_with(&sudoku, Techniques::HIDDEN_SINGLES), 30);
+
+        let mut solver = DfsSolver::new(sudoku.clone());
+        assert_eq!(solver.propagate(), Ok(()));
+        assert!(solver.candidates().is_empty());
+        assert!(solver.get_sudoku().is_solution_of(&sudoku));
+        assert_eq!(solver.techniques().naked_pairs, 0);
+        assert_eq!(solver.techniques().naked_triples, 1);
+    }
+
+    #[test]
+    fn test_naked_subset_eliminations() {
+        let sudoku = "0".repeat(81).parse::<Sudoku>().unwrap();
+        let mut solver = DfsSolver::new(sudoku);
+
+        // The first two cells of the top row can only hold 1 and 2
+        for col in 0..2 {
+            *solver.domain_mut(Pos::from_row_col(0, col)).unwrap() = Domain::from_iter([1, 2]);
+        }
+
+        assert_eq!(solver.naked_subsets(), 2);
+        assert_eq!(solver.techniques().naked_pairs, 2);
+        assert_eq!(
+            solver.domain(Pos::from_row_col(0, 8)),
+            Some(Domain::from_iter(3..=9))
+        );
+        assert_eq!(
+            solver.domain(Pos::from_row_col(2, 2)),
+            Some(Domain::from_iter(3..=9))
+        );
+        assert_eq!(
+            solver.domain(Pos::from_row_col(1, 8)),
+            Some(Domain::from_iter(1..=9))
+        );
+    }
+
+    #[test]
+    fn test_pointing_pair() {
+        let sudoku =
+            "000091300270000008000000000051030000000800020090000000400200060000000900300000000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let singles_and_subsets =
+            Techniques::HIDDEN_SINGLES | Techniques::NAKED_PAIRS | Techniques::NAKED_TRIPLES;
+        assert_eq!(unresolved_with(&sudoku, singles_and_subsets), 40);
+
+        let mut solver = DfsSolver::new(sudoku.clone());
+        assert_eq!(solver.propagate(), Ok(()));
+        assert!(solver.candidates().is_empty());
+        assert!(solver.get_sudoku().is_solution_of(&sudoku));
+        assert_eq!(solver.techniques().pointing_pairs, 1);
+        assert_eq!(solver.techniques().box_line_reductions, 0);
+    }
+
+    #[test]
+    fn test_box_line_reduction() {
+        let sudoku =
+            "000007400060100000020300000400020000000000001000000050000050260301400000000000800"
+                .parse::<Sudoku>()
+                .unwrap();
+        let singles_and_subsets =
+            Techniques::HIDDEN_SINGLES | Techniques::NAKED_PAIRS | Techniques::NAKED_TRIPLES;
+        assert_eq!(unresolved_with(&sudoku, singles_and_subsets), 29);
+
+        let mut solver = DfsSolver::new(sudoku.clone());
+        assert_eq!(solver.propagate(), Ok(()));
+        assert!(solver.candidates().is_empty());
+        assert!(solver.get_sudoku().is_solution_of(&sudoku));
+        assert_eq!(solver.techniques().pointing_pairs, 0);
+        assert_eq!(solver.techniques().box_line_reductions, 1);
+    }
+
+    /// Counts the empty peers of every cell from scratch.
+    fn recount_empty_peers(solver: &DfsSolver) -> Vec<u8> {
+        Pos::all(9)
+            .map(|pos| {
+                solver
+                    .sudoku
+                    .peers(pos)
+                    .iter()
+                    .filter(|&&peer| solver.domain(peer).is_some())
+                    .count() as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_peers_tracking() {
+        let sudoku =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let mut solver = DfsSolver::new(sudoku);
+        assert_eq!(solver.empty_peers, recount_empty_peers(&solver));
+
+        solver.propagate().unwrap();
+        assert_eq!(solver.empty_peers, recount_empty_peers(&solver));
+        let before = solver.empty_peers.clone();
+
+        let (pos, domain) = solver.mrv_domain(&mut 0).unwrap();
+        let mark = solver
+            .fc_pruning(pos, domain.iter().next().unwrap())
+            .unwrap();
+        assert_eq!(solver.empty_peers, recount_empty_peers(&solver));
+        assert_ne!(solver.empty_peers, before);
+
+        solver.undo(mark);
+        assert_eq!(solver.empty_peers, before);
+    }
+
+    #[test]
+    fn test_cell_ordering() {
+        let mut solver = DfsSolver::new("0".repeat(81).parse::<Sudoku>().unwrap());
+
+        // Only 12 empty peers left for the top left cell, the bottom right one keeping all 20
+        for k in 1..9 {
+            solver.take_domain(Pos::from_box(0, k, 3));
+        }
+        let (top_left, bottom_right) = (Pos::from_row_col(0, 0), Pos::from_row_col(8, 8));
+        *solver.domain_mut(top_left).unwrap() = Domain::from_iter([1, 2]);
+        *solver.domain_mut(bottom_right).unwrap() = Domain::from_iter([1, 2]);
+
+        let picked = |solver: &mut DfsSolver, ordering| {
+            solver.set_ordering(ordering);
+            solver.mrv_domain(&mut 0).unwrap().0
+        };
+        assert_eq!(picked(&mut solver, CellOrdering::Mrv), top_left);
+        assert_eq!(picked(&mut solver, CellOrdering::MrvDegree), bottom_right);
+        assert_eq!(
+            picked(&mut solver, CellOrdering::DomOverDegree),
+            bottom_right
+        );
+
+        // 2/12 of the top left cell against 3/20 of the bottom right one
+        solver.domain_mut(bottom_right).unwrap().insert(3);
+        assert_eq!(picked(&mut solver, CellOrdering::Mrv), top_left);
+        assert_eq!(picked(&mut solver, CellOrdering::MrvDegree), top_left);
+        assert_eq!(
+            picked(&mut solver, CellOrdering::DomOverDegree),
+            bottom_right
+        );
+    }
+
+    #[test]
+    fn test_cell_orderings_solve() {
+        let sudoku =
+            "000000015020060000000000408003000900000100000000008000150400000000070300800000060"
+                .parse::<Sudoku>()
+                .unwrap();
+        let visited = [
+            CellOrdering::Mrv,
+            CellOrdering::MrvDegree,
+            CellOrdering::DomOverDegree,
+        ]
+        .map(|ordering| {
+            let mut solver = DfsSolver::new(sudoku.clone());
+            solver.set_ordering(ordering);
+            let (solved, visited_nodes) = solver.solve();
+
+            assert!(solved);
+            assert!(solver.get_sudoku().is_solution_of(&sudoku));
+            visited_nodes
+        });
+
+        assert!(visited[1] < visited[0]);
+    }
+
+    #[test]
+    fn test_cell_orderings_hard() {
+        let mut totals = [0; 3];
+        for grid in HARD_INPUTS {
+            let sudoku = grid.parse::<Sudoku>().unwrap();
+            let visited = [
+                CellOrdering::Mrv,
+                CellOrdering::MrvDegree,
+                CellOrdering::DomOverDegree,
+            ]
+            .map(|ordering| {
+                let mut solver = DfsSolver::new(sudoku.clone());
+                solver.set_ordering(ordering);
+                let (solved, visited_nodes) = solver.solve();
+
+                assert!(solved);
+                visited_nodes
+            });
+
+            // The degree tie-break pays off on every puzzle, dom/deg only over the whole set
+            assert!(visited[1] < visited[0]);
+            for (total, visited_nodes) in totals.iter_mut().zip(visited) {
+                *total += visited_nodes;
+            }
+        }
+
+        assert!(totals[1] < totals[2] && totals[2] < totals[0]);
+    }
+
+    #[test]
+    fn test_techniques_disabled() {
+        let sudoku =
+            "000000026080003000000070000100400800605200000007000300030000900000050000000600000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let mut plain = DfsSolver::new(sudoku.clone());
+        plain.set_techniques(Techniques::empty());
+        plain.ac3().unwrap();
 
-        related
+        let mut solver = DfsSolver::new(sudoku);
+        solver.set_techniques(Techniques::empty());
+        assert_eq!(solver.propagate(), Ok(()));
+        assert!(!solver.candidates().is_empty());
+        assert_eq!(solver.possible_values, plain.possible_values);
+        assert_eq!(solver.techniques(), TechniqueCounts::default());
+        assert_eq!(Techniques::default(), Techniques::all());
     }
 }
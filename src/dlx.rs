@@ -1,8 +1,29 @@
-use std::{error::Error, iter::repeat};
-
-use log::error;
-
-use crate::{solver::SudokuSolver, sudoku::Sudoku};
+use std::{
+    error::Error,
+    fmt,
+    iter::once,
+    mem,
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+
+use crate::{
+    dfs::{SearchLimit, DEADLINE_CHECK_INTERVAL_NODES},
+    solver::{NodeCounts, ProgressHook, ProgressReporter, SudokuSolver},
+    sudoku::{value_to_char, Pos, Sudoku, SudokuError, Unit, Variant},
+};
+
+// Number of visited nodes between the checks of the stop flag by the workers of the parallel
+// search, which also report their progress to the hook of the caller at the same interval
+const PARALLEL_CHECK_INTERVAL_NODES: u64 = 1000;
 
 // This DLX implementation is largely based on Ulrik Sverdrup's more comprehensive
 // implementation at https://github.com/bluss/dlx/.
@@ -26,67 +47,156 @@ impl Direction {
     }
 }
 
-/// Node of the DLX matrix.
-#[derive(Debug, Clone, Copy)]
-struct Node<T> {
-    /// Links to available directions (i.e. previous, next, up, down in that particular order).
-    links: [usize; 4],
-    value: T,
+/// Largest number of nodes in the arena of the DLX matrix, the links being narrowed to `u32`
+/// with `UNLINKED` reserved for the nodes not linked yet.
+const MAX_NODES: usize = u32::MAX as usize;
+
+const UNLINKED: u32 = u32::MAX;
+
+/// Column head node of the DLX matrix, i.e. its index in the arena between 1 and the number of
+/// columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ColumnRef(usize);
+
+/// Row body item of the DLX matrix, i.e. its index in the arena after the header row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BodyRef(usize);
+
+/// Arena of the nodes of the DLX matrix as a struct of arrays, i.e. the links of every direction
+/// and the metadata in separate arrays, so the hot loops of covering and uncovering only chase
+/// the links they follow through the cache. The singleton head node comes first, followed by the
+/// column heads and then the row bodies, the metadata of which is only reachable through a
+/// `ColumnRef` or a `BodyRef` respectively. Every node takes 20 bytes, i.e. four `u32` links and
+/// a count or a column number, against the 48 bytes of a node with `usize` links and an enum.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Nodes {
+    /// Links of every node to available directions (i.e. previous, next, up, down in that
+    /// particular order).
+    links: [Vec<u32>; 4],
+    /// Counter for items alive in every column, in the order of the column heads.
+    counts: Vec<u32>,
+    /// Column number of every row body item for reference to column header.
+    columns: Vec<u32>,
 }
 
-impl<T> Node<T> {
-    /// Initializes a new node with the given inner value and no links to other nodes.
-    fn new(value: T) -> Self {
-        Self {
-            links: [!0; 4],
-            value,
-        }
+impl Nodes {
+    #[inline]
+    fn len(&self) -> usize {
+        self.links[0].len()
     }
 
-    fn get_link(&self, dir: Direction) -> usize {
-        self.links[dir as usize]
+    /// Returns the number of nodes before the first row body item, i.e. the head and the column
+    /// heads.
+    #[inline]
+    fn header_len(&self) -> usize {
+        self.counts.len() + 1
     }
 
-    fn set_link(&mut self, idx: usize, dir: Direction) -> &mut Self {
-        self.links[dir as usize] = idx;
-        self
+    /// Returns the number of nodes the arena can hold without reallocating, the metadata being
+    /// reserved along with the links.
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.links[0].capacity()
     }
 
-    fn assign(&mut self, dir: Direction) -> &mut usize {
-        &mut self.links[dir as usize]
+    /// Reserves the capacity for at least `additional` more nodes of any kind, see
+    /// `DlxSolver::with_amortized_arena`.
+    #[cfg(any(test, feature = "bench"))]
+    fn reserve(&mut self, additional: usize) {
+        for links in &mut self.links {
+            links.reserve(additional);
+        }
+        self.columns.reserve(additional);
     }
-}
 
-/// Value stored inside the DLX node (`Node`).
-#[derive(Debug, Clone, Copy)]
-enum Point {
-    /// Singleton head node before all columns.
-    Head(usize),
-    /// Column head with counter for items alive in the column.
-    Column(usize),
-    /// Row body item with column number for reference to column header.
-    Body(usize),
-}
+    /// Reserves the capacity for exactly `columns` more column heads and `bodies` more row body
+    /// items, plus the head of an empty arena, so that appending them doesn't reallocate.
+    fn reserve_exact(&mut self, columns: usize, bodies: usize) {
+        let head = usize::from(self.len() == 0);
 
-impl Point {
-    fn value(&self) -> usize {
-        match *self {
-            Point::Head(x) => {
-                error!("Possible error: Head should not need to be directly accessed");
-                x
-            }
-            Point::Column(x) | Point::Body(x) => x,
+        for links in &mut self.links {
+            links.reserve_exact(head + columns + bodies);
         }
+        self.counts.reserve_exact(columns);
+        self.columns.reserve_exact(bodies);
     }
 
-    fn value_mut(&mut self) -> &mut usize {
-        match self {
-            Point::Head(x) => {
-                error!("Possible error: Head should not need to be directly accessed");
-                x
-            }
-            Point::Column(x) | Point::Body(x) => x,
+    /// Appends a new node with no links to other nodes, and returns its index. Returns `Err` if
+    /// the arena is full, i.e. the index wouldn't fit into the links.
+    fn push_links(&mut self) -> Result<usize, ExactCoverError> {
+        let idx = self.len();
+
+        if idx >= MAX_NODES {
+            return Err(ExactCoverError::MatrixTooLarge { max: MAX_NODES });
         }
+
+        for links in &mut self.links {
+            links.push(UNLINKED);
+        }
+
+        Ok(idx)
+    }
+
+    /// Appends the singleton head node to the empty arena.
+    fn push_head(&mut self) -> Result<usize, ExactCoverError> {
+        assert_eq!(self.len(), 0, "Head must be the first node");
+        self.push_links()
+    }
+
+    /// Appends an empty column head after the head and the previous column heads.
+    fn push_column(&mut self) -> Result<ColumnRef, ExactCoverError> {
+        assert!(
+            self.len() > 0 && self.columns.is_empty(),
+            "Column heads must be between the head and the row bodies"
+        );
+        let idx = self.push_links()?;
+        self.counts.push(0);
+
+        Ok(ColumnRef(idx))
+    }
+
+    /// Appends a row body item of the column `col`, which isn't linked to it yet.
+    fn push_body(&mut self, col: ColumnRef) -> Result<BodyRef, ExactCoverError> {
+        let idx = self.push_links()?;
+        self.columns.push(col.0 as u32);
+
+        Ok(BodyRef(idx))
+    }
+
+    /// Removes the row body items from `len` onwards.
+    fn truncate_bodies(&mut self, len: usize) {
+        assert!(len >= self.header_len(), "Header can't be truncated");
+
+        for links in &mut self.links {
+            links.truncate(len);
+        }
+        self.columns.truncate(len - self.header_len());
+    }
+
+    #[inline]
+    fn get_link(&self, idx: usize, dir: Direction) -> usize {
+        self.links[dir as usize][idx] as usize
+    }
+
+    #[inline]
+    fn set_link(&mut self, idx: usize, link: usize, dir: Direction) -> &mut Self {
+        self.links[dir as usize][idx] = link as u32;
+        self
+    }
+
+    #[inline]
+    fn count(&self, col: ColumnRef) -> u32 {
+        self.counts[col.0 - 1]
+    }
+
+    #[inline]
+    fn count_mut(&mut self, col: ColumnRef) -> &mut u32 {
+        &mut self.counts[col.0 - 1]
+    }
+
+    #[inline]
+    fn column(&self, body: BodyRef) -> ColumnRef {
+        ColumnRef(self.columns[body.0 - self.header_len()] as usize)
     }
 }
 
@@ -99,11 +209,11 @@ struct Walker {
 
 impl Walker {
     #[inline]
-    fn next(&mut self, dlx: &DlxSolver, dir: Direction) -> Option<usize> {
-        let next = dlx.nodes[self.idx].get_link(dir);
+    fn next(&mut self, dlx: &ExactCover, dir: Direction) -> Option<usize> {
+        let next = dlx.nodes.get_link(self.idx, dir);
         self.idx = next;
 
-        assert_ne!(next, !0, "Invalid index found in traversal");
+        assert_ne!(next, UNLINKED as usize, "Invalid index found in traversal");
 
         if next == self.start {
             return None;
@@ -111,16 +221,185 @@ impl Walker {
 
         Some(next)
     }
+
+    /// Returns the next column head of the header row walked from the head.
+    #[inline]
+    fn next_column(&mut self, dlx: &ExactCover) -> Option<ColumnRef> {
+        self.next(dlx, Direction::Next).map(ColumnRef)
+    }
+
+    /// Returns the next row body item of the row walked from one of its items, or of the column
+    /// walked from its head.
+    #[inline]
+    fn next_body(&mut self, dlx: &ExactCover, dir: Direction) -> Option<BodyRef> {
+        self.next(dlx, dir).map(BodyRef)
+    }
+}
+
+/// Heuristic picking the next column to cover in Algorithm X, i.e. the constraint to branch on.
+/// The visited nodes are the rows tried in the chosen columns with any of them, and the scans the
+/// column heads compared to choose them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnSelection {
+    /// Scans every column for the one with the fewest rows (Knuth's S heuristic), the first one
+    /// among the tied.
+    #[default]
+    MinCount,
+    /// Picks the first column without scanning the others, backtracking once it's found empty.
+    FirstAvailable,
+    /// Scans like `MinCount`, but stops at the first column with at most one row, an empty column
+    /// later in the header being then only found after the row of the chosen one is tried.
+    MinCountEarlyExit,
+}
+
+/// Row of an exact cover matrix, numbered from 0 in the order of appending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RowId(pub usize);
+
+/// Error from building an exact cover matrix, i.e. a row the matrix can't hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExactCoverError {
+    /// The row refers to a column outside of the range 1-`cols`.
+    ColumnOutOfRange { col: usize, cols: usize },
+    /// The row refers to the column `col` more than once.
+    DuplicateColumn { col: usize },
+    /// The row doesn't contain any columns.
+    EmptyRow,
+    /// The matrix would contain more than `max` nodes, the most its links can address.
+    MatrixTooLarge { max: usize },
+}
+
+impl fmt::Display for ExactCoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExactCoverError::ColumnOutOfRange { col, cols } => write!(
+                f,
+                "Column {} outside of the defined universe of columns 1-{}",
+                col, cols
+            ),
+            ExactCoverError::DuplicateColumn { col } => {
+                write!(f, "Column {} repeated within the same row", col)
+            }
+            ExactCoverError::EmptyRow => write!(f, "Input must not be empty"),
+            ExactCoverError::MatrixTooLarge { max } => write!(
+                f,
+                "Exact cover matrix would exceed the maximum of {} nodes",
+                max
+            ),
+        }
+    }
 }
 
+impl Error for ExactCoverError {}
+
+/// Counters of the work done on the dancing links of the matrix besides the visited nodes,
+/// reported in the metadata.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DlxStats {
+    /// Columns covered by the searches, including the ones covered by a stopped search.
+    pub covers: u64,
+    /// Columns uncovered by the searches, equal to `covers` as every search restores the matrix.
+    pub uncovers: u64,
+    /// Rows appended while constructing the matrix, including the ones removed afterwards.
+    pub rows_appended: usize,
+    /// Number of nodes in the arena of the matrix, the header and the removed rows included.
+    pub arena_nodes: usize,
+}
+
+/// Unpruned exact cover matrix of the grids of a given size, i.e. with a row for every digit of
+/// every cell, built once and copied by `DlxSolver::with_template` for each puzzle.
+#[derive(Debug, Clone)]
+pub struct MatrixTemplate {
+    size: usize,
+    nodes: Nodes,
+    row_table: Vec<usize>,
+    subset_data: Vec<(Pos, usize)>,
+}
+
+impl MatrixTemplate {
+    /// Builds the matrix of the grids with `size` rows.
+    pub fn new(size: usize) -> Result<Self, SudokuError> {
+        let solver = DlxSolver::new("0".repeat(size * size).parse()?)?;
+
+        Ok(Self {
+            size,
+            nodes: solver.cover.nodes,
+            row_table: solver.cover.row_table,
+            subset_data: solver.subset_data,
+        })
+    }
+
+    /// Tells whether `DlxSolver::with_template` accepts the `sudoku`, i.e. it's a classic one of
+    /// the size of the template.
+    pub fn fits(&self, sudoku: &Sudoku) -> bool {
+        sudoku.size() == self.size && sudoku.variant() == Variant::Classic
+    }
+}
+
+/// Frame of the iterative search, i.e. a column chosen at a level of the search and the row tried
+/// in it (`None` before the first row).
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    col: ColumnRef,
+    row: Option<BodyRef>,
+}
+
+/// General exact cover solver, i.e. Algorithm X with the dancing links technique over a matrix of
+/// `num_columns` columns, each of which has to be covered by exactly one of the selected rows.
+/// Not tied to Sudoku (which `DlxSolver` adapts to it), e.g. N-queens or polyomino packing can
+/// be solved by appending a row for every placement. The columns are numbered from 1, and the
+/// rows are identified by their `RowId` in the order of appending.
 #[derive(Debug)]
-pub struct DlxSolver {
-    sudoku: Sudoku,
-    nodes: Vec<Node<Point>>,
+pub struct ExactCover {
+    nodes: Nodes,
     num_of_cols: usize,
     row_table: Vec<usize>,
-    subset_data: Vec<[usize; 3]>,
+    /// Number of rows removed from the matrix for good, see `remove_row`.
+    removed_rows: usize,
+    /// Rows tried during the search, i.e. the decisions of `NodeCounts`.
     visited_nodes: u64,
+    propagations: u64,
+    scans: u64,
+    /// Heuristic of `choose_column`.
+    column_selection: ColumnSelection,
+    covers: u64,
+    uncovers: u64,
+    /// Rows appended during the construction, including the ones removed afterwards.
+    rows_appended: usize,
+    /// Whether appending a row outgrew the capacity of the arena, i.e. the nodes were copied into
+    /// a larger allocation, which the exact reservation of `DlxSolver` never lets happen.
+    arena_reallocated: bool,
+    /// Maximum number of nodes to visit before stopping the search, unlimited if `None`.
+    node_budget: Option<u64>,
+    budget_exceeded: bool,
+    /// Maximum number of visited nodes before giving up, unlimited if `None`.
+    max_nodes: Option<u64>,
+    /// Maximum solving time before giving up, unlimited if `None`.
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    /// Limit which stopped the last search before it finished, if any.
+    limit_reached: Option<SearchLimit>,
+    reporter: ProgressReporter,
+}
+
+/// Sudoku adapter of `ExactCover`, the rows of which are the candidate digits of the cells.
+#[derive(Debug)]
+pub struct DlxSolver {
+    sudoku: Sudoku,
+    cover: ExactCover,
+    /// Cell and digit index (the digit minus one) of every row of the exact cover matrix.
+    subset_data: Vec<(Pos, usize)>,
+    /// Maximum number of distinct solutions to enumerate before stopping the search.
+    max_solutions: usize,
+    /// Distinct solutions found by the last search as 1D grids, the first one also set to the
+    /// inner grid.
+    solutions: Vec<Vec<u8>>,
+    /// Whether the first solution is searched for with `ExactCover::solve_first_parallel`.
+    parallel: bool,
+    /// Whether the last search extracted a solution failing the verification, see
+    /// `verified_solution`.
+    corrupted: bool,
 }
 
 impl SudokuSolver for DlxSolver {
@@ -131,7 +410,15 @@ impl SudokuSolver for DlxSolver {
     ///
     /// https://en.wikipedia.org/wiki/Knuth%27s_Algorithm_X
     fn solve(&mut self) -> (bool, u64) {
-        (self.algox(&mut Vec::new()), self.visited_nodes)
+        match self.parallel && self.max_solutions == 1 {
+            true => self.solve_parallel(),
+            false => self.collect_solutions(self.max_solutions),
+        }
+        (!self.solutions.is_empty(), self.cover.visited_nodes)
+    }
+
+    fn is_corrupted(&self) -> bool {
+        self.corrupted
     }
 
     /// Returns the inner grid. Notably doesn't check whether the solving process has finished and
@@ -140,241 +427,989 @@ impl SudokuSolver for DlxSolver {
         self.sudoku.clone_grid()
     }
 
+    fn get_sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
     /// Returns the inner grid as a 1D `String`. Notably doesn't check whether the solving process
     /// has finished and might return unexpected results.
     fn grid_to_string(&self) -> String {
         self.sudoku.grid_to_string()
     }
+
+    fn set_progress_reporter(&mut self, reporter: ProgressReporter) {
+        self.cover.reporter = reporter;
+    }
+
+    fn node_counts(&self) -> NodeCounts {
+        self.cover.node_counts()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cover.reporter.is_cancelled()
+    }
+
+    fn limit_reached(&self) -> Option<SearchLimit> {
+        self.cover.limit_reached
+    }
+
+    fn dlx_stats(&self) -> Option<DlxStats> {
+        Some(self.cover.stats())
+    }
+
+    /// Returns all the distinct solutions found during the enumeration as 1D `String`s.
+    fn solutions(&self) -> Vec<String> {
+        self.solutions
+            .iter()
+            .map(|solution| solution.iter().map(|&v| value_to_char(v)).collect())
+            .collect()
+    }
 }
 
 impl DlxSolver {
-    pub fn new(sudoku: Sudoku) -> Self {
-        // Universe is hardcoded for the 9x9 grid size
-        let universe = 9 * 9 * 4;
+    #[allow(dead_code)]
+    pub fn new(sudoku: Sudoku) -> Result<Self, SudokuError> {
+        Self::with_max_solutions(sudoku, 1)
+    }
+
+    /// Constructs a new solver which continues the search after each found solution until
+    /// `max_solutions` distinct solutions are found or the search space is exhausted. Returns
+    /// `Err` if the exact cover matrix can't be constructed from the grid.
+    pub fn with_max_solutions(sudoku: Sudoku, max_solutions: usize) -> Result<Self, SudokuError> {
+        let universe = universe_of(&sudoku);
+        Self::with_universe(sudoku, max_solutions, universe, None)
+    }
+
+    /// Constructs a new solver like `with_max_solutions`, but only with the rows of the digits
+    /// allowed by the pencil marks of the 9x9 grid, i.e. the `candidates` bitmask of every cell in
+    /// row-major order in the format of `Sudoku::candidates_for`. A puzzle the marks leave without
+    /// a solution is reported as unsolvable by the search like any other.
+    pub fn with_candidates(
+        sudoku: Sudoku,
+        max_solutions: usize,
+        candidates: &[u16; 81],
+    ) -> Result<Self, SudokuError> {
+        if sudoku.size() != 9 {
+            return Err(SudokuError::SizeMismatch {
+                size: sudoku.size(),
+                expected: 9,
+            });
+        }
+
+        let universe = universe_of(&sudoku);
+        Self::with_universe(sudoku, max_solutions, universe, Some(candidates))
+    }
+
+    /// Constructs a new solver like `with_max_solutions`, but with the given number of columns
+    /// in the `universe` instead of the one of the grid size, restricted to the `candidates` if
+    /// any. The arena is reserved once for the exact number of nodes of the matrix.
+    fn with_universe(
+        sudoku: Sudoku,
+        max_solutions: usize,
+        universe: usize,
+        candidates: Option<&[u16]>,
+    ) -> Result<Self, SudokuError> {
+        let digits = allowed_digits(&sudoku, candidates);
+        let size = sudoku.size();
+        let bodies = Pos::all(size)
+            .map(|pos| digits[pos.index(size)].count_ones() as usize * row_len(&sudoku, pos))
+            .sum();
+
+        let solver = Self::with_arena(sudoku, max_solutions, universe, &digits, |nodes| {
+            nodes.reserve_exact(universe, bodies)
+        })?;
+        debug_assert!(
+            !solver.cover.arena_reallocated,
+            "Arena reserved for fewer nodes than the matrix has"
+        );
+
+        Ok(solver)
+    }
+
+    /// Constructs a new solver like `new`, but with the arena reserved for 4 nodes per column and
+    /// grown on demand beyond them, as it was before the exact reservation. Only compiled for the
+    /// comparison of the two in the tests and in the benches with the `bench` feature.
+    #[cfg(any(test, feature = "bench"))]
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_amortized_arena(sudoku: Sudoku) -> Result<Self, SudokuError> {
+        let universe = universe_of(&sudoku);
+        let digits = allowed_digits(&sudoku, None);
+
+        Self::with_arena(sudoku, 1, universe, &digits, |nodes| {
+            nodes.reserve(4 * universe)
+        })
+    }
+
+    /// Constructs a new solver with the rows of the `digits` of every cell, the arena of which is
+    /// reserved by `reserve` before the header is inserted.
+    fn with_arena(
+        sudoku: Sudoku,
+        max_solutions: usize,
+        universe: usize,
+        digits: &[u32],
+        reserve: impl FnOnce(&mut Nodes),
+    ) -> Result<Self, SudokuError> {
+        let mut cover = ExactCover::without_nodes(universe);
+        reserve(&mut cover.nodes);
+        cover.init(universe)?;
+
         let mut solver = Self {
             sudoku,
-            nodes: Vec::with_capacity(4 * universe),
-            num_of_cols: universe,
-            row_table: Vec::new(),
+            cover,
             subset_data: Vec::new(),
-            visited_nodes: 0,
+            max_solutions,
+            solutions: Vec::new(),
+            parallel: false,
+            corrupted: false,
         };
+        solver.grid_to_problem(digits)?;
 
-        solver.init(universe);
-        solver.grid_to_problem();
+        Ok(solver)
+    }
+
+    /// Constructs a new solver from a copy of the unpruned `template` matrix of the grid size,
+    /// the rows of the digits ruled out by the clues of the `sudoku` being removed from it. The
+    /// rows left are the same as with `with_max_solutions` and in the same order, so is the
+    /// search. Returns `Err` if the `sudoku` isn't a classic one of the size of the template.
+    pub fn with_template(
+        template: &MatrixTemplate,
+        sudoku: &Sudoku,
+        max_solutions: usize,
+    ) -> Result<Self, SudokuError> {
+        if sudoku.size() != template.size {
+            return Err(SudokuError::SizeMismatch {
+                size: sudoku.size(),
+                expected: template.size,
+            });
+        }
 
-        solver
+        if sudoku.variant() != Variant::Classic {
+            return Err(SudokuError::UnsupportedVariant(sudoku.variant()));
+        }
+
+        let size = sudoku.size();
+        let mut cover = ExactCover::without_nodes(size * size * 4);
+        cover.nodes = template.nodes.clone();
+        cover.row_table = template.row_table.clone();
+        cover.rows_appended = template.row_table.len();
+
+        let masks = sudoku.candidate_masks();
+
+        for (row, &(pos, k)) in template.subset_data.iter().enumerate() {
+            if masks[pos.index(size)] & (1 << (k + 1)) == 0 {
+                cover.remove_row(row);
+            }
+        }
+
+        Ok(Self {
+            sudoku: sudoku.clone(),
+            cover,
+            subset_data: template.subset_data.clone(),
+            max_solutions,
+            solutions: Vec::new(),
+            parallel: false,
+            corrupted: false,
+        })
     }
 
-    /// Initializes the exact cover representation by inserting a head node and a column row
-    /// (and doing the necessary linking).
-    fn init(&mut self, universe: usize) {
-        // Insert head node and the column row
-        let nodes = &mut self.nodes;
-        nodes.push(Node::new(Point::Head(0)));
-        nodes.extend(repeat(Node::new(Point::Column(0))).take(universe));
+    /// Clears the results and the counters of the previous searches, so that the next one starts
+    /// afresh. The matrix itself is already restored to its state after the construction by every
+    /// search, as the columns are uncovered on the way back even if the search is stopped.
+    #[allow(dead_code)]
+    pub fn reset(&mut self) {
+        self.cover.reset();
+        self.solutions.clear();
+        self.corrupted = false;
+    }
 
-        // Link the whole header row in both dimensions
-        for (idx, node) in nodes.iter_mut().enumerate() {
-            // Selflink in Up-Down axis
-            *node.assign(Direction::Next) = idx + 1;
-            *node.assign(Direction::Prev) = idx.wrapping_sub(1);
-            *node.assign(Direction::Up) = idx;
-            *node.assign(Direction::Down) = idx;
+    /// Counts the distinct solutions of the Sudoku up to `limit`, e.g. `limit = 2` is enough to
+    /// tell whether the puzzle is proper. Returns `None` if the search visits more than
+    /// `node_budget` nodes before it's able to tell the count.
+    pub fn count_solutions(&mut self, limit: usize, node_budget: Option<u64>) -> Option<usize> {
+        self.cover.node_budget = node_budget;
+        self.collect_solutions(limit);
+
+        match self.cover.budget_exceeded || self.corrupted {
+            true => None,
+            false => Some(self.solutions.len()),
         }
+    }
 
-        // Fixup begin/end
-        let len = nodes.len();
-        *nodes[0].assign(Direction::Prev) = len - 1;
-        *nodes[len - 1].assign(Direction::Next) = 0;
+    /// Sets the node and time limits of the search, see `ExactCover::set_limits`.
+    pub fn set_limits(&mut self, max_nodes: Option<u64>, timeout: Option<Duration>) {
+        self.cover.set_limits(max_nodes, timeout);
+    }
+
+    /// Sets the heuristic picking the next column to cover, see `ColumnSelection`.
+    pub fn set_column_selection(&mut self, column_selection: ColumnSelection) {
+        self.cover.set_column_selection(column_selection);
     }
 
-    /// Converts the 2D Sudoku grid (9x9) into an exact cover representation by calculating
-    /// the necessary constraints.
-    fn grid_to_problem(&mut self) {
-        for i in 0..9 {
-            for j in 0..9 {
-                self.calculate_constraint(i, j);
+    /// Sets whether the rows of the first chosen column are explored in parallel, see
+    /// `ExactCover::solve_first_parallel`. Only applied to the search of the first solution, i.e.
+    /// ignored if `max_solutions` is above 1 and by `count_solutions`.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Tells whether the Sudoku is proper by stopping the search as soon as a second solution is
+    /// found. Returns `None` if the grid has no solution at all, or if the search visits more
+    /// than `node_budget` nodes before it's able to tell. The inner grid holds the first solution
+    /// afterwards, so it doesn't need to be solved again.
+    pub fn is_unique(&mut self, node_budget: Option<u64>) -> Option<bool> {
+        match self.count_solutions(2, node_budget)? {
+            0 => None,
+            count => Some(count == 1),
+        }
+    }
+
+    /// Enumerates the distinct solutions of the Sudoku by continuing the search after each one,
+    /// `visit` being invoked with every solution as a 1D grid. Stops once `limit` solutions are
+    /// found or `visit` breaks, and returns the number of solutions visited. A solution failing
+    /// the verification stops the enumeration without being visited, see `is_corrupted`. The
+    /// matrix is restored afterwards, so the enumeration can be repeated.
+    pub fn enumerate(
+        &mut self,
+        limit: usize,
+        mut visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+    ) -> usize {
+        let (sudoku, subset_data) = (&self.sudoku, &self.subset_data);
+        let mut corrupted = false;
+
+        let found = self.cover.enumerate(limit, |rows| {
+            match verified_solution(sudoku, subset_data, rows) {
+                Some(solution) => visit(&solution),
+                None => {
+                    corrupted = true;
+                    ControlFlow::Break(())
+                }
             }
+        });
+        self.corrupted = corrupted;
+
+        found - usize::from(corrupted)
+    }
+
+    /// Enumerates up to `limit` solutions into `self.solutions`, the first one also replacing the
+    /// partially solved inner grid.
+    fn collect_solutions(&mut self, limit: usize) {
+        let mut solutions = Vec::new();
+        self.enumerate(limit, |solution| {
+            solutions.push(solution.to_vec());
+            ControlFlow::Continue(())
+        });
+
+        if let Some(first) = solutions.first() {
+            self.sudoku.set_solution(first);
         }
+
+        self.solutions = solutions;
+    }
+
+    /// Searches for the first solution with `ExactCover::solve_first_parallel` into
+    /// `self.solutions`, also replacing the partially solved inner grid with it.
+    fn solve_parallel(&mut self) {
+        let rows = self.cover.solve_first_parallel();
+        let first = rows
+            .as_ref()
+            .and_then(|rows| verified_solution(&self.sudoku, &self.subset_data, rows));
+        self.corrupted = rows.is_some() && first.is_none();
+
+        if let Some(first) = &first {
+            self.sudoku.set_solution(first);
+        }
+
+        self.solutions = first.into_iter().collect();
     }
 
-    fn calculate_constraint(&mut self, i: usize, j: usize) {
-        let value = self.sudoku.get_grid_value(&(i, j));
+    /// Gives access to the cell and digit of every row for corrupting them in the tests of the
+    /// verification of the solutions.
+    #[cfg(test)]
+    pub(crate) fn subset_data_mut(&mut self) -> &mut [(Pos, usize)] {
+        &mut self.subset_data
+    }
+
+    /// Returns the number of rows in the exact cover matrix, i.e. the candidate cell and digit
+    /// pairs left after the peers of the clues are ruled out.
+    #[allow(dead_code)]
+    pub fn matrix_rows(&self) -> usize {
+        self.cover.num_rows()
+    }
+
+    /// Converts the 2D Sudoku grid (of any size) into an exact cover representation by
+    /// calculating the necessary constraints for the `digits` of every cell, see
+    /// `allowed_digits`.
+    fn grid_to_problem(&mut self, digits: &[u32]) -> Result<(), SudokuError> {
+        let size = self.sudoku.size();
+
+        for pos in Pos::all(size) {
+            self.calculate_constraint(pos, digits[pos.index(size)])?;
+        }
 
-        // Hardcoded variables for 9x9 grids
-        let nu = 9;
-        let offset = 1;
-        let cat_offset = nu * nu;
+        Ok(())
+    }
 
-        for k in 0..9 {
-            // Skip filled cells
-            if value != 0 && k as u8 + 1 != value {
+    /// Appends a row for every digit allowed by the `candidates` bitmask of the cell at `pos`,
+    /// i.e. only for the value of a filled cell, and leaves out the digits already taken by a
+    /// peer of an empty one since no solution could contain them, as well as the ones ruled out by
+    /// the pencil marks of the client. The cells on a diagonal of an X-Sudoku also get the column
+    /// of their digit on it. Returns `Err` if a constraint falls outside of the columns of the
+    /// matrix.
+    fn calculate_constraint(&mut self, pos: Pos, candidates: u32) -> Result<(), SudokuError> {
+        for k in 0..self.sudoku.size() {
+            if candidates & (1 << (k + 1)) == 0 {
                 continue;
             }
 
-            let b = (i / 3) * 3 + (j / 3);
+            // Append the row to the exact cover matrix and store the subset data
+            self.cover.append_row(row_columns(&self.sudoku, pos, k))?;
+            self.subset_data.push((pos, k));
+        }
 
-            #[allow(clippy::erasing_op, clippy::identity_op)]
-            let constraints = [
-                offset + 0 * cat_offset + i + j * nu, // RxCy
-                offset + 1 * cat_offset + i + k * nu, // Rx#z
-                offset + 2 * cat_offset + j + k * nu, // Cy#z
-                offset + 3 * cat_offset + b + k * nu, // Bb#z
-            ];
+        Ok(())
+    }
+}
 
-            // Append the row to the exact cover matrix and store the subset data
-            self.append_row(constraints).unwrap();
-            self.subset_data.push([i, j, k]);
+/// Returns the number of columns of the exact cover matrix of the `sudoku`, i.e. one for each of
+/// the cell, row, column, and square constraints of every cell or digit, plus one for each digit
+/// on both of the diagonals of an X-Sudoku.
+fn universe_of(sudoku: &Sudoku) -> usize {
+    let size = sudoku.size();
+
+    match sudoku.variant() {
+        Variant::Classic => size * size * 4,
+        Variant::X => size * size * 4 + 2 * size,
+    }
+}
+
+/// Inconsistency of a solution extracted from the exact cover matrix, i.e. a bug in the
+/// bookkeeping of the rows rather than a property of the puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CorruptSolution {
+    /// The solution doesn't consist of a row for each cell.
+    RowCount { rows: usize, cells: usize },
+    /// More than one row of the solution fills the cell, leaving another one empty.
+    RepeatedCell(Pos),
+    /// The row of a given cell has another digit than the clue.
+    AlteredGiven(Pos),
+    /// The digits of the solution break the rules of the puzzle in the unit.
+    Violation(Unit, usize),
+}
+
+impl fmt::Display for CorruptSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorruptSolution::RowCount { rows, cells } => {
+                write!(f, "{} rows selected for {} cells", rows, cells)
+            }
+            CorruptSolution::RepeatedCell(pos) => write!(f, "cell {} filled more than once", pos),
+            CorruptSolution::AlteredGiven(pos) => write!(f, "given cell {} altered", pos),
+            CorruptSolution::Violation(unit, index) => {
+                write!(f, "digit repeated in {} {}", unit, index)
+            }
         }
     }
+}
 
-    #[inline]
-    fn head(&self) -> usize {
-        0
+/// Returns the bitmasks of the digits of every cell to append a row for, i.e. the `candidate_masks`
+/// of the `sudoku` without the digits ruled out by the pencil marks of the `candidates` if any.
+fn allowed_digits(sudoku: &Sudoku, candidates: Option<&[u16]>) -> Vec<u32> {
+    let masks = sudoku.candidate_masks();
+
+    match candidates {
+        Some(candidates) => masks
+            .iter()
+            .zip(candidates)
+            .map(|(&mask, &marks)| mask & u32::from(marks))
+            .collect(),
+        None => masks,
     }
+}
 
-    #[inline]
-    fn head_node(&self) -> &Node<Point> {
-        &self.nodes[0]
+/// Returns the columns of the row of the digit `k + 1` in the cell at `pos`, i.e. of the cell,
+/// row, column, and square constraints it satisfies, plus the diagonals of an X-Sudoku it lies on.
+fn row_columns(sudoku: &Sudoku, pos: Pos, k: usize) -> impl Iterator<Item = usize> {
+    let (i, j) = (pos.row(), pos.col());
+
+    let nu = sudoku.size();
+    let offset = 1;
+    let cat_offset = nu * nu;
+    let b = pos.box_index(nu.isqrt());
+    let x = sudoku.variant() == Variant::X;
+
+    #[allow(clippy::erasing_op, clippy::identity_op)]
+    let constraints = [
+        offset + 0 * cat_offset + i + j * nu, // RxCy
+        offset + 1 * cat_offset + i + k * nu, // Rx#z
+        offset + 2 * cat_offset + j + k * nu, // Cy#z
+        offset + 3 * cat_offset + b + k * nu, // Bb#z
+    ];
+    let diagonals = [
+        (x && i == j).then_some(offset + 4 * cat_offset + k), // D0#z
+        (x && i + j == nu - 1).then_some(offset + 4 * cat_offset + nu + k), // D1#z
+    ];
+
+    constraints
+        .into_iter()
+        .chain(diagonals.into_iter().flatten())
+}
+
+/// Returns the number of nodes in each row of the cell at `pos`, the same for all of its digits.
+fn row_len(sudoku: &Sudoku, pos: Pos) -> usize {
+    row_columns(sudoku, pos, 0).count()
+}
+
+/// Converts the rows of the solution to the grid format using the `subset_data` of the rows and
+/// checks the result, logging the diagnostics of a corrupt one. Returns `None` in that case, to
+/// be reported as an internal error instead of a solution.
+fn verified_solution(
+    sudoku: &Sudoku,
+    subset_data: &[(Pos, usize)],
+    rows: &[RowId],
+) -> Option<Vec<u8>> {
+    decode_solution(sudoku, subset_data, rows)
+        .inspect_err(|e| {
+            error!(
+                error = %e,
+                rows = rows.len(),
+                "Exact cover matrix yielded a corrupt solution"
+            );
+        })
+        .ok()
+}
+
+/// Collects the digits of the rows of the solution into a 1D grid, which must have exactly one
+/// row for each cell, keep the givens, and satisfy the rules of the `sudoku`.
+fn decode_solution(
+    sudoku: &Sudoku,
+    subset_data: &[(Pos, usize)],
+    rows: &[RowId],
+) -> Result<Vec<u8>, CorruptSolution> {
+    let size = sudoku.size();
+    let cells = size * size;
+
+    if rows.len() != cells {
+        return Err(CorruptSolution::RowCount {
+            rows: rows.len(),
+            cells,
+        });
     }
 
-    #[inline]
-    fn walk_from(&self, idx: usize) -> Walker {
-        Walker { idx, start: idx }
+    // With as many rows as cells, a cell left empty implies another one filled twice
+    let mut solution = vec![0; cells];
+
+    for &RowId(i) in rows {
+        let (pos, k) = subset_data[i];
+        let cell = &mut solution[pos.index(size)];
+
+        if *cell != 0 {
+            return Err(CorruptSolution::RepeatedCell(pos));
+        }
+        *cell = (k + 1) as u8;
+    }
+
+    // Every solution must agree with the givens, which keep their values in the grid even after
+    // the first solution replaced it
+    if let Some(pos) = Pos::all(size).find(|&pos| {
+        sudoku.is_given(pos) && sudoku.get_grid_value(pos) != solution[pos.index(size)]
+    }) {
+        return Err(CorruptSolution::AlteredGiven(pos));
+    }
+
+    let mut grid = sudoku.clone();
+    grid.set_solution(&solution);
+
+    match grid.find_violation(None) {
+        Some((unit, index)) => Err(CorruptSolution::Violation(unit, index)),
+        None => Ok(solution),
+    }
+}
+
+impl ExactCover {
+    /// Constructs an empty matrix of `num_columns` columns, to which the rows are appended with
+    /// `append_row`. Returns `Err` if the header alone wouldn't fit into the arena of the nodes.
+    #[allow(dead_code)]
+    pub fn new(num_columns: usize) -> Result<Self, ExactCoverError> {
+        let mut cover = Self::without_nodes(num_columns);
+        cover.init(num_columns)?;
+        Ok(cover)
+    }
+
+    /// Constructs a matrix of `num_columns` columns without even the header nodes, which the
+    /// caller has to provide.
+    fn without_nodes(num_columns: usize) -> Self {
+        Self {
+            nodes: Nodes::default(),
+            num_of_cols: num_columns,
+            row_table: Vec::new(),
+            removed_rows: 0,
+            visited_nodes: 0,
+            propagations: 0,
+            scans: 0,
+            column_selection: ColumnSelection::default(),
+            covers: 0,
+            uncovers: 0,
+            rows_appended: 0,
+            arena_reallocated: false,
+            node_budget: None,
+            budget_exceeded: false,
+            max_nodes: None,
+            timeout: None,
+            deadline: None,
+            limit_reached: None,
+            reporter: ProgressReporter::default(),
+        }
+    }
+
+    /// Returns the first solution found as the selected rows, `None` if there's none or the
+    /// search is stopped before finding one.
+    #[allow(dead_code)]
+    pub fn solve_first(&mut self) -> Option<Vec<RowId>> {
+        let mut first = None;
+        self.enumerate(1, |rows| {
+            first = Some(rows.to_vec());
+            ControlFlow::Continue(())
+        });
+
+        first
+    }
+
+    /// Enumerates the distinct solutions by continuing the search after each one, `visit` being
+    /// invoked with the selected rows of every solution. Stops once `limit` solutions are found,
+    /// `visit` breaks or the search reaches a limit, and returns the number of solutions visited.
+    /// The matrix is restored afterwards, so the enumeration can be repeated.
+    pub fn enumerate(
+        &mut self,
+        limit: usize,
+        mut visit: impl FnMut(&[RowId]) -> ControlFlow<()>,
+    ) -> usize {
+        let mut found = 0;
+        self.budget_exceeded = false;
+        self.limit_reached = None;
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        if limit > 0 {
+            self.algox(&mut |rows| {
+                found += 1;
+
+                match visit(rows) {
+                    ControlFlow::Continue(()) if found < limit => ControlFlow::Continue(()),
+                    _ => ControlFlow::Break(()),
+                }
+            });
+        }
+
+        found
+    }
+
+    /// Returns the first solution like `solve_first`, but explores the rows of the first chosen
+    /// column in parallel on the rayon pool, each on its own copy of the matrix, the others being
+    /// stopped once one of them finds a solution. The solution of the first row (in the order of
+    /// the column) which has one is returned, i.e. the same as by `solve_first` if the problem has
+    /// a single solution, while any of them might be returned otherwise. The counters add up the
+    /// work done on all of the rows, which the node limit applies to together, the workers sharing
+    /// the budget at every check of the stop flag (i.e. overshooting it by less than
+    /// `PARALLEL_CHECK_INTERVAL_NODES` each).
+    pub fn solve_first_parallel(&mut self) -> Option<Vec<RowId>> {
+        self.budget_exceeded = false;
+        self.limit_reached = None;
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        if self.nodes.get_link(self.head(), Direction::Next) == self.head() {
+            return Some(Vec::new());
+        }
+
+        let col = self.choose_column()?;
+        self.cover(col);
+
+        let mut rows = Vec::new();
+        let mut col_rows = self.walk_from(col.0);
+
+        while let Some(row) = col_rows.next_body(self, Direction::Down) {
+            rows.push(row);
+        }
+
+        // The hook of the caller and the node budget are shared by the workers, which report the
+        // visited nodes of all of them together
+        let stop = Arc::new(AtomicBool::new(false));
+        let exhausted = Arc::new(AtomicBool::new(false));
+        let visited = Arc::new(AtomicU64::new(self.visited_nodes));
+        let reporter = Arc::new(Mutex::new(mem::take(&mut self.reporter)));
+        let workers: Vec<_> = rows
+            .into_iter()
+            .map(|row| {
+                let hook =
+                    Self::worker_hook(&stop, &exhausted, &visited, &reporter, self.max_nodes);
+                (
+                    row,
+                    self.fork(ProgressReporter::new(hook, PARALLEL_CHECK_INTERVAL_NODES)),
+                )
+            })
+            .collect();
+
+        let results: Vec<_> = workers
+            .into_par_iter()
+            .map(|(row, mut worker)| {
+                let solution = worker.solve_branch(row);
+
+                if solution.is_some() {
+                    stop.store(true, Ordering::Relaxed);
+                }
+
+                (solution, worker)
+            })
+            .collect();
+
+        self.uncover(col);
+        let mut first = None;
+
+        for (solution, worker) in results {
+            self.visited_nodes += worker.visited_nodes;
+            self.propagations += worker.propagations;
+            self.scans += worker.scans;
+            self.covers += worker.covers;
+            self.uncovers += worker.uncovers;
+            self.limit_reached = self.limit_reached.or(worker.limit_reached);
+            first = first.or(solution);
+        }
+
+        if exhausted.load(Ordering::Relaxed) {
+            self.limit_reached = Some(SearchLimit::Nodes);
+        }
+
+        // The hooks of the workers holding the other references are dropped with them
+        self.reporter = Arc::into_inner(reporter)
+            .expect("Reporter still shared after the parallel search")
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        // A limit reached by another row doesn't matter once a solution is found
+        if first.is_some() {
+            self.limit_reached = None;
+        }
+
+        first
+    }
+
+    /// Returns the progress hook of a worker of `solve_first_parallel`, which stops the worker
+    /// once the `stop` flag is set, and otherwise forwards the progress of all the workers to the
+    /// `reporter` of the caller, setting the flag if it cancels the search. The `exhausted` flag is
+    /// set along with it once the workers visited `max_nodes` together.
+    fn worker_hook(
+        stop: &Arc<AtomicBool>,
+        exhausted: &Arc<AtomicBool>,
+        visited: &Arc<AtomicU64>,
+        reporter: &Arc<Mutex<ProgressReporter>>,
+        max_nodes: Option<u64>,
+    ) -> ProgressHook {
+        let (stop, exhausted, visited, reporter) = (
+            stop.clone(),
+            exhausted.clone(),
+            visited.clone(),
+            reporter.clone(),
+        );
+
+        Box::new(move |progress| {
+            let visited_nodes = visited.fetch_add(PARALLEL_CHECK_INTERVAL_NODES, Ordering::Relaxed)
+                + PARALLEL_CHECK_INTERVAL_NODES;
+
+            if stop.load(Ordering::Relaxed) {
+                return false;
+            }
+
+            if max_nodes.is_some_and(|max| visited_nodes >= max) {
+                exhausted.store(true, Ordering::Relaxed);
+                stop.store(true, Ordering::Relaxed);
+                return false;
+            }
+
+            let running = reporter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .tick(visited_nodes, progress.depth + 1);
+
+            if !running {
+                stop.store(true, Ordering::Relaxed);
+            }
+
+            running
+        })
+    }
+
+    /// Copies the matrix in its current state for a worker of `solve_first_parallel`, with the
+    /// limits of the search but none of its counters.
+    fn fork(&self, reporter: ProgressReporter) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            row_table: self.row_table.clone(),
+            removed_rows: self.removed_rows,
+            column_selection: self.column_selection,
+            max_nodes: self.max_nodes,
+            timeout: self.timeout,
+            deadline: self.deadline,
+            reporter,
+            ..Self::without_nodes(self.num_of_cols)
+        }
+    }
+
+    /// Searches for the first solution including the `row` of the column covered by
+    /// `solve_first_parallel`, the same way as the search tries the row at the first level.
+    fn solve_branch(&mut self, row: BodyRef) -> Option<Vec<RowId>> {
+        self.visited_nodes += 1;
+
+        if !self.reporter.tick(self.visited_nodes, 1) {
+            return None;
+        }
+
+        let mut r_walker = self.walk_from(row.0);
+
+        while let Some(rj) = r_walker.next_body(self, Direction::Next) {
+            self.cover(self.nodes.column(rj));
+        }
+
+        let first = RowId(self.row_index_of(row));
+        let mut solution = None;
+        self.algox(&mut |rows| {
+            solution = Some(once(first).chain(rows.iter().copied()).collect());
+            ControlFlow::Break(())
+        });
+
+        let mut r_walker = self.walk_from(row.0);
+
+        while let Some(rj) = r_walker.next_body(self, Direction::Prev) {
+            self.uncover(self.nodes.column(rj));
+        }
+
+        solution
+    }
+
+    /// Sets the node and time limits of the search, reported by `limit_reached` once reached
+    /// unlike the inconclusive `node_budget` of `DlxSolver::count_solutions`.
+    pub fn set_limits(&mut self, max_nodes: Option<u64>, timeout: Option<Duration>) {
+        self.max_nodes = max_nodes;
+        self.timeout = timeout;
+    }
+
+    /// Sets the heuristic picking the next column to cover, `ColumnSelection::MinCount` by
+    /// default.
+    pub fn set_column_selection(&mut self, column_selection: ColumnSelection) {
+        self.column_selection = column_selection;
+    }
+
+    /// Returns the limit which stopped the last search before it finished, if any.
+    #[allow(dead_code)]
+    pub fn limit_reached(&self) -> Option<SearchLimit> {
+        self.limit_reached
+    }
+
+    /// Returns the counters of the work done by the searches since the construction or `reset`.
+    pub fn node_counts(&self) -> NodeCounts {
+        NodeCounts {
+            decisions: self.visited_nodes,
+            propagations: self.propagations,
+            scans: self.scans,
+        }
+    }
+
+    /// Returns the counters of the covered and uncovered columns since the construction or
+    /// `reset`, and the size of the matrix.
+    pub fn stats(&self) -> DlxStats {
+        DlxStats {
+            covers: self.covers,
+            uncovers: self.uncovers,
+            rows_appended: self.rows_appended,
+            arena_nodes: self.nodes.len(),
+        }
+    }
+
+    /// Returns the number of rows in the matrix.
+    pub fn num_rows(&self) -> usize {
+        self.row_table.len() - self.removed_rows
+    }
+
+    /// Clears the counters and the limit reached by the previous searches, the matrix itself
+    /// being restored by every search.
+    fn reset(&mut self) {
+        self.visited_nodes = 0;
+        self.propagations = 0;
+        self.scans = 0;
+        self.covers = 0;
+        self.uncovers = 0;
+        self.budget_exceeded = false;
+        self.limit_reached = None;
+    }
+
+    /// Removes the items of the `row` from their columns for good, unlike covering which is
+    /// undone by uncovering.
+    fn remove_row(&mut self, row: usize) {
+        let start = BodyRef(self.row_table[row]);
+        let mut items = self.walk_from(start.0);
+        self.remove(start.0, Direction::Down);
+        *self.col_head_value_mut(start) -= 1;
+
+        while let Some(body) = items.next_body(self, Direction::Next) {
+            self.remove(body.0, Direction::Down);
+            *self.col_head_value_mut(body) -= 1;
+        }
+
+        self.removed_rows += 1;
+    }
+
+    /// Initializes the exact cover representation by inserting a head node and a column row
+    /// (and doing the necessary linking).
+    /// Returns `Err` if the header row wouldn't fit into the arena.
+    fn init(&mut self, universe: usize) -> Result<(), ExactCoverError> {
+        // Checked upfront rather than node by node, the header being allocated at once
+        if universe >= MAX_NODES {
+            return Err(ExactCoverError::MatrixTooLarge { max: MAX_NODES });
+        }
+
+        // Insert head node and the column row
+        let nodes = &mut self.nodes;
+        nodes.reserve_exact(universe, 0);
+        nodes.push_head()?;
+
+        for _ in 0..universe {
+            nodes.push_column()?;
+        }
+
+        // Link the whole header row in both dimensions
+        let len = nodes.len();
+
+        for idx in 0..len {
+            // Selflink in Up-Down axis
+            nodes
+                .set_link(idx, idx + 1, Direction::Next)
+                .set_link(idx, idx.wrapping_sub(1), Direction::Prev)
+                .set_link(idx, idx, Direction::Up)
+                .set_link(idx, idx, Direction::Down);
+        }
+
+        // Fixup begin/end
+        nodes
+            .set_link(0, len - 1, Direction::Prev)
+            .set_link(len - 1, 0, Direction::Next);
+
+        Ok(())
     }
 
     #[inline]
-    fn get_node_value(&self, idx: usize) -> usize {
-        self.nodes[idx].value.value()
+    fn head(&self) -> usize {
+        0
     }
 
     #[inline]
-    fn get_col_head(&self, idx: usize) -> usize {
-        assert!(
-            idx > self.num_of_cols,
-            "Expected row item index, got {}",
-            idx
-        );
-        self.get_node_value(idx)
+    fn walk_from(&self, idx: usize) -> Walker {
+        Walker { idx, start: idx }
     }
 
     /// Returns a mutable value of the row item's column head.
     #[inline]
-    fn col_head_value_mut(&mut self, idx: usize) -> &mut usize {
-        let col_head_idx = self.get_col_head(idx);
-        self.nodes[col_head_idx].value.value_mut()
+    fn col_head_value_mut(&mut self, body: BodyRef) -> &mut u32 {
+        let col = self.nodes.column(body);
+        self.nodes.count_mut(col)
     }
 
-    /// Returns a row index for a node index.
+    /// Returns a row index for a row item.
     #[inline]
-    fn row_index_of(&self, idx: usize) -> usize {
-        let pos = self.row_table.partition_point(move |&x| x <= idx);
+    fn row_index_of(&self, body: BodyRef) -> usize {
+        let pos = self.row_table.partition_point(move |&x| x <= body.0);
         assert_ne!(pos, 0, "Solution contains index before first row");
         pos - 1
     }
 
-    /// Converts the node indices (the solution format outputted by the solver) to row indices,
-    /// converts the row indices to the grid format using the `self.subset_data` contents, sorts
-    /// the result, and finally collects it into a 1D vector format. After this conversion
-    /// process the result is passed to the inner Sudoku's `set_solution` method, which replaces
-    /// the partially solved grid with the full solution.
-    fn set_solution(&mut self, solution: &mut [usize]) {
-        let solution_rows: Vec<usize> = solution.iter().map(|&s| self.row_index_of(s)).collect();
-        let subset_data = self.subset_data.clone();
-        let mut solution_data: Vec<_> =
-            solution_rows.iter().map(move |&i| subset_data[i]).collect();
-        solution_data.sort_by_key(|d| (d[0], d[1]));
-        let final_solution: Vec<u8> = solution_data.iter().map(|d| (d[2] + 1) as u8).collect();
-
-        self.sudoku.set_solution(&final_solution);
-    }
-
     /// Appends a new item `new_idx` to an existing column `col` of the DLX matrix.
-    fn append_to_col(&mut self, col: usize, new_idx: usize) {
-        assert!(
-            col <= self.num_of_cols && col != 0,
-            "Invalid column {}",
-            col
-        );
-        assert!(new_idx < self.nodes.len(), "Invalid index {}", new_idx);
-        assert!(matches!(self.nodes[new_idx].value, Point::Body(_)));
-
-        let head_idx = col;
-        let head = &mut self.nodes[head_idx];
-        let old_end = head.get_link(Direction::Up);
-
-        head.set_link(new_idx, Direction::Up);
-        *head.value.value_mut() += 1;
-        self.nodes[old_end].set_link(new_idx, Direction::Down);
-        self.nodes[new_idx]
-            .set_link(old_end, Direction::Up)
-            .set_link(head_idx, Direction::Down);
+    fn append_to_col(&mut self, col: ColumnRef, new: BodyRef) {
+        assert!(new.0 < self.nodes.len(), "Invalid index {}", new.0);
+
+        let (head_idx, new_idx) = (col.0, new.0);
+        let old_end = self.nodes.get_link(head_idx, Direction::Up);
+
+        self.nodes.set_link(head_idx, new_idx, Direction::Up);
+        *self.nodes.count_mut(col) += 1;
+        self.nodes
+            .set_link(old_end, new_idx, Direction::Down)
+            .set_link(new_idx, old_end, Direction::Up)
+            .set_link(new_idx, head_idx, Direction::Down);
     }
 
     /// Tries to append a new row to the DLX matrix, triggers a rollback by returning `Err` if
     /// the input doesn't match the basic criteria.
-    fn try_append(&mut self, row: impl IntoIterator<Item = usize>) -> Result<(), Box<dyn Error>> {
+    fn try_append(&mut self, row: impl IntoIterator<Item = usize>) -> Result<(), ExactCoverError> {
         let original_len = self.nodes.len();
 
         for r in row {
-            if r == 0 {
-                return Err("Invalid column zero".into());
+            if r == 0 || r > self.num_of_cols {
+                return Err(ExactCoverError::ColumnOutOfRange {
+                    col: r,
+                    cols: self.num_of_cols,
+                });
             }
 
-            if r > self.num_of_cols {
-                return Err("Input outside of the defined universe".into());
+            // A column linked twice into the row would be covered twice by `cover`
+            let row_columns = &self.nodes.columns[original_len - self.nodes.header_len()..];
+            if row_columns.contains(&(r as u32)) {
+                return Err(ExactCoverError::DuplicateColumn { col: r });
             }
 
-            let body_node = Node::new(Point::Body(r));
-            self.nodes.push(body_node);
+            self.nodes.push_body(ColumnRef(r))?;
         }
 
         if self.nodes.len() == original_len {
-            return Err("Input must not be empty".into());
+            return Err(ExactCoverError::EmptyRow);
         }
 
         Ok(())
     }
 
-    /// Appends a row (a subset) to the DLX matrix.
-    fn append_row(&mut self, row: impl IntoIterator<Item = usize>) -> Result<(), Box<dyn Error>> {
+    /// Appends a row (a subset) covering the given columns to the DLX matrix, and returns its
+    /// `RowId`. The columns must be between 1 and the number of columns, the row can't be empty,
+    /// and its nodes must fit into the arena.
+    pub fn append_row(
+        &mut self,
+        row: impl IntoIterator<Item = usize>,
+    ) -> Result<RowId, ExactCoverError> {
         let start_idx = self.nodes.len();
+        let capacity = self.nodes.capacity();
 
         // Attempt to create nodes for all items
         if let Err(e) = self.try_append(row) {
             // Rollback on error
-            self.nodes.truncate(start_idx);
+            self.nodes.truncate_bodies(start_idx);
             return Err(e);
         }
 
         // Append new items to each column
         for idx in start_idx..self.nodes.len() {
-            self.append_to_col(self.nodes[idx].value.value(), idx);
+            let body = BodyRef(idx);
+            self.append_to_col(self.nodes.column(body), body);
         }
 
         // Link the Prev-Next axis
         let end_idx = self.nodes.len();
 
-        for (idx, node) in self.nodes[start_idx..].iter_mut().enumerate() {
-            let prev_idx = if idx == 0 {
+        for idx in start_idx..end_idx {
+            let prev_idx = if idx == start_idx {
                 end_idx - 1
             } else {
-                start_idx + idx - 1
+                idx - 1
             };
-            let next_idx = if start_idx + idx + 1 == end_idx {
+            let next_idx = if idx + 1 == end_idx {
                 start_idx
             } else {
-                start_idx + idx + 1
+                idx + 1
             };
 
-            node.set_link(prev_idx, Direction::Prev);
-            node.set_link(next_idx, Direction::Next);
+            self.nodes
+                .set_link(idx, prev_idx, Direction::Prev)
+                .set_link(idx, next_idx, Direction::Next);
         }
 
         self.row_table.push(start_idx);
+        self.rows_appended += 1;
+        self.arena_reallocated |= self.nodes.capacity() != capacity;
 
-        Ok(())
+        Ok(RowId(self.row_table.len() - 1))
     }
 
     /// Removes (hides) a single node defined by `idx` in direction `dir` from the doubly linked list.
@@ -382,12 +1417,10 @@ impl DlxSolver {
         let right = dir;
         let left = right.opposite();
 
-        let x = &self.nodes[idx];
-        let xr = x.get_link(right);
-        let xl = x.get_link(left);
+        let xr = self.nodes.get_link(idx, right);
+        let xl = self.nodes.get_link(idx, left);
 
-        self.nodes[xr].set_link(xl, left);
-        self.nodes[xl].set_link(xr, right);
+        self.nodes.set_link(xr, xl, left).set_link(xl, xr, right);
     }
 
     /// Restores a previously removed (hid) node defined by `idx` into the doubly linked list.
@@ -395,50 +1428,55 @@ impl DlxSolver {
         let right = dir;
         let left = dir.opposite();
 
-        let x = &self.nodes[idx];
-        let xr = x.get_link(right);
-        let xl = x.get_link(left);
+        let xr = self.nodes.get_link(idx, right);
+        let xl = self.nodes.get_link(idx, left);
 
-        self.nodes[xr].set_link(idx, left);
-        self.nodes[xl].set_link(idx, right);
+        self.nodes.set_link(xr, idx, left).set_link(xl, idx, right);
     }
 
     /// Covers a column by de-linking it from its neighbors in the matrix.
-    fn cover(&mut self, idx: usize) {
-        self.remove(idx, Direction::Next);
-        let mut rows = self.walk_from(idx);
+    fn cover(&mut self, col: ColumnRef) {
+        self.covers += 1;
+        self.remove(col.0, Direction::Next);
+        let mut rows = self.walk_from(col.0);
 
-        while let Some(ri) = rows.next(self, Direction::Down) {
-            let mut ri_walker = self.walk_from(ri);
+        while let Some(ri) = rows.next_body(self, Direction::Down) {
+            let mut ri_walker = self.walk_from(ri.0);
+            self.propagations += 1;
 
-            while let Some(rij) = ri_walker.next(self, Direction::Next) {
-                self.remove(rij, Direction::Down);
+            while let Some(rij) = ri_walker.next_body(self, Direction::Next) {
+                self.remove(rij.0, Direction::Down);
                 *self.col_head_value_mut(rij) -= 1;
             }
         }
     }
 
     /// Uncovers a column be re-linking it to its neighbors in the matrix.
-    fn uncover(&mut self, idx: usize) {
-        let mut rows = self.walk_from(idx);
+    fn uncover(&mut self, col: ColumnRef) {
+        self.uncovers += 1;
+        let mut rows = self.walk_from(col.0);
 
-        while let Some(ri) = rows.next(self, Direction::Up) {
-            let mut ri_walker = self.walk_from(ri);
+        while let Some(ri) = rows.next_body(self, Direction::Up) {
+            let mut ri_walker = self.walk_from(ri.0);
 
-            while let Some(rij) = ri_walker.next(self, Direction::Prev) {
-                self.restore(rij, Direction::Down);
+            while let Some(rij) = ri_walker.next_body(self, Direction::Prev) {
+                self.restore(rij.0, Direction::Down);
                 *self.col_head_value_mut(rij) += 1;
             }
         }
 
-        self.restore(idx, Direction::Next);
+        self.restore(col.0, Direction::Next);
     }
 
-    /// Solves the inner Sudoku using Donald Knuth's Algorithm X (straightforward recursive,
-    /// nondeterministic, depth-first, backtracking) with the dancing links technique. Uses
-    /// `partial_res` to handle partial solutions, which improves the perofmrance when
-    /// compared to calling `self.sudoku.set_grid_value` for every modification).
-    fn algox(&mut self, partial_res: &mut Vec<usize>) -> bool {
+    /// Solves the exact cover problem using Donald Knuth's Algorithm X (nondeterministic, depth-first,
+    /// backtracking) with the dancing links technique. The search is iterative over an explicit
+    /// stack of frames, each holding the chosen column and the row tried in it, which replaces the
+    /// recursion of the straightforward implementation while covering and uncovering the columns
+    /// in the same order. Returns `true` once `visit` breaks on a solution or the search is
+    /// stopped by a limit, otherwise the search continues after each solution until the whole
+    /// search space is explored. The covered columns are uncovered on the way back either way,
+    /// leaving the matrix as it was before the search.
+    fn algox(&mut self, visit: &mut dyn FnMut(&[RowId]) -> ControlFlow<()>) -> bool {
         /*
         1. If the current matrix A has no more columns, the partial solution is a valid solution. Termination.
         2. Otherwise choose a column c deterministically.
@@ -451,68 +1489,835 @@ impl DlxSolver {
         6. Repeat this algorithm recursively on the reduced matrix A
         */
 
-        if self.head_node().get_link(Direction::Next) == self.head() {
-            self.set_solution(partial_res);
-            return true;
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut stop = false;
+        // Whether the matrix reduced by the rows of the stack is yet to be examined
+        let mut descend = true;
+
+        loop {
+            if descend {
+                descend = false;
+
+                if self
+                    .node_budget
+                    .is_some_and(|budget| self.visited_nodes > budget)
+                {
+                    // Stop the search without a conclusive result
+                    self.budget_exceeded = true;
+                    stop = true;
+                } else if let Some(limit) = self.exceeded_limit() {
+                    debug!(%limit, visited_nodes = self.visited_nodes, "Search limit reached");
+                    self.limit_reached = Some(limit);
+                    stop = true;
+                } else if self.nodes.get_link(self.head(), Direction::Next) == self.head() {
+                    debug_assert!(
+                        stack.iter().all(|frame| frame.row.is_some()),
+                        "Solution with a column left without a row"
+                    );
+                    // Every solution is a distinct set of rows, so no duplicates can be visited
+                    let rows: Vec<RowId> = stack
+                        .iter()
+                        .filter_map(|frame| frame.row)
+                        .map(|row| RowId(self.row_index_of(row)))
+                        .collect();
+                    stop = visit(&rows).is_break();
+                } else if let Some(col) = self.choose_column() {
+                    // Cover the column with the least nodes itself (2, 3)
+                    self.cover(col);
+                    stack.push(Frame { col, row: None });
+                }
+                // Otherwise a column can't be covered by any row, so the partial solution is a
+                // dead end to backtrack from without covering anything
+            }
+
+            let Some(&Frame { col, row }) = stack.last() else {
+                break;
+            };
+
+            // Uncover the columns of the row tried previously, excluding it from the partial
+            // solution again
+            if let Some(row) = row {
+                let mut row_iter = self.walk_from(row.0);
+
+                while let Some(rj) = row_iter.next_body(self, Direction::Prev) {
+                    self.uncover(self.nodes.column(rj));
+                }
+            }
+
+            // Exploring the rows in the chosen column (3)
+            let next = self
+                .nodes
+                .get_link(row.map_or(col.0, |row| row.0), Direction::Down);
+
+            if stop || next == col.0 {
+                self.uncover(col);
+                stack.pop();
+                continue;
+            }
+
+            // Include row r to the partial solution (4)
+            self.visited_nodes += 1;
+
+            // Stop the whole search if cancelled by the progress hook, the row not being covered
+            if !self.reporter.tick(self.visited_nodes, stack.len()) {
+                stop = true;
+                self.uncover(col);
+                stack.pop();
+                continue;
+            }
+
+            // Cover each column sharing a '1' with the row (5)
+            let next = BodyRef(next);
+            let mut r_walker = self.walk_from(next.0);
+
+            while let Some(rj) = r_walker.next_body(self, Direction::Next) {
+                self.cover(self.nodes.column(rj));
+            }
+
+            // Repeat with the reduced matrix A (6)
+            stack.last_mut().unwrap().row = Some(next);
+            descend = true;
         }
 
-        let mut col_idx = 0;
-        let mut min = !0;
+        stop
+    }
+
+    /// Returns the column to cover next as picked by the `ColumnSelection`, or `None` as soon as
+    /// an empty column is found.
+    fn choose_column(&mut self) -> Option<ColumnRef> {
         let mut col_heads = self.walk_from(self.head());
 
-        // Picking the column with least nodes (2)
-        while let Some(idx) = col_heads.next(self, Direction::Next) {
-            let count = self.get_node_value(idx);
-            self.visited_nodes += 1;
+        if self.column_selection == ColumnSelection::FirstAvailable {
+            let col = col_heads.next_column(self)?;
+            self.scans += 1;
+
+            return (self.nodes.count(col) != 0).then_some(col);
+        }
+
+        let early_exit = self.column_selection == ColumnSelection::MinCountEarlyExit;
+        let mut chosen = None;
+        let mut min = !0;
+
+        while let Some(col) = col_heads.next_column(self) {
+            let count = self.nodes.count(col);
+            self.scans += 1;
+
+            if count == 0 {
+                return None;
+            }
 
             if count < min {
                 min = count;
-                col_idx = idx;
+                chosen = Some(col);
 
-                if min == 0 {
-                    // Found a minimum (2)
+                // No column can have fewer rows without being empty
+                if early_exit && count == 1 {
                     break;
                 }
             }
+        }
 
-            if min == 0 {
-                return false;
+        chosen
+    }
+
+    /// Returns the limit of the `set_limits` exceeded by the search, the deadline being only
+    /// checked every `DEADLINE_CHECK_INTERVAL_NODES` visited nodes.
+    fn exceeded_limit(&self) -> Option<SearchLimit> {
+        if self.max_nodes.is_some_and(|max| self.visited_nodes >= max) {
+            return Some(SearchLimit::Nodes);
+        }
+
+        match self.deadline {
+            Some(deadline)
+                if self
+                    .visited_nodes
+                    .is_multiple_of(DEADLINE_CHECK_INTERVAL_NODES)
+                    && Instant::now() >= deadline =>
+            {
+                Some(SearchLimit::Time)
             }
+            _ => None,
         }
+    }
+}
 
-        // Exploring the rows in the chosen column (3)
-        // Cover the column itself (3)
-        self.cover(col_idx);
-        let mut col_items = self.walk_from(col_idx);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 16x16 grid with 4x4 squares, the values from 10 to 16 represented with `A` to `G`
+    const HEX_UNSOLVED_GRID: &str = "008A03041F05200DF050A0080CD2070BCG00E10500B0890A00400G0209A85F1009B03000F0000200070000000000A0F000EG1F5A703008960F00698B02GE0473G0C000100080000000000A000040700060058037E00FCGD40B780D000659F100000702E180030000B80074DG0A060E2C0560980320C1GD07000000A04D703000";
+    const HEX_SOLVED_GRID: &str = "968AB3741FE52CGDF15EA698GCD2473BCG2DE1F537B4896A734BDGC269A85F1E89B6374DF51AE2CG47D3GC2E986BA5F12CEG1F5A743DB8965FA1698BC2GED473GDC42E1FB38796A51EF25A69DG4C73B86A958B37E12FCGD43B784DGCA659F1E2D4G7C2E18B936A5FB83974DG5AF61E2CA56F98B32EC1GD47E21CF5A64D7G3B89";
+
+    #[test]
+    fn test_append_invalid_row() {
+        let sudoku = Sudoku::new(String::from(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::new(sudoku).unwrap();
+        let (nodes, rows) = (solver.cover.nodes.len(), solver.cover.row_table.len());
+
+        assert_eq!(
+            solver.cover.append_row([1, 0, 2]),
+            Err(ExactCoverError::ColumnOutOfRange { col: 0, cols: 324 })
+        );
+        assert_eq!(
+            solver.cover.append_row([1, 325]),
+            Err(ExactCoverError::ColumnOutOfRange {
+                col: 325,
+                cols: 324
+            })
+        );
+        assert_eq!(solver.cover.append_row([]), Err(ExactCoverError::EmptyRow));
 
-        // Cover columns sharing a '1' with the current column (3)
-        while let Some(ci) = col_items.next(self, Direction::Down) {
-            // Include row r to the partial solution (4)
-            partial_res.push(ci);
-            self.visited_nodes += 1;
+        // The failed appends are rolled back
+        assert_eq!(solver.cover.nodes.len(), nodes);
+        assert_eq!(solver.cover.row_table.len(), rows);
+        assert_eq!(solver.cover.nodes.counts.len(), 324);
+        assert_eq!(solver.cover.nodes.columns.len(), nodes - 325);
+    }
 
-            // Cover each column (5)
-            let mut r_walker = self.walk_from(ci);
+    #[test]
+    fn test_append_duplicate_column() {
+        let mut cover = ExactCover::new(2).unwrap();
+        let nodes = cover.nodes.len();
+
+        assert_eq!(
+            cover.append_row([1, 1, 2]),
+            Err(ExactCoverError::DuplicateColumn { col: 1 })
+        );
+        assert_eq!(cover.nodes.len(), nodes);
+
+        // The links stay intact for the search after the rejected row
+        assert_eq!(cover.append_row([2]), Ok(RowId(0)));
+        assert_eq!(cover.append_row([1]), Ok(RowId(1)));
+        let mut solutions = Vec::new();
+        let found = cover.enumerate(usize::MAX, |rows| {
+            solutions.push(rows.to_vec());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(found, 1);
+        assert_eq!(solutions, vec![vec![RowId(1), RowId(0)]]);
+    }
+
+    #[test]
+    fn test_matrix_too_large() {
+        // Rejected before anything is allocated for the header, the links being narrowed to u32
+        assert!(matches!(
+            ExactCover::new(MAX_NODES),
+            Err(ExactCoverError::MatrixTooLarge { max: MAX_NODES })
+        ));
+        assert!(ExactCover::new(4).is_ok());
+    }
 
-            while let Some(rj) = r_walker.next(self, Direction::Next) {
-                self.cover(self.get_col_head(rj));
+    #[test]
+    fn test_constraint_out_of_universe() {
+        // The constraints of the 16x16 grid don't fit into the columns of the 9x9 one
+        let sudoku = Sudoku::new(String::from(HEX_UNSOLVED_GRID)).unwrap();
+
+        assert!(matches!(
+            DlxSolver::with_universe(sudoku, 1, 9 * 9 * 4, None),
+            Err(SudokuError::Matrix(ExactCoverError::ColumnOutOfRange {
+                cols: 324,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_matrix_pruned_by_candidates() {
+        let sudoku = Sudoku::new(String::from(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        ))
+        .unwrap();
+        let candidates = sudoku
+            .candidate_masks()
+            .iter()
+            .map(|mask| mask.count_ones() as usize)
+            .sum::<usize>();
+        let mut solver = DlxSolver::new(sudoku).unwrap();
+
+        // A single row per clue and at most 9 per empty cell, most of them ruled out by the peers
+        assert_eq!(solver.matrix_rows(), candidates);
+        assert!(solver.matrix_rows() < 30 + 51 * 9);
+        assert_eq!(solver.cover.row_table.len(), solver.matrix_rows());
+        assert!(solver.solve().0);
+        assert!(solver.get_sudoku().is_solved());
+    }
+
+    #[test]
+    fn test_candidates() {
+        // Has multiple solutions, the first one of which is ruled out by the pencil marks
+        let improper = Sudoku::new(String::from(
+            "000000010400000000020000000000050407008000300001090000300400200050100000000800000",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::with_max_solutions(improper.clone(), 2).unwrap();
+        assert!(solver.solve().0);
+        let (first, second) = (&solver.solutions[0], &solver.solutions[1]);
+
+        // The cells the two solutions disagree on and the top band are marked with the digits of
+        // the second one, the others allowing any digit
+        let mut marks = [0b11_1111_1110; 81];
+        for (k, (&a, &b)) in first.iter().zip(second).enumerate() {
+            if a != b || k < 27 {
+                marks[k] = 1 << b;
             }
+        }
+
+        let mut restricted = DlxSolver::with_candidates(improper.clone(), 2, &marks).unwrap();
+        assert!(restricted.matrix_rows() < solver.matrix_rows());
+        assert!(restricted.solve().0);
+        assert_eq!(restricted.solutions.len(), 1);
+        assert_eq!(&restricted.solutions[0], second);
+        assert_eq!(restricted.count_solutions(2, None), Some(1));
+
+        // Ruling out the value of a given, or every digit of an empty cell, leaves no solution
+        for (k, mask) in [(7, 0b11_1111_1100), (0, 0)] {
+            let mut marks = [0b11_1111_1110; 81];
+            marks[k] = mask;
+            let mut solver = DlxSolver::with_candidates(improper.clone(), 1, &marks).unwrap();
+            assert!(!solver.solve().0);
+            assert!(solver.solutions().is_empty());
+        }
+
+        // The marks only fit the 9x9 grids
+        let hex = Sudoku::new(String::from(HEX_UNSOLVED_GRID)).unwrap();
+        assert!(matches!(
+            DlxSolver::with_candidates(hex, 1, &[0b11_1111_1110; 81]),
+            Err(SudokuError::SizeMismatch {
+                size: 16,
+                expected: 9
+            })
+        ));
+    }
+
+    #[test]
+    fn test_x_sudoku() {
+        let puzzle =
+            "000000098006089400100500000000000080400200007000000000000904800600000000030075000";
+        let x_solution =
+            "254367198376189425189542673567491382491238567823756914715924836642813759938675241";
+
+        // None of the many classic solutions satisfies the diagonals
+        let mut classic = DlxSolver::with_max_solutions(puzzle.parse().unwrap(), 2).unwrap();
+        assert!(classic.solve().0);
+        assert_eq!(classic.solutions().len(), 2);
+        assert!(!classic.solutions().contains(&String::from(x_solution)));
+
+        let sudoku = puzzle.parse::<Sudoku>().unwrap().with_variant(Variant::X);
+        let mut solver = DlxSolver::with_max_solutions(sudoku.clone(), 2).unwrap();
+        assert_eq!(solver.cover.num_of_cols, 9 * 9 * 4 + 2 * 9);
+        // Besides the heads, the rows of the cells on the diagonals have a node more, the ones of
+        // the center two more
+        let masks = sudoku.candidate_masks();
+        assert_eq!(
+            solver.cover.nodes.len() - classic.cover.nodes.len(),
+            2 * 9
+                + (0..9)
+                    .flat_map(|k| [Pos::from_row_col(k, k), Pos::from_row_col(k, 8 - k)])
+                    .map(|pos| masks[pos.index(9)].count_ones() as usize)
+                    .sum::<usize>()
+        );
+        assert!(solver.solve().0);
+        assert_eq!(solver.solutions(), vec![String::from(x_solution)]);
+        assert_eq!(solver.count_solutions(2, None), Some(1));
+    }
+
+    #[test]
+    fn test_corrupt_solution() {
+        let sudoku: Sudoku =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+                .parse()
+                .unwrap();
+        type Corruption = fn(&mut [(Pos, usize)]);
+        type Expected = fn(CorruptSolution) -> bool;
+
+        let corrupted = |corrupt: Corruption| {
+            let mut solver = DlxSolver::new(sudoku.clone()).unwrap();
+            corrupt(solver.subset_data_mut());
+            solver
+        };
+
+        // The rows of (0, 1) fill (0, 0) instead, every row is shifted to the next digit, and the
+        // rows of the empty cells (the cells with more than one row) all fill in a 1
+        let cases: [(Corruption, Expected); 3] = [
+            (
+                |data| {
+                    for (pos, _) in data.iter_mut().filter(|(pos, _)| *pos == (0, 1).into()) {
+                        *pos = (0, 0).into();
+                    }
+                },
+                |e| e == CorruptSolution::RepeatedCell((0, 0).into()),
+            ),
+            (
+                |data| {
+                    for (_, k) in data.iter_mut() {
+                        *k = (*k + 1) % 9;
+                    }
+                },
+                |e| e == CorruptSolution::AlteredGiven((0, 0).into()),
+            ),
+            (
+                |data| {
+                    let row_counts: Vec<_> = data
+                        .iter()
+                        .map(|&(pos, _)| data.iter().filter(|(other, _)| *other == pos).count())
+                        .collect();
+                    for ((_, k), rows) in data.iter_mut().zip(row_counts) {
+                        if rows > 1 {
+                            *k = 0;
+                        }
+                    }
+                },
+                |e| matches!(e, CorruptSolution::Violation(..)),
+            ),
+        ];
+
+        for (corrupt, expected) in cases {
+            let mut solver = corrupted(corrupt);
+            assert!(!solver.solve().0);
+            assert!(solver.is_corrupted());
+            assert!(solver.solutions().is_empty());
+            assert_eq!(solver.count_solutions(2, None), None);
+
+            let mut parallel = corrupted(corrupt);
+            parallel.set_parallel(true);
+            assert!(!parallel.solve().0);
+            assert!(parallel.is_corrupted());
+
+            let mut solver = corrupted(corrupt);
+            let rows = solver.cover.solve_first().unwrap();
+            let e = decode_solution(&solver.sudoku, &solver.subset_data, &rows).unwrap_err();
+            assert!(expected(e), "Unexpected {:?}", e);
+        }
 
-            // Repeat recursively with the reduced matrix A (6)
-            if self.algox(partial_res) {
-                return true;
+        let mut solver = DlxSolver::new(sudoku).unwrap();
+        let rows = solver.cover.solve_first().unwrap();
+        assert_eq!(
+            decode_solution(&solver.sudoku, &solver.subset_data, &rows[1..]),
+            Err(CorruptSolution::RowCount {
+                rows: 80,
+                cells: 81
+            })
+        );
+        assert!(solver.solve().0);
+        assert!(!solver.is_corrupted());
+    }
+
+    #[test]
+    fn test_arena_reserved_exactly() {
+        let sudoku17 =
+            "000000010400000000020000000000050407008000300001090000300400200050100000000800000";
+        let x_sudoku =
+            "000000098006089400100500000000000080400200007000000000000904800600000000030075000";
+        let solvers = [
+            DlxSolver::new(sudoku17.parse().unwrap()).unwrap(),
+            DlxSolver::new("0".repeat(81).parse().unwrap()).unwrap(),
+            DlxSolver::new(x_sudoku.parse::<Sudoku>().unwrap().with_variant(Variant::X)).unwrap(),
+            DlxSolver::with_candidates(sudoku17.parse().unwrap(), 1, &[0b10_1010_1010; 81])
+                .unwrap(),
+            DlxSolver::new(HEX_UNSOLVED_GRID.parse().unwrap()).unwrap(),
+        ];
+
+        // The rows of a sparse puzzle have several times as many nodes as there are columns
+        assert!(solvers[0].cover.nodes.len() > 4 * 324);
+
+        let exact_nodes = solvers[0].cover.nodes.clone();
+
+        for solver in solvers {
+            let nodes = &solver.cover.nodes;
+            assert_eq!(nodes.len(), nodes.capacity());
+            assert_eq!(nodes.columns.len(), nodes.columns.capacity());
+            assert!(!solver.cover.arena_reallocated);
+        }
+
+        // The same matrix as with the reservation before the exact one, which grows the arena
+        let amortized = DlxSolver::with_amortized_arena(sudoku17.parse().unwrap()).unwrap();
+        assert_eq!(amortized.cover.nodes, exact_nodes);
+        assert!(amortized.cover.arena_reallocated);
+
+        // Rows appended beyond the header of a matrix without a reservation grow the arena
+        let mut cover = ExactCover::new(2).unwrap();
+        assert!(!cover.arena_reallocated);
+        for _ in 0..4 {
+            cover.append_row([1, 2]).unwrap();
+        }
+        assert!(cover.arena_reallocated);
+    }
+
+    #[test]
+    fn test_enumerate() {
+        let mut solver = DlxSolver::new("0".repeat(81).parse().unwrap()).unwrap();
+        let mut solutions = Vec::new();
+        let found = solver.enumerate(5, |solution| {
+            solutions.push(solution.to_vec());
+            ControlFlow::Continue(())
+        });
+
+        assert_eq!(found, 5);
+        assert!(solutions.iter().all(|solution| {
+            let sudoku = Sudoku::new(solution.iter().map(|&v| value_to_char(v)).collect());
+            sudoku.is_ok_and(|sudoku| sudoku.is_solved())
+        }));
+        assert!((1..5).all(|i| !solutions[..i].contains(&solutions[i])));
+
+        // The matrix is restored after the search, so the enumeration is repeatable
+        let mut again = Vec::new();
+        let found = solver.enumerate(10, |solution| {
+            again.push(solution.to_vec());
+            match again.len() {
+                5 => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
             }
+        });
+        assert_eq!(found, 5);
+        assert_eq!(again, solutions);
+
+        // A proper puzzle has a single solution regardless of the limit
+        let sudoku = Sudoku::new(String::from(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::new(sudoku).unwrap();
+        assert_eq!(solver.enumerate(10, |_| ControlFlow::Continue(())), 1);
+        assert_eq!(solver.count_solutions(10, None), Some(1));
+        assert!(solver.solve().0);
+        assert!(solver.get_sudoku().is_solved());
+    }
+
+    #[test]
+    fn test_is_unique() {
+        let proper =
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+        let mut solver = DlxSolver::new(Sudoku::new(String::from(proper)).unwrap()).unwrap();
+        assert_eq!(solver.is_unique(None), Some(true));
+        assert!(solver.get_sudoku().is_solution_of(&proper.parse().unwrap()));
+
+        // Any puzzle with fewer than 17 clues has multiple solutions
+        let improper =
+            "000000010400000000020000000000050407008000300001090000300400200050100000000800000";
+        let mut solver = DlxSolver::new(Sudoku::new(String::from(improper)).unwrap()).unwrap();
+        assert_eq!(solver.is_unique(None), Some(false));
+        assert!(solver
+            .get_sudoku()
+            .is_solution_of(&improper.parse().unwrap()));
+
+        // Meets the default Sudoku constraints, but has no solution
+        let contradictory =
+            "500020410620000000400010062007230604006000379030090005098006001015300940004100738";
+        let mut solver = DlxSolver::new(Sudoku::new(String::from(contradictory)).unwrap()).unwrap();
+        assert_eq!(solver.is_unique(None), None);
+        assert!(!solver.get_sudoku().is_solved());
+
+        let mut solver = DlxSolver::new(Sudoku::new(String::from(improper)).unwrap()).unwrap();
+        assert_eq!(solver.is_unique(Some(1)), None);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut solver = DlxSolver::new("0".repeat(81).parse().unwrap()).unwrap();
+        solver.set_limits(Some(500), None);
+
+        // Enumerating the solutions of the empty grid only stops at a limit
+        assert!(solver.enumerate(usize::MAX, |_| ControlFlow::Continue(())) > 0);
+        assert_eq!(solver.limit_reached(), Some(SearchLimit::Nodes));
+        assert_eq!(solver.cover.visited_nodes, 500);
+
+        let mut solver = DlxSolver::new("0".repeat(81).parse().unwrap()).unwrap();
+        solver.set_limits(None, Some(Duration::from_millis(1)));
+        let start = Instant::now();
+
+        solver.enumerate(usize::MAX, |_| ControlFlow::Continue(()));
+        assert_eq!(solver.limit_reached(), Some(SearchLimit::Time));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // The matrix is left intact by the stopped search
+        solver.set_limits(None, None);
+        assert!(solver.solve().0);
+        assert_eq!(solver.limit_reached(), None);
+        assert!(solver.get_sudoku().is_solved());
+    }
+
+    #[test]
+    fn test_empty_column() {
+        // The first cell has no candidates left, so its column has no rows at all
+        let sudoku = Sudoku::new(String::from(
+            "012345678900000000000000000000000000000000000000000000000000000000000000000000000",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::new(sudoku).unwrap();
+        let links = solver.cover.nodes.links.clone();
+
+        assert_eq!(solver.cover.choose_column(), None);
+        assert!(!solver.solve().0);
+
+        // Backtracked right away, without covering any column or trying any row
+        assert_eq!(solver.node_counts().decisions, 0);
+        assert_eq!(solver.node_counts().propagations, 0);
+        assert_eq!(solver.node_counts().scans, 2);
+        assert_eq!(solver.cover.nodes.links, links);
+    }
+
+    #[test]
+    fn test_template() {
+        let template = MatrixTemplate::new(9).unwrap();
+
+        for grid in [
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+            "500020410620000000400010062007230604006000379030090005098006001015300940004100738",
+        ] {
+            let sudoku = Sudoku::new(String::from(grid)).unwrap();
+            let mut built = DlxSolver::new(sudoku.clone()).unwrap();
+            let mut copied = DlxSolver::with_template(&template, &sudoku, 1).unwrap();
+
+            // The same rows in the same order, so the search doesn't differ either
+            assert_eq!(copied.matrix_rows(), built.matrix_rows());
+            assert_eq!(copied.solve(), built.solve());
+            assert_eq!(copied.solutions(), built.solutions());
+            assert_eq!(copied.node_counts(), built.node_counts());
+        }
+
+        let template = MatrixTemplate::new(16).unwrap();
+        let sudoku = Sudoku::new(String::from(HEX_UNSOLVED_GRID)).unwrap();
+        let mut solver = DlxSolver::with_template(&template, &sudoku, 1).unwrap();
+        assert!(solver.solve().0);
+        assert_eq!(solver.grid_to_string(), HEX_SOLVED_GRID);
+
+        // Neither the grids of another size nor the other variants fit into the template
+        let classic = Sudoku::new(String::from(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        ))
+        .unwrap();
+        assert!(matches!(
+            DlxSolver::with_template(&template, &classic, 1),
+            Err(SudokuError::SizeMismatch {
+                size: 9,
+                expected: 16
+            })
+        ));
+
+        let template = MatrixTemplate::new(9).unwrap();
+        assert!(matches!(
+            DlxSolver::with_template(&template, &classic.with_variant(Variant::X), 1),
+            Err(SudokuError::UnsupportedVariant(Variant::X))
+        ));
+    }
+
+    #[test]
+    fn test_reset() {
+        let sudoku = Sudoku::new(String::from(
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::new(sudoku).unwrap();
+        let (links, counts) = (
+            solver.cover.nodes.links.clone(),
+            solver.cover.nodes.counts.clone(),
+        );
+        let (solved, visited_nodes) = solver.solve();
+        let (solutions, node_counts, stats) =
+            (solver.solutions(), solver.node_counts(), solver.dlx_stats());
+
+        assert!(solved);
+        assert_eq!(solver.cover.nodes.links, links);
+        assert_eq!(solver.cover.nodes.counts, counts);
+
+        // The repeated search on the same instance starts from the same matrix and counters
+        solver.reset();
+        assert!(solver.solutions().is_empty());
+        assert_eq!(solver.node_counts(), NodeCounts::default());
+        assert_eq!(solver.solve(), (true, visited_nodes));
+        assert_eq!(solver.solutions(), solutions);
+        assert_eq!(solver.node_counts(), node_counts);
+        assert_eq!(solver.dlx_stats(), stats);
+        assert_eq!(solver.cover.nodes.links, links);
+        assert_eq!(solver.cover.nodes.counts, counts);
+    }
+
+    #[test]
+    fn test_column_selection() {
+        let sudoku = Sudoku::new(String::from(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        ))
+        .unwrap();
+        let mut solved = None;
+
+        for (column_selection, (decisions, propagations, scans)) in [
+            (ColumnSelection::MinCount, (81, 183, 13284)),
+            (ColumnSelection::FirstAvailable, (924, 2137, 924)),
+            (ColumnSelection::MinCountEarlyExit, (81, 183, 769)),
+        ] {
+            let mut solver = DlxSolver::new(sudoku.clone()).unwrap();
+            solver.set_column_selection(column_selection);
+
+            assert_eq!(solver.solve(), (true, decisions));
+            assert_eq!(
+                solver.node_counts(),
+                NodeCounts {
+                    decisions,
+                    propagations,
+                    scans,
+                },
+                "{:?}",
+                column_selection
+            );
+            assert_eq!(
+                *solved.get_or_insert(solver.solutions()),
+                solver.solutions()
+            );
+        }
+    }
 
-            partial_res.pop();
-            let mut row_iter = self.walk_from(ci);
+    #[test]
+    fn test_parallel() {
+        // The hardest puzzle for DLX in the 'sudoku17' collection
+        let hard = Sudoku::new(String::from(
+            "000801000000000205000000700507620000030000010000000000600000040000250000490000000",
+        ))
+        .unwrap();
+        let mut sequential = DlxSolver::new(hard.clone()).unwrap();
+        let mut solver = DlxSolver::new(hard).unwrap();
+        solver.set_parallel(true);
+        let links = solver.cover.nodes.links.clone();
+
+        assert!(sequential.solve().0);
+        assert!(solver.solve().0);
+        assert_eq!(solver.solutions(), sequential.solutions());
+        assert_eq!(solver.cover.nodes.links, links);
+        let stats = solver.dlx_stats().unwrap();
+        assert_eq!(stats.covers, stats.uncovers);
+
+        // Any of the solutions of an improper puzzle, and none of an unsolvable one
+        let mut solver = DlxSolver::new("0".repeat(81).parse().unwrap()).unwrap();
+        solver.set_parallel(true);
+        assert!(solver.solve().0);
+        assert!(solver.get_sudoku().is_solved());
+
+        let unsolvable = Sudoku::new(String::from(
+            "012345678900000000000000000000000000000000000000000000000000000000000000000000000",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::new(unsolvable).unwrap();
+        solver.set_parallel(true);
+        assert!(!solver.solve().0);
+        assert!(solver.solutions().is_empty());
+    }
 
-            while let Some(rj) = row_iter.next(self, Direction::Prev) {
-                self.uncover(self.get_col_head(rj));
+    #[test]
+    fn test_parallel_node_limit() {
+        // Perfect matchings of an odd number of columns, which don't exist, but the search only
+        // finds out after trying the matchings of all but one of them
+        let mut cover = ExactCover::new(15).unwrap();
+        for i in 1..=15 {
+            for j in i + 1..=15 {
+                cover.append_row([i, j]).unwrap();
             }
         }
+        cover.set_limits(Some(5000), None);
+
+        // The 14 rows of the first column share the budget instead of getting the whole of it each
+        assert_eq!(cover.solve_first_parallel(), None);
+        assert_eq!(cover.limit_reached, Some(SearchLimit::Nodes));
+        assert!(cover.visited_nodes >= 5000);
+        assert!(cover.visited_nodes < 5000 + 14 * PARALLEL_CHECK_INTERVAL_NODES);
+    }
+
+    #[test]
+    fn test_parallel_cancel() {
+        let hard = Sudoku::new(String::from(
+            "000801000000000205000000700507620000030000010000000000600000040000250000490000000",
+        ))
+        .unwrap();
+        let mut solver = DlxSolver::new(hard).unwrap();
+        solver.set_parallel(true);
+        solver.set_progress_reporter(ProgressReporter::new(Box::new(|_| false), 1));
+
+        // Stopped by the hook of the caller at the first check of any of the workers
+        assert!(!solver.solve().0);
+        assert!(solver.is_cancelled());
+    }
+
+    /// Builds the N-queens problem as an exact cover of the rows and the columns of the board, the
+    /// diagonals being covered by at most one queen with the help of a slack row for each of them.
+    /// Returns the matrix and the placement of every row, `None` for the slack rows.
+    fn queens(n: usize) -> (ExactCover, Vec<Option<(usize, usize)>>) {
+        let diagonals = 2 * n - 1;
+        let mut cover = ExactCover::new(2 * n + 2 * diagonals).unwrap();
+        let mut placements = Vec::new();
+
+        for (r, c) in (0..n).flat_map(|r| (0..n).map(move |c| (r, c))) {
+            let row = [
+                1 + r,
+                1 + n + c,
+                1 + 2 * n + r + c,
+                1 + 2 * n + diagonals + r + n - 1 - c,
+            ];
+            assert_eq!(cover.append_row(row), Ok(RowId(placements.len())));
+            placements.push(Some((r, c)));
+        }
+
+        for diagonal in 2 * n + 1..=2 * n + 2 * diagonals {
+            cover.append_row([diagonal]).unwrap();
+            placements.push(None);
+        }
+
+        (cover, placements)
+    }
+
+    #[test]
+    fn test_exact_cover_queens() {
+        let (mut cover, placements) = queens(8);
+        let mut boards = Vec::new();
+        let found = cover.enumerate(usize::MAX, |rows| {
+            let queens: Vec<_> = rows.iter().filter_map(|row| placements[row.0]).collect();
+            boards.push(queens);
+            ControlFlow::Continue(())
+        });
+
+        // The slack rows are determined by the queens, so every board is found exactly once
+        assert_eq!(found, 92);
+        assert_eq!(cover.num_rows(), 64 + 2 * 15);
+        assert!(boards.iter().all(|queens| {
+            queens.len() == 8
+                && queens.iter().enumerate().all(|(i, &(r1, c1))| {
+                    queens[i + 1..]
+                        .iter()
+                        .all(|&(r2, c2)| r1 != r2 && c1 != c2 && r1.abs_diff(r2) != c1.abs_diff(c2))
+                })
+        }));
+        assert!((1..boards.len()).all(|i| !boards[..i].contains(&boards[i])));
+
+        let (mut cover, _) = queens(6);
+        assert!(cover.solve_first().is_some());
+        assert_eq!(
+            cover.enumerate(usize::MAX, |_| ControlFlow::Continue(())),
+            4
+        );
+
+        // No way to place 3 queens on a 3x3 board
+        let (mut cover, _) = queens(3);
+        assert_eq!(cover.solve_first(), None);
+        assert_eq!(cover.limit_reached(), None);
+    }
+
+    #[test]
+    fn test_other_sizes() {
+        let mut solver =
+            DlxSolver::new(Sudoku::new(String::from("1030040220404003")).unwrap()).unwrap();
+
+        assert_eq!(solver.cover.num_of_cols, 4 * 4 * 4);
+        assert!(solver.solve().0);
+        assert_eq!(solver.grid_to_string(), "1234341223414123");
 
-        self.uncover(col_idx);
+        let mut solver =
+            DlxSolver::new(Sudoku::new(String::from(HEX_UNSOLVED_GRID)).unwrap()).unwrap();
 
-        false
+        assert_eq!(solver.cover.num_of_cols, 16 * 16 * 4);
+        assert!(solver.solve().0);
+        assert_eq!(solver.grid_to_string(), HEX_SOLVED_GRID);
+        assert_eq!(solver.solutions(), [HEX_SOLVED_GRID]);
     }
 }
@@ -0,0 +1,199 @@
+use std::{error::Error, fmt};
+
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+
+use crate::{
+    controller::{EntryError, ErrorResponse},
+    dfs::SearchLimit,
+    quota::QuotaUsage,
+    sudoku::Conflict,
+};
+
+/// Maximum number of conflicts listed in the message of `ApiError::ConstraintViolation`, all of
+/// them are included in the `conflicts` array of the response body regardless.
+const MAX_REPORTED_CONFLICTS: usize = 3;
+
+/// Errors surfaced to the clients of the API. Each variant maps to a HTTP status code and a
+/// machine-readable `kind` included in the `ErrorResponse` body.
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// The request payload exceeds the configured limit in bytes.
+    PayloadTooLarge { limit: usize },
+    /// The request doesn't contain any entries.
+    EmptyBatch,
+    /// The entry grid doesn't match the input format constraints.
+    InvalidFormat(String),
+    /// The entry grid doesn't consist of 81 cells arranged as 9 rows of 9.
+    InvalidDimensions(String),
+    /// The entry grid doesn't meet the default Sudoku constraints, contains every conflict found.
+    ConstraintViolation(Vec<Conflict>),
+    /// The requested solver doesn't exist, contains the message listing the valid options.
+    UnknownSolver(String),
+    /// The solve options of the entry are outside of the allowed ranges.
+    InvalidOptions(String),
+    /// The entry grid has fewer clues than any puzzle with a unique solution, only rejected if the
+    /// entry requires one.
+    TooFewClues { clues: usize },
+    /// The entry grid has more than one solution, only rejected if the entry requires a unique
+    /// one.
+    NotUnique,
+    /// The entry grid meets the default Sudoku constraints, but has no solution.
+    Unsolvable { index: usize },
+    /// The solving process of the entry was cancelled before it finished.
+    Cancelled { index: usize },
+    /// The search of the entry reached its node or time limit before it finished.
+    LimitReached { index: usize, limit: SearchLimit },
+    /// The rate limiter quota is exhausted until the next element is replenished.
+    RateLimited { retry_after_s: u64 },
+    /// The admin token is missing or invalid, or the admin endpoints are disabled.
+    Unauthorized,
+    /// The API key is missing or unknown while the quotas are enabled.
+    InvalidApiKey,
+    /// The batch doesn't fit into the remaining quota of the API key.
+    QuotaExceeded(QuotaUsage),
+    /// The usage is requested while no quotas are configured.
+    QuotasDisabled,
+    /// Every entry of the batch failed, contains the errors of the individual entries.
+    BatchFailed(Vec<EntryError>),
+    /// Unexpected failure on the server side.
+    Internal(String),
+}
+
+impl ApiError {
+    /// Returns the machine-readable identifier of the error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApiError::PayloadTooLarge { .. } => "payload_too_large",
+            ApiError::EmptyBatch => "empty_batch",
+            ApiError::InvalidFormat(_) => "invalid_format",
+            ApiError::InvalidDimensions(_) => "invalid_dimensions",
+            ApiError::ConstraintViolation(_) => "constraint_violation",
+            ApiError::UnknownSolver(_) => "unknown_solver",
+            ApiError::InvalidOptions(_) => "invalid_options",
+            ApiError::TooFewClues { .. } => "too_few_clues",
+            ApiError::NotUnique => "not_unique",
+            ApiError::Unsolvable { .. } => "unsolvable",
+            ApiError::Cancelled { .. } => "cancelled",
+            ApiError::LimitReached { .. } => "limit_reached",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::InvalidApiKey => "invalid_api_key",
+            ApiError::QuotaExceeded(_) => "quota_exceeded",
+            ApiError::QuotasDisabled => "quotas_disabled",
+            ApiError::BatchFailed(_) => "batch_failed",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::PayloadTooLarge { limit } => write!(
+                f,
+                "The request payload exceeds the limit of {} bytes",
+                limit
+            ),
+            ApiError::EmptyBatch => {
+                write!(
+                    f,
+                    "Empty batch, the request must contain at least one entry"
+                )
+            }
+            ApiError::InvalidFormat(message)
+            | ApiError::InvalidDimensions(message)
+            | ApiError::UnknownSolver(message)
+            | ApiError::InvalidOptions(message)
+            | ApiError::Internal(message) => write!(f, "{}", message),
+            ApiError::ConstraintViolation(conflicts) => {
+                write!(f, "Default Sudoku constraints not met")?;
+
+                for (i, conflict) in conflicts.iter().take(MAX_REPORTED_CONFLICTS).enumerate() {
+                    write!(f, "{} {}", if i == 0 { "," } else { ";" }, conflict)?;
+                }
+
+                match conflicts.len().checked_sub(MAX_REPORTED_CONFLICTS) {
+                    Some(more) if more > 0 => write!(f, "; and {} more", more),
+                    _ => Ok(()),
+                }
+            }
+            ApiError::TooFewClues { clues } => write!(
+                f,
+                "The puzzle has only {} clues, but a puzzle with a unique solution needs at least 17",
+                clues
+            ),
+            ApiError::NotUnique => write!(f, "The puzzle has more than one solution"),
+            ApiError::Unsolvable { index } => write!(
+                f,
+                "The puzzle of entry {} meets the default Sudoku constraints, but has no solution",
+                index
+            ),
+            ApiError::Cancelled { index } => write!(
+                f,
+                "Solving the puzzle of entry {} was cancelled before it finished",
+                index
+            ),
+            ApiError::LimitReached { index, limit } => write!(
+                f,
+                "Solving the puzzle of entry {} reached the {} limit before it finished",
+                index, limit
+            ),
+            ApiError::RateLimited { retry_after_s } => write!(
+                f,
+                "Too many requests, the quota is replenished in {} seconds",
+                retry_after_s
+            ),
+            ApiError::Unauthorized => write!(f, "Missing or invalid admin token"),
+            ApiError::InvalidApiKey => write!(f, "Missing or unknown API key"),
+            ApiError::QuotaExceeded(usage) => write!(
+                f,
+                "Quota exceeded, {} of the {} puzzles remaining, the quota is replenished in {} seconds",
+                usage.remaining, usage.limit, usage.reset_s
+            ),
+            ApiError::QuotasDisabled => write!(f, "Quotas are disabled on this server"),
+            ApiError::BatchFailed(errors) => match errors.as_slice() {
+                [single] => write!(f, "{}", single.error().message()),
+                _ => write!(f, "All {} entries of the batch failed", errors.len()),
+            },
+        }
+    }
+}
+
+impl Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::EmptyBatch
+            | ApiError::InvalidFormat(_)
+            | ApiError::InvalidDimensions(_)
+            | ApiError::ConstraintViolation(_)
+            | ApiError::UnknownSolver(_)
+            | ApiError::InvalidOptions(_)
+            | ApiError::TooFewClues { .. } => StatusCode::BAD_REQUEST,
+            ApiError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::NotUnique | ApiError::Unsolvable { .. } | ApiError::LimitReached { .. } => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::Cancelled { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Unauthorized | ApiError::InvalidApiKey => StatusCode::UNAUTHORIZED,
+            ApiError::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::QuotasDisabled => StatusCode::NOT_FOUND,
+            // Client errors take precedence over the internal ones
+            ApiError::BatchFailed(errors) => [
+                StatusCode::BAD_REQUEST,
+                StatusCode::UNPROCESSABLE_ENTITY,
+                StatusCode::SERVICE_UNAVAILABLE,
+            ]
+            .into_iter()
+            .find(|&status| errors.iter().any(|e| e.error().status() == status))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        ErrorResponse::from(self).respond_with(HttpResponse::build(self.status_code()))
+    }
+}
@@ -1,7 +1,19 @@
+pub mod admin;
+pub mod constraint;
 pub mod controller;
 pub mod dfs;
 pub mod dlx;
+pub mod error;
+pub mod limiter;
+pub mod quota;
+pub mod rating;
+pub mod shutdown;
 pub mod solver;
+pub mod stats;
 pub mod sudoku;
+pub mod timing;
+pub mod tls;
+pub mod transport;
+pub mod ws;
 
 // Necessary to export the modules to be integration tested in 'tests'
@@ -0,0 +1,194 @@
+use std::{
+    net::IpAddr,
+    num::NonZeroU32,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::Duration,
+};
+
+use actix_governor::{
+    governor::{
+        clock::{Clock, DefaultClock, QuantaInstant},
+        middleware::StateInformationMiddleware,
+        state::keyed::DefaultKeyedStateStore,
+        NotUntil, Quota, RateLimiter,
+    },
+    KeyExtractor, PeerIpKeyExtractor, SimpleKeyExtractionError,
+};
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web, Error, HttpResponse, HttpResponseBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{controller::ErrorResponse, error::ApiError};
+
+// Same headers as the ones set by the governor middleware with `use_headers`
+const X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const X_RATELIMIT_AFTER: HeaderName = HeaderName::from_static("x-ratelimit-after");
+
+type KeyedRateLimiter =
+    RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock, StateInformationMiddleware>;
+
+/// Peer IP based key extractor for the governor middleware. Behaves identically to the default
+/// `PeerIpKeyExtractor`, but responds with an `ErrorResponse` body and a `Retry-After` header
+/// once the quota is exhausted instead of the plaintext default.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitExtractor;
+
+impl KeyExtractor for RateLimitExtractor {
+    type Key = IpAddr;
+    type KeyExtractionError = SimpleKeyExtractionError<&'static str>;
+
+    fn extract(&self, req: &ServiceRequest) -> Result<Self::Key, Self::KeyExtractionError> {
+        PeerIpKeyExtractor.extract(req)
+    }
+
+    fn exceed_rate_limit_response(
+        &self,
+        negative: &NotUntil<QuantaInstant>,
+        response: HttpResponseBuilder,
+    ) -> HttpResponse {
+        // Rounded up to avoid telling the client to retry before the quota is replenished
+        let wait_time = negative.wait_time_from(DefaultClock::default().now());
+        let retry_after_s = wait_time.as_secs() + u64::from(wait_time.subsec_nanos() > 0);
+
+        ErrorResponse::from(&ApiError::RateLimited { retry_after_s }).respond_with(response)
+    }
+}
+
+/// Effective limits of the rate limiter, adjustable at runtime via the admin endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimits {
+    /// Interval after which one element of the quota is replenished in seconds.
+    pub interval_s: u64,
+    /// Number of requests that can occur before the requests start being blocked.
+    pub burst_size: u32,
+}
+
+impl RateLimits {
+    fn quota(&self) -> Result<Quota, ApiError> {
+        let invalid = || {
+            ApiError::InvalidOptions(String::from(
+                "The rate limit interval and burst size must both be at least 1",
+            ))
+        };
+        let burst_size = NonZeroU32::new(self.burst_size).ok_or_else(invalid)?;
+
+        Quota::with_period(Duration::from_secs(self.interval_s))
+            .map(|quota| quota.allow_burst(burst_size))
+            .ok_or_else(invalid)
+    }
+}
+
+/// Current limits of the rate limiter combined with the live statistics of its buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitStats {
+    #[serde(flatten)]
+    pub limits: RateLimits,
+    /// Number of clients with a bucket tracked by the current limiter.
+    pub tracked_keys: usize,
+    /// Number of requests let through since the server started.
+    pub allowed_requests: u64,
+    /// Number of requests blocked since the server started.
+    pub blocked_requests: u64,
+}
+
+/// Shared state of the `rate_limit` middleware, stored in the app data. Updating the limits
+/// atomically swaps the limiter, which resets the buckets of all the clients.
+#[derive(Debug)]
+pub struct RateLimitState {
+    current: RwLock<(RateLimits, Arc<KeyedRateLimiter>)>,
+    allowed_requests: AtomicU64,
+    blocked_requests: AtomicU64,
+}
+
+impl RateLimitState {
+    pub fn new(limits: RateLimits) -> Result<Self, ApiError> {
+        Ok(Self {
+            current: RwLock::new((limits, Self::limiter(limits)?)),
+            allowed_requests: AtomicU64::new(0),
+            blocked_requests: AtomicU64::new(0),
+        })
+    }
+
+    fn limiter(limits: RateLimits) -> Result<Arc<KeyedRateLimiter>, ApiError> {
+        Ok(Arc::new(
+            RateLimiter::keyed(limits.quota()?).with_middleware::<StateInformationMiddleware>(),
+        ))
+    }
+
+    fn current(&self) -> (RateLimits, Arc<KeyedRateLimiter>) {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        (current.0, current.1.clone())
+    }
+
+    /// Replaces the limits, rejecting zero values with `ApiError::InvalidOptions`.
+    pub fn update(&self, limits: RateLimits) -> Result<(), ApiError> {
+        let limiter = Self::limiter(limits)?;
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = (limits, limiter);
+
+        Ok(())
+    }
+
+    pub fn stats(&self) -> RateLimitStats {
+        let (limits, limiter) = self.current();
+
+        RateLimitStats {
+            limits,
+            tracked_keys: limiter.len(),
+            allowed_requests: self.allowed_requests.load(Ordering::Relaxed),
+            blocked_requests: self.blocked_requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Peer IP based rate limiting middleware consulting the shared `RateLimitState` of the app
+/// data, the requests passing through unlimited if it's missing. Sets the same headers and
+/// error responses as the governor middleware with the `RateLimitExtractor`.
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(state) = req.app_data::<web::Data<RateLimitState>>().cloned() else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+
+    let key = RateLimitExtractor.extract(&req)?;
+    let (limits, limiter) = state.current();
+
+    match limiter.check_key(&key) {
+        Ok(snapshot) => {
+            state.allowed_requests.fetch_add(1, Ordering::Relaxed);
+
+            let mut res = next.call(req).await?;
+            let headers = res.headers_mut();
+            headers.insert(X_RATELIMIT_LIMIT, HeaderValue::from(limits.burst_size));
+            headers.insert(
+                X_RATELIMIT_REMAINING,
+                HeaderValue::from(snapshot.remaining_burst_capacity()),
+            );
+
+            Ok(res.map_into_boxed_body())
+        }
+        Err(negative) => {
+            state.blocked_requests.fetch_add(1, Ordering::Relaxed);
+
+            let wait_time = negative.wait_time_from(DefaultClock::default().now());
+            let mut builder = HttpResponse::TooManyRequests();
+            builder
+                .insert_header((X_RATELIMIT_AFTER, wait_time.as_secs()))
+                .insert_header((X_RATELIMIT_LIMIT, limits.burst_size))
+                .insert_header((X_RATELIMIT_REMAINING, 0));
+
+            let res = RateLimitExtractor.exceed_rate_limit_response(&negative, builder);
+            Ok(req.into_response(res))
+        }
+    }
+}
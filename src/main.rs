@@ -1,43 +1,93 @@
+mod admin;
+mod constraint;
 mod controller;
 mod dfs;
 mod dlx;
+mod error;
+mod limiter;
+mod quota;
+mod rating;
+mod shutdown;
 mod solver;
+mod stats;
 mod sudoku;
+mod timing;
+mod tls;
+mod transport;
+mod ws;
 
-use std::{env, io::Result};
-
-use actix_governor::{
-    governor::middleware::StateInformationMiddleware, Governor, GovernorConfig,
-    GovernorConfigBuilder, PeerIpKeyExtractor,
+use std::{
+    collections::HashMap,
+    env,
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    time::Duration,
 };
-use actix_web::{middleware::Logger, App, HttpServer};
+
+use actix_web::{middleware::from_fn, web, App, HttpServer};
+use admin::AdminToken;
 use dotenv::dotenv;
-use env_logger::Env;
-use log::info;
+use limiter::{RateLimitState, RateLimits};
+use quota::{MemoryQuotaStore, Quotas};
+use rustls::ServerConfig;
+use shutdown::Shutdown;
+use tracing::info;
+use tracing_actix_web::TracingLogger;
+use tracing_subscriber::EnvFilter;
+use transport::Transport;
+
+// Additional time given to the cancelled solves to respond before the workers are force stopped
+const SHUTDOWN_TIMEOUT_MARGIN_S: u64 = 5;
 
 #[derive(Debug)]
 struct Conf {
-    host: String,
-    port: u16,
-    governor_conf: GovernorConfig<PeerIpKeyExtractor, StateInformationMiddleware>,
+    transport: Transport,
+    tls: Option<ServerConfig>,
+    rate_limits: RateLimits,
+    admin_token: Option<String>,
+    api_keys: HashMap<String, u64>,
+    quota_window: Duration,
+    payload_limit: usize,
+    grace_period: Duration,
 }
 
 impl Conf {
-    fn new() -> Self {
+    fn new() -> Result<Self> {
         dotenv().ok();
 
         // Logging
-        env_logger::init_from_env(Env::default().default_filter_or("info"));
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+        let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+        match env::var("LOG_FORMAT").unwrap_or("compact".into()).as_str() {
+            "json" => subscriber.json().init(),
+            _ => subscriber.compact().init(),
+        }
 
         // Socket bindings
-        let host = match env::var("MODE").unwrap_or("testing".into()).as_str() {
-            "prod" => String::from("0.0.0.0"),
-            _ => String::from("127.0.0.1"),
+        let transport = Transport::from_env()?;
+
+        // TLS termination, plaintext unless both of the paths are set
+        let tls_path = |key: &str| env::var(key).ok().filter(|path| !path.is_empty());
+        let tls = match (tls_path("TLS_CERT_PATH"), tls_path("TLS_KEY_PATH")) {
+            (Some(_), Some(_)) if matches!(transport, Transport::Unix(_)) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "TLS is only supported on the TCP transport, unset either UNIX_SOCKET or the TLS paths",
+                ));
+            }
+            (Some(cert_path), Some(key_path)) => Some(tls::server_config(
+                Path::new(&cert_path),
+                Path::new(&key_path),
+            )?),
+            (None, None) => None,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Both TLS_CERT_PATH and TLS_KEY_PATH must be set to enable TLS",
+                ));
+            }
         };
-        let port = env::var("PORT")
-            .unwrap_or("8080".into())
-            .parse::<u16>()
-            .expect("Failed to parse the host port number");
 
         // Rate limiting
         let interval_s = env::var("QUOTA_REPLENISH_INTERVAL_S")
@@ -48,35 +98,153 @@ impl Conf {
             .unwrap_or("3".into())
             .parse::<u32>()
             .expect("Failed to parse the burst size");
-        let governor_conf = GovernorConfigBuilder::default()
-            .per_second(interval_s)
-            .burst_size(burst_size)
-            .use_headers()
-            .finish()
-            .expect("Failed to generate a config for the rate limiter");
-
-        Self {
-            host,
-            port,
-            governor_conf,
-        }
+
+        // Admin endpoints, disabled unless a token is set
+        let admin_token = env::var("ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        // Per-key quotas, disabled unless any keys are set
+        let api_keys = env::var("API_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                let (key, limit) = pair
+                    .trim()
+                    .split_once(':')
+                    .expect("Failed to parse the API keys, expected 'key:limit' pairs");
+                let limit = limit
+                    .parse::<u64>()
+                    .expect("Failed to parse the quota limit of an API key");
+                (key.to_owned(), limit)
+            })
+            .collect::<HashMap<String, u64>>();
+        let quota_window_s = env::var("QUOTA_WINDOW_S")
+            .unwrap_or("86400".into())
+            .parse::<u64>()
+            .expect("Failed to parse the quota window");
+
+        // Request payloads
+        let payload_limit = env::var("PAYLOAD_LIMIT_BYTES")
+            .unwrap_or(controller::DEFAULT_PAYLOAD_LIMIT.to_string())
+            .parse::<usize>()
+            .expect("Failed to parse the payload limit");
+
+        // Graceful shutdown
+        let grace_period_s = env::var("SHUTDOWN_GRACE_PERIOD_S")
+            .unwrap_or("30".into())
+            .parse::<u64>()
+            .expect("Failed to parse the shutdown grace period");
+
+        Ok(Self {
+            transport,
+            tls,
+            rate_limits: RateLimits {
+                interval_s,
+                burst_size,
+            },
+            admin_token,
+            api_keys,
+            quota_window: Duration::from_secs(quota_window_s),
+            payload_limit,
+            grace_period: Duration::from_secs(grace_period_s),
+        })
     }
 }
 
 #[actix_web::main]
 async fn main() -> Result<()> {
-    let conf = Conf::new();
+    let conf = Conf::new()?;
 
-    info!("Starting a listener on {}:{}", conf.host, conf.port);
+    let shutdown = Shutdown::new();
+    let app_shutdown = shutdown.clone();
+    let rate_limit_state = web::Data::new(
+        RateLimitState::new(conf.rate_limits)
+            .expect("Failed to generate a config for the rate limiter"),
+    );
+    let payload_limit = conf.payload_limit;
+    let admin_token = conf
+        .admin_token
+        .map(|token| web::Data::new(AdminToken::new(token)));
+    let quotas = (!conf.api_keys.is_empty()).then(|| {
+        web::Data::new(Quotas::new(
+            MemoryQuotaStore::new(),
+            conf.api_keys,
+            conf.quota_window,
+        ))
+    });
 
-    // Only panics if no socket addresses were successfully bound or if no Tokio runtime is set up
-    HttpServer::new(move || {
-        App::new()
-            .wrap(Governor::new(&conf.governor_conf))
-            .wrap(Logger::default())
-            .service(controller::solve)
+    // Only panics if no Tokio runtime is set up
+    let builder = HttpServer::new(move || {
+        let mut app = App::new()
+            .app_data(web::Data::new(app_shutdown.clone()))
+            .app_data(rate_limit_state.clone())
+            .app_data(controller::json_config(payload_limit));
+
+        if let Some(admin_token) = &admin_token {
+            app = app.app_data(admin_token.clone());
+        }
+
+        if let Some(quotas) = &quotas {
+            app = app.app_data(quotas.clone());
+        }
+
+        app.wrap(TracingLogger::default())
+            // The admin endpoints are exempt from the rate limiting of the public ones
+            .service(admin::get_rate_limit)
+            .service(admin::put_rate_limit)
+            .service(
+                web::scope("")
+                    .wrap(from_fn(limiter::rate_limit))
+                    .service(controller::solve)
+                    .service(controller::solve_dfs)
+                    .service(controller::validate)
+                    .service(web::scope("/v1").service(controller::solve_v1))
+                    .service(ws::solve_ws)
+                    .service(quota::get_usage),
+            )
     })
-    .bind((conf.host, conf.port))?
-    .run()
-    .await
+    // The termination signals are handled by the shutdown coordinator instead
+    .disable_signals()
+    .shutdown_timeout(conf.grace_period.as_secs() + SHUTDOWN_TIMEOUT_MARGIN_S);
+
+    info!(
+        "Starting a listener on the {} ({})",
+        conf.transport,
+        if conf.tls.is_some() {
+            "TLS"
+        } else {
+            "plaintext"
+        }
+    );
+
+    let server = match (&conf.transport, conf.tls) {
+        (Transport::Tcp { host, port }, Some(tls)) => {
+            builder.bind_rustls_0_23((host.as_str(), *port), tls)?
+        }
+        (Transport::Tcp { host, port }, None) => builder.bind((host.as_str(), *port))?,
+        #[cfg(unix)]
+        (Transport::Unix(path), _) => {
+            transport::remove_stale_socket(path)?;
+            builder.bind_uds(path)?
+        }
+        // Already rejected while resolving the transport
+        #[cfg(not(unix))]
+        (Transport::Unix(_), _) => {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Unix domain sockets aren't supported on this platform",
+            ))
+        }
+    }
+    .run();
+
+    actix_web::rt::spawn(shutdown::handle_signals(
+        server.handle(),
+        shutdown,
+        conf.grace_period,
+    ));
+
+    server.await
 }
@@ -0,0 +1,275 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::ApiError;
+
+/// Header identifying the consumer whose quota the request is accounted to.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Puzzle consumption of a single API key within the rolling window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    /// Number of puzzles the key can consume within the window.
+    pub limit: u64,
+    /// Number of puzzles consumed within the current window.
+    pub consumed: u64,
+    pub remaining: u64,
+    /// Seconds until the oldest consumption leaves the window, i.e. until `remaining` grows.
+    pub reset_s: u64,
+}
+
+/// Storage of the consumed quotas, kept behind a trait so that the accounting can be moved into an
+/// external store shared by multiple instances.
+pub trait QuotaStore: Send + Sync {
+    /// Records `amount` puzzles consumed by the key if they fit into the `limit` of the rolling
+    /// `window`, which must be checked and updated atomically. Returns the usage after the
+    /// consumption, or the unchanged usage as the error if the quota would be exceeded.
+    fn try_consume(
+        &self,
+        key: &str,
+        amount: u64,
+        limit: u64,
+        window: Duration,
+    ) -> Result<QuotaUsage, QuotaUsage>;
+
+    /// Returns the current usage of the key without consuming anything.
+    fn usage(&self, key: &str, limit: u64, window: Duration) -> QuotaUsage;
+}
+
+/// In-memory `QuotaStore` recording the timestamped consumptions of each key.
+#[derive(Debug, Default)]
+pub struct MemoryQuotaStore {
+    consumptions: Mutex<HashMap<String, VecDeque<(Instant, u64)>>>,
+}
+
+impl MemoryQuotaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn try_consume_at(
+        &self,
+        key: &str,
+        amount: u64,
+        limit: u64,
+        window: Duration,
+        now: Instant,
+    ) -> Result<QuotaUsage, QuotaUsage> {
+        let mut consumptions = self
+            .consumptions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let history = consumptions.entry(key.to_owned()).or_default();
+        let usage = Self::expire(history, limit, window, now);
+
+        if amount > usage.remaining {
+            return Err(usage);
+        }
+
+        if amount > 0 {
+            history.push_back((now, amount));
+        }
+
+        Ok(Self::expire(history, limit, window, now))
+    }
+
+    fn usage_at(&self, key: &str, limit: u64, window: Duration, now: Instant) -> QuotaUsage {
+        let mut consumptions = self
+            .consumptions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match consumptions.get_mut(key) {
+            Some(history) => Self::expire(history, limit, window, now),
+            None => Self::expire(&mut VecDeque::new(), limit, window, now),
+        }
+    }
+
+    /// Drops the consumptions older than the window and summarizes the remaining ones.
+    fn expire(
+        history: &mut VecDeque<(Instant, u64)>,
+        limit: u64,
+        window: Duration,
+        now: Instant,
+    ) -> QuotaUsage {
+        while history
+            .front()
+            .is_some_and(|&(at, _)| now.duration_since(at) >= window)
+        {
+            history.pop_front();
+        }
+
+        let consumed = history.iter().map(|&(_, amount)| amount).sum::<u64>();
+        // Rounded up to avoid telling the client to retry before the quota is replenished
+        let reset_s = history.front().map_or(0, |&(at, _)| {
+            let reset = window - now.duration_since(at);
+            reset.as_secs() + u64::from(reset.subsec_nanos() > 0)
+        });
+
+        QuotaUsage {
+            limit,
+            consumed,
+            remaining: limit.saturating_sub(consumed),
+            reset_s,
+        }
+    }
+}
+
+impl QuotaStore for MemoryQuotaStore {
+    fn try_consume(
+        &self,
+        key: &str,
+        amount: u64,
+        limit: u64,
+        window: Duration,
+    ) -> Result<QuotaUsage, QuotaUsage> {
+        self.try_consume_at(key, amount, limit, window, Instant::now())
+    }
+
+    fn usage(&self, key: &str, limit: u64, window: Duration) -> QuotaUsage {
+        self.usage_at(key, limit, window, Instant::now())
+    }
+}
+
+/// Per-key puzzle quotas of the solver endpoints, stored in the app data. Each API key has its
+/// own budget of puzzles per rolling window, the requests without a known key being rejected.
+pub struct Quotas {
+    store: Box<dyn QuotaStore>,
+    limits: HashMap<String, u64>,
+    window: Duration,
+}
+
+impl Quotas {
+    pub fn new(
+        store: impl QuotaStore + 'static,
+        limits: HashMap<String, u64>,
+        window: Duration,
+    ) -> Self {
+        Self {
+            store: Box::new(store),
+            limits,
+            window,
+        }
+    }
+
+    /// Returns the API key of the request and its limit.
+    fn key<'a>(&self, req: &'a HttpRequest) -> Result<(&'a str, u64), ApiError> {
+        let key = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        match key.and_then(|key| Some((key, *self.limits.get(key)?))) {
+            Some(key) => Ok(key),
+            None => {
+                debug!("Incoming request rejected due to a missing or unknown API key");
+                Err(ApiError::InvalidApiKey)
+            }
+        }
+    }
+
+    /// Consumes the quota of `puzzles` for the caller, rejecting the whole batch with
+    /// `ApiError::QuotaExceeded` if it doesn't fit into the remaining quota.
+    pub fn consume(&self, req: &HttpRequest, puzzles: usize) -> Result<QuotaUsage, ApiError> {
+        let (key, limit) = self.key(req)?;
+
+        self.store
+            .try_consume(key, puzzles as u64, limit, self.window)
+            .map_err(|usage| {
+                debug!(
+                    puzzles,
+                    remaining = usage.remaining,
+                    "Incoming batch rejected due to an exceeded quota"
+                );
+                ApiError::QuotaExceeded(usage)
+            })
+    }
+
+    pub fn usage(&self, req: &HttpRequest) -> Result<QuotaUsage, ApiError> {
+        let (key, limit) = self.key(req)?;
+
+        Ok(self.store.usage(key, limit, self.window))
+    }
+
+    /// Consumes the quota if the quotas are enabled, i.e. stored in the app data.
+    pub(crate) fn consume_if_enabled(
+        quotas: Option<&web::Data<Quotas>>,
+        req: &HttpRequest,
+        puzzles: usize,
+    ) -> Result<(), ApiError> {
+        match quotas {
+            Some(quotas) => quotas.consume(req, puzzles).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reports the current puzzle consumption of the caller's API key, or `ApiError::QuotasDisabled`
+/// if no quotas are configured.
+#[get("/usage")]
+pub async fn get_usage(
+    req: HttpRequest,
+    quotas: Option<web::Data<Quotas>>,
+) -> Result<HttpResponse, ApiError> {
+    let quotas = quotas.ok_or(ApiError::QuotasDisabled)?;
+
+    Ok(HttpResponse::Ok().json(quotas.usage(&req)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_consume_within_limit() {
+        let store = MemoryQuotaStore::new();
+        let now = Instant::now();
+
+        let usage = store.try_consume_at("key", 7, 10, WINDOW, now).unwrap();
+        assert_eq!(usage.consumed, 7);
+        assert_eq!(usage.remaining, 3);
+        assert_eq!(usage.reset_s, 60);
+
+        // Batches exceeding the remaining quota are rejected as a whole
+        let usage = store.try_consume_at("key", 4, 10, WINDOW, now).unwrap_err();
+        assert_eq!(usage.remaining, 3);
+
+        let usage = store.try_consume_at("key", 3, 10, WINDOW, now).unwrap();
+        assert_eq!(usage.remaining, 0);
+
+        // Keys are accounted independently
+        assert_eq!(store.usage_at("other", 10, WINDOW, now).remaining, 10);
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let store = MemoryQuotaStore::new();
+        let start = Instant::now();
+
+        store.try_consume_at("key", 6, 10, WINDOW, start).unwrap();
+        let later = start + Duration::from_secs(30);
+        store.try_consume_at("key", 4, 10, WINDOW, later).unwrap();
+
+        let usage = store.usage_at("key", 10, WINDOW, later);
+        assert_eq!(usage.remaining, 0);
+        assert_eq!(usage.reset_s, 30);
+
+        // Only the first consumption has left the window
+        let usage = store.usage_at("key", 10, WINDOW, start + WINDOW);
+        assert_eq!(usage.consumed, 4);
+        assert_eq!(usage.reset_s, 30);
+
+        let usage = store.usage_at("key", 10, WINDOW, later + WINDOW);
+        assert_eq!(usage.consumed, 0);
+        assert_eq!(usage.reset_s, 0);
+    }
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dfs::{DfsSolver, TechniqueCounts},
+    solver::{Metadata, SudokuSolver},
+    sudoku::Sudoku,
+};
+
+// Effort scores at the deciles (10th to 90th percentile) of the first 1000 puzzles of the
+// 'sudoku17' collection. The score doesn't depend on the solver, so neither does the grade
+const EFFORT_CALIBRATION: [u64; 9] = [26, 30, 33, 37, 40, 45, 51, 62, 295];
+
+// Weights of the applications of the propagation techniques and of the cells left to the search
+// in the effort score, roughly by how hard they are to spot
+const HIDDEN_SINGLE_WEIGHT: u64 = 1;
+const LOCKED_CANDIDATES_WEIGHT: u64 = 3;
+const NAKED_PAIR_WEIGHT: u64 = 3;
+const NAKED_TRIPLE_WEIGHT: u64 = 5;
+const UNRESOLVED_CELL_WEIGHT: u64 = 10;
+
+/// Solver-independent measure of the work a puzzle takes, i.e. the techniques the constraint
+/// propagation of DFS applies to the original puzzle and the cells it leaves to the search,
+/// regardless of the solver which actually solved it. The diagonals of an X-Sudoku are
+/// propagated like the other units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Effort {
+    pub techniques: TechniqueCounts,
+    /// Empty cells left unresolved by the propagation.
+    pub unresolved: usize,
+}
+
+impl Effort {
+    /// Runs the propagation on a copy of the Sudoku under the rules of its variant and collects
+    /// its counters.
+    pub fn of(sudoku: &Sudoku) -> Self {
+        let mut dfs = DfsSolver::new(sudoku.clone());
+        // A contradiction still leaves the cells it didn't resolve
+        let _ = dfs.propagate();
+
+        Self {
+            techniques: dfs.techniques(),
+            unresolved: dfs.get_sudoku().empty_positions().count(),
+        }
+    }
+
+    /// Weighs the counters into a single score, which the grade is the decile of.
+    pub fn score(&self) -> u64 {
+        let t = &self.techniques;
+
+        HIDDEN_SINGLE_WEIGHT * t.hidden_singles as u64
+            + LOCKED_CANDIDATES_WEIGHT * (t.pointing_pairs + t.box_line_reductions) as u64
+            + NAKED_PAIR_WEIGHT * t.naked_pairs as u64
+            + NAKED_TRIPLE_WEIGHT * t.naked_triples as u64
+            + UNRESOLVED_CELL_WEIGHT * self.unresolved as u64
+    }
+}
+
+/// Difficulty estimate of a single solved puzzle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Difficulty {
+    /// Number of nodes visited by the solver.
+    pub visited_nodes: u64,
+    /// Number of given cells in the puzzle.
+    pub clues: usize,
+    /// Effort score of the puzzle, see `Effort`.
+    pub score: u64,
+    /// Normalized grade between 1 (easiest) and 10 (hardest), i.e. the decile of the effort
+    /// score among the calibration puzzles, the same for either of the solvers.
+    pub grade: u8,
+}
+
+/// Rates the difficulty of a solved puzzle based on the solver process metadata and the clue
+/// count of the puzzle. The grade is derived from the `effort` of the puzzle, while the visited
+/// nodes are only reported as is.
+pub fn rating(metadata: &Metadata, clues: usize, effort: &Effort) -> Difficulty {
+    let score = effort.score();
+    let exceeded = EFFORT_CALIBRATION
+        .iter()
+        .filter(|&&threshold| score > threshold)
+        .count();
+
+    Difficulty {
+        visited_nodes: metadata.visited_nodes(),
+        clues,
+        score,
+        grade: exceeded as u8 + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader},
+    };
+
+    use super::*;
+    use crate::{
+        solver::{Solver, SolverType},
+        sudoku::Variant,
+    };
+
+    fn rate(grid: &str, solver_type: SolverType) -> Difficulty {
+        let mut solver =
+            Solver::new(Sudoku::new(String::from(grid)).unwrap(), solver_type).unwrap();
+        solver.solve();
+
+        rating(
+            solver.metadata(),
+            solver.clues(),
+            &Effort::of(solver.puzzle()),
+        )
+    }
+
+    #[test]
+    fn test_easy_reference() {
+        let grid =
+            "509003407001547893473918560057030684102860309836704105390076201010382040204000730";
+        let dfs = rate(grid, SolverType::Dfs);
+        let dlx = rate(grid, SolverType::Dlx);
+
+        assert_eq!(dfs.clues, 54);
+        assert_eq!(dfs.grade, 1);
+        assert_eq!((dfs.score, dfs.grade), (dlx.score, dlx.grade));
+    }
+
+    #[test]
+    fn test_sudoku17_reference() {
+        let grid =
+            "000000012008030000000000040120500000000004700060000000507000300000620000000100000";
+        let dfs = rate(grid, SolverType::Dfs);
+        let dlx = rate(grid, SolverType::Dlx);
+
+        assert_eq!(dfs.clues, 17);
+        // Fully resolved by the propagation, which DLX doesn't have, yet rated the same
+        assert_eq!(dfs.visited_nodes, 0);
+        assert!(dlx.visited_nodes > 0);
+        assert_eq!(dfs.grade, dlx.grade);
+        assert_eq!(dfs.score, dlx.score);
+        assert!((2..10).contains(&dfs.grade));
+
+        // Mostly left to the search by the propagation, which puts it in the top decile
+        let hard =
+            "000801000000000205000000700507620000030000010000000000600000040000250000490000000";
+        let (dfs, dlx) = (rate(hard, SolverType::Dfs), rate(hard, SolverType::Dlx));
+        assert_eq!(dfs.grade, 10);
+        assert_eq!(dlx.grade, 10);
+    }
+
+    #[test]
+    fn test_x_variant() {
+        let puzzle =
+            "050060000000080020100042073560000000400030000003000014000900030000010000000000201"
+                .parse::<Sudoku>()
+                .unwrap()
+                .with_variant(Variant::X);
+        let x = Effort::of(&puzzle);
+        let classic = Effort::of(&puzzle.clone().with_variant(Variant::Classic));
+
+        // Fully resolved only with the diagonals, which the score has to account for
+        assert_eq!(x.unresolved, 0);
+        assert!(classic.unresolved > 0);
+        assert_ne!(x.score(), classic.score());
+
+        let mut solver = Solver::new(puzzle, SolverType::Dlx).unwrap();
+        solver.solve();
+        assert_eq!(
+            solver.get_sudoku().grid_to_string(),
+            "254367198376189425189542673567491382491238567823756914715924836642813759938675241"
+        );
+        assert_eq!(
+            rating(solver.metadata(), solver.clues(), &x).score,
+            x.score()
+        );
+    }
+
+    #[test]
+    fn test_calibration_spread() {
+        assert!(EFFORT_CALIBRATION.is_sorted());
+        assert!(EFFORT_CALIBRATION.windows(2).all(|w| w[0] < w[1]));
+
+        // Every grade is given to roughly a tenth of the calibration puzzles
+        let file = File::open("./tests/sudoku17").unwrap();
+        let mut grades = [0; 10];
+
+        for line in BufReader::new(file).lines().take(1000) {
+            let score = Effort::of(&line.unwrap().parse().unwrap()).score();
+            let exceeded = EFFORT_CALIBRATION.iter().filter(|&&t| score > t).count();
+            grades[exceeded] += 1;
+        }
+
+        assert!(
+            grades.iter().all(|&count| (50..=150).contains(&count)),
+            "{grades:?}"
+        );
+    }
+}
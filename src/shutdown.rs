@@ -0,0 +1,85 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use actix_web::dev::ServerHandle;
+use tracing::{info, warn};
+
+/// Coordinates the graceful shutdown of the server with the solver layer. Stored in the app data,
+/// the solvers of the in-flight batches observe its shared cancellation flag.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels all the in-flight and queued solves.
+    pub fn trigger(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the shared cancellation flag to be observed by the solvers.
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+}
+
+/// Waits for a termination signal (SIGTERM or Ctrl-C), after which the server stops accepting new
+/// connections and the in-flight batches are allowed to finish within the `grace_period`. Once the
+/// grace period is over, the remaining solves are cancelled so that the batches respond with
+/// their partial results instead of being cut off.
+pub async fn handle_signals(server: ServerHandle, shutdown: Shutdown, grace_period: Duration) {
+    wait_for_signal().await;
+    info!(
+        "Received a termination signal, draining the in-flight batches for {} seconds",
+        grace_period.as_secs()
+    );
+
+    let stop = actix_web::rt::spawn(async move { server.stop(true).await });
+    actix_web::rt::time::sleep(grace_period).await;
+
+    if !stop.is_finished() {
+        warn!("Grace period is over, cancelling the remaining solves");
+        shutdown.trigger();
+    }
+
+    let _ = stop.await;
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            tokio::select! {
+                _ = sigterm.recv() => (),
+                _ = tokio::signal::ctrl_c() => (),
+            }
+        }
+        Err(e) => {
+            warn!(
+                "Failed to listen for SIGTERM, only Ctrl-C is handled: {}",
+                e
+            );
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
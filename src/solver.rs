@@ -1,8 +1,26 @@
-use std::{fmt::Debug, time::Instant};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{dfs::DfsSolver, dlx::DlxSolver, sudoku::Sudoku};
+use crate::{
+    dfs::{Contradiction, DfsOptions, DfsSolver, SearchLimit, SearchStats, TechniqueCounts},
+    dlx::{ColumnSelection, DlxSolver, DlxStats, MatrixTemplate},
+    sudoku::{OutputFormat, Pos, Sudoku, SudokuError, Variant},
+    timing::Stopwatch,
+};
+
+// Re-exported for the library consumers of its former location, unused by the binary itself
+#[allow(unused_imports)]
+pub use crate::constraint::has_unique_items;
 
 pub mod macros {
     macro_rules! skip_fail_option {
@@ -17,16 +35,545 @@ pub mod macros {
     pub(crate) use skip_fail_option;
 }
 
+/// Work done by a solver in the units defined the same way for both of them, the `decisions`
+/// being reported as the visited nodes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCounts {
+    /// Branches tried at the choice points, i.e. the tentative values of the cells for DFS and
+    /// the rows of the chosen column for DLX.
+    pub decisions: u64,
+    /// Candidates eliminated as the consequence of the decisions, i.e. the values removed from the
+    /// domains by the assignments for DFS and the rows removed by covering the columns for DLX.
+    pub propagations: u64,
+    /// Choices compared to pick the next choice point, i.e. the empty cells compared by MRV for
+    /// DFS and the columns compared by their sizes for DLX.
+    pub scans: u64,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Metadata {
+    /// Decisions of the solver, see `NodeCounts`.
     visited_nodes: u64,
-    cpu_time_ms: u128,
+    node_counts: NodeCounts,
+    /// CPU time consumed by the solving thread, falls back to the wall-clock time on platforms
+    /// where the thread CPU time is unavailable.
+    cpu_time: Duration,
+    wall_time: Duration,
+    /// Peak length of the DFS undo trail, `0` for DLX.
+    max_trail_len: usize,
+    /// Applications of the DFS propagation techniques, all zero for DLX.
+    techniques: TechniqueCounts,
+    /// Values removed by MAC during the DFS search, `0` for DLX and forward checking.
+    mac_prunings: u64,
+    /// Limit which stopped the DFS search before it finished, if any.
+    limit_reached: Option<SearchLimit>,
+    /// Counters of the DFS search, `None` for DLX.
+    search_stats: Option<SearchStats>,
+    /// Counters of the DLX matrix, `None` for DFS.
+    dlx_stats: Option<DlxStats>,
+    /// Whether the grid was already solved, in which case neither of the solvers was run.
+    already_solved: bool,
+}
+
+impl Metadata {
+    pub fn visited_nodes(&self) -> u64 {
+        self.visited_nodes
+    }
+
+    pub fn node_counts(&self) -> NodeCounts {
+        self.node_counts
+    }
+
+    pub fn cpu_time(&self) -> Duration {
+        self.cpu_time
+    }
+
+    pub fn wall_time(&self) -> Duration {
+        self.wall_time
+    }
+
+    #[allow(dead_code)]
+    pub fn max_trail_len(&self) -> usize {
+        self.max_trail_len
+    }
+
+    #[allow(dead_code)]
+    pub fn techniques(&self) -> TechniqueCounts {
+        self.techniques
+    }
+
+    #[allow(dead_code)]
+    pub fn mac_prunings(&self) -> u64 {
+        self.mac_prunings
+    }
+
+    #[allow(dead_code)]
+    pub fn limit_reached(&self) -> Option<SearchLimit> {
+        self.limit_reached
+    }
+
+    pub fn search_stats(&self) -> Option<SearchStats> {
+        self.search_stats
+    }
+
+    pub fn dlx_stats(&self) -> Option<DlxStats> {
+        self.dlx_stats
+    }
+
+    pub fn already_solved(&self) -> bool {
+        self.already_solved
+    }
+}
+
+// Node budget of the second-solution probe if the entry doesn't specify its `node_limit`
+const UNIQUE_PROBE_NODE_BUDGET: u64 = 1_000_000;
+// Number of visited nodes between the checks of the shared cancellation flag
+const CANCEL_CHECK_INTERVAL_NODES: u64 = 1000;
+
+/// Solver algorithms available to the `Solver` wrapper.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SolverType {
+    /// AC-3 constraint propagation followed by DFS with MRV heuristic and Forward Checking.
+    Dfs,
+    /// Algorithm X with the dancing links technique, used by default as it's magnitudes faster.
+    #[default]
+    Dlx,
+    /// Picks either of the solvers heuristically based on the given Sudoku.
+    Auto,
+}
+
+impl SolverType {
+    pub const VARIANTS: [SolverType; 3] = [SolverType::Dfs, SolverType::Dlx, SolverType::Auto];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolverType::Dfs => "dfs",
+            SolverType::Dlx => "dlx",
+            SolverType::Auto => "auto",
+        }
+    }
+
+    /// Resolves `SolverType::Auto` into the solver expected to perform better for the given
+    /// Sudoku, other variants are returned as is. DLX dominates on sparse puzzles, so DFS is only
+    /// picked for heavily clued puzzles which a cheap naked singles pass already completes (i.e.
+    /// the AC-3 phase of DFS finishes the puzzle without any search), and for the grids other
+    /// than 9x9 which the heuristic isn't tuned for.
+    pub fn resolve(self, sudoku: &Sudoku) -> SolverType {
+        // Tuned based on the benchmarks, see `benches/rand_solver_comps.rs`
+        const AUTO_DFS_MIN_CLUES: usize = 40;
+
+        match self {
+            SolverType::Auto if sudoku.size() != 9 => SolverType::Dfs,
+            SolverType::Auto => {
+                if sudoku.clue_count() >= AUTO_DFS_MIN_CLUES
+                    && propagate_singles(sudoku.as_flat().to_vec()) == 0
+                {
+                    SolverType::Dfs
+                } else {
+                    SolverType::Dlx
+                }
+            }
+            solver_type => solver_type,
+        }
+    }
+}
+
+/// Fills the cells of the row-major 9x9 grid with only a single legal value until no such cells
+/// remain, and returns the number of cells left empty afterwards.
+fn propagate_singles(mut grid: Vec<u8>) -> usize {
+    loop {
+        let mut change = false;
+
+        for pos in Pos::all(9) {
+            if grid[pos.index(9)] != 0 {
+                continue;
+            }
+
+            // Bitmask of the values already present in the related cells
+            let mut seen = 0u16;
+
+            for x in 0..9 {
+                seen |= 1 << grid[Pos::from_row_col(pos.row(), x).index(9)];
+                seen |= 1 << grid[Pos::from_row_col(x, pos.col()).index(9)];
+                seen |= 1 << grid[Pos::from_box(pos.box_index(3), x, 3).index(9)];
+            }
+
+            let candidates = !seen & 0b11_1111_1110;
+
+            if candidates.count_ones() == 1 {
+                grid[pos.index(9)] = candidates.trailing_zeros() as u8;
+                change = true;
+            }
+        }
+
+        if !change {
+            return grid.iter().filter(|&&value| value == 0).count();
+        }
+    }
 }
 
-pub trait SudokuSolver {
+impl fmt::Display for SolverType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SolverType {
+    type Err = String;
+
+    /// Parses the solver type case-insensitively, returns an error message listing the valid
+    /// options if the name doesn't match any of them.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::VARIANTS
+            .into_iter()
+            .find(|variant| variant.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let valid = Self::VARIANTS
+                    .iter()
+                    .map(|variant| format!("'{}'", variant))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                format!("Unknown solver '{}', valid options are: {}", s, valid)
+            })
+    }
+}
+
+/// Options of a single solver process, as applied after the request level validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolveOptions {
+    /// Maximum number of distinct solutions to search for.
+    pub max_solutions: usize,
+    /// Maximum number of nodes to visit before giving up, unlimited if `None`.
+    pub node_limit: Option<u64>,
+    /// Maximum solving time in milliseconds before giving up, unlimited if `None`.
+    pub timeout_ms: Option<u64>,
+    /// Whether the grid after the constraint propagation should be included in the result.
+    pub include_propagated: bool,
+    /// Whether the remaining candidates of the cells after the constraint propagation should be
+    /// included in the result.
+    #[serde(alias = "return_candidates")]
+    pub include_candidates: bool,
+    /// Whether the uniqueness of the solution should be checked with a second-solution probe.
+    pub check_unique: bool,
+    /// Whether the pretty-printed solution should be included in the result.
+    #[serde(default)]
+    pub pretty: bool,
+    /// Whether the cells filled in by the solver should be included in the result.
+    #[serde(default)]
+    pub include_diff: bool,
+    /// Format of the solved and propagated grid strings of the result.
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    /// Whether the puzzles without a unique solution should be rejected before solving.
+    #[serde(default)]
+    pub require_unique: bool,
+    /// Whether the structural statistics of the puzzle should be included in the result.
+    #[serde(default)]
+    pub include_stats: bool,
+    /// Seed of the shuffled value order of the DFS search, the values of each cell being tried in
+    /// ascending order if `None`.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Whether DFS should stop after the propagation, returning the partially filled grid if the
+    /// propagation alone doesn't solve the puzzle. DFS is used regardless of the requested solver,
+    /// as DLX doesn't propagate.
+    #[serde(default)]
+    pub propagate_only: bool,
+    /// Whether DFS should continue after the first solution to determine whether there's a second
+    /// one, bounded by the `node_limit` (or the budget of the uniqueness probe).
+    #[serde(default)]
+    pub detect_multiple: bool,
+    /// Whether DLX should explore the rows of the first chosen column in parallel when searching
+    /// for the first solution, which is the same as without it for the puzzles with a single one.
+    #[serde(default)]
+    pub parallel: bool,
+    /// Heuristic picking the next column to cover in the DLX search.
+    #[serde(default)]
+    pub column_selection: ColumnSelection,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            max_solutions: 1,
+            node_limit: None,
+            timeout_ms: None,
+            include_propagated: false,
+            include_candidates: false,
+            check_unique: false,
+            pretty: false,
+            include_diff: false,
+            output_format: OutputFormat::FlatZero,
+            require_unique: false,
+            include_stats: false,
+            seed: None,
+            propagate_only: false,
+            detect_multiple: false,
+            parallel: false,
+            column_selection: ColumnSelection::MinCount,
+        }
+    }
+}
+
+/// State of the grid after the AC-3 constraint propagation of `DfsSolver`, before the search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Propagation {
+    /// Grid with the cells resolved by the propagation filled in.
+    pub grid: Sudoku,
+    /// Remaining candidates of the still empty cells, keyed by their position.
+    pub candidates: BTreeMap<Pos, Vec<u8>>,
+}
+
+impl Propagation {
+    /// Runs the propagation on a copy of the given Sudoku.
+    fn new(sudoku: &Sudoku) -> Self {
+        let mut dfs = DfsSolver::new(sudoku.clone());
+        // The contradicting domains are still included as empty ones
+        let _ = dfs.propagate();
+
+        Self {
+            grid: dfs.get_sudoku().clone(),
+            candidates: dfs.candidates(),
+        }
+    }
+}
+
+/// Uniqueness of the solution, serialized as `true`, `false`, or `"unknown"`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Uniqueness {
+    /// The puzzle is proper, i.e. it has exactly one solution.
+    Unique,
+    /// The puzzle has more than one solution.
+    Multiple,
+    /// The uniqueness wasn't checked, or the probe ran out of its node budget.
+    #[default]
+    Unknown,
+}
+
+impl Uniqueness {
+    /// Probes a copy of the Sudoku for a second solution with DLX, restricted to the pencil marks
+    /// of the `candidates` if any (see `DlxSolver::with_candidates`), giving up after
+    /// `node_limit` visited nodes or the default budget of the `check_unique` probe if it's
+    /// `None`.
+    pub fn probe(sudoku: &Sudoku, candidates: Option<&[u16; 81]>, node_limit: Option<u64>) -> Self {
+        let budget = node_limit.unwrap_or(UNIQUE_PROBE_NODE_BUDGET);
+        let probe = match candidates {
+            Some(candidates) => DlxSolver::with_candidates(sudoku.clone(), 1, candidates),
+            None => DlxSolver::new(sudoku.clone()),
+        };
+
+        Self::from_probe(
+            probe
+                .ok()
+                .and_then(|mut probe| probe.is_unique(Some(budget))),
+        )
+    }
+
+    /// Converts the result of `DlxSolver::is_unique`, `None` if the probe ran out of its budget or
+    /// found no solution at all.
+    fn from_probe(unique: Option<bool>) -> Self {
+        match unique {
+            Some(true) => Uniqueness::Unique,
+            Some(false) => Uniqueness::Multiple,
+            None => Uniqueness::Unknown,
+        }
+    }
+}
+
+impl Serialize for Uniqueness {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Uniqueness::Unique => serializer.serialize_bool(true),
+            Uniqueness::Multiple => serializer.serialize_bool(false),
+            Uniqueness::Unknown => serializer.serialize_str("unknown"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Uniqueness {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Bool(bool),
+            Str(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Bool(true) => Ok(Uniqueness::Unique),
+            Raw::Bool(false) => Ok(Uniqueness::Multiple),
+            Raw::Str(s) if s == "unknown" => Ok(Uniqueness::Unknown),
+            Raw::Str(s) => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(&s),
+                &"true, false, or \"unknown\"",
+            )),
+        }
+    }
+}
+
+/// Outcome of a single solver process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// A solution was found and it passed the verification.
+    Solved,
+    /// The search was exhausted without finding a solution, i.e. the puzzle has no solution
+    /// despite meeting the default Sudoku constraints.
+    Unsolvable,
+    /// The solver claimed to have found a solution, but it doesn't pass the verification.
+    InternalError,
+    /// The solving process was cancelled by the progress hook before it finished.
+    Cancelled,
+    /// The DFS search was stopped by the node or time limit before it finished, also recorded in
+    /// the metadata.
+    LimitReached(SearchLimit),
+    /// The propagation-only solving left some of the cells empty, the partially filled grid being
+    /// available via `get_inner_grid`.
+    Incomplete,
+}
+
+/// Snapshot of a running solver process passed to the progress hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub visited_nodes: u64,
+    /// Current depth of the search, i.e. the number of tentative assignments.
+    pub depth: usize,
+}
+
+/// Callback invoked periodically by the solvers during the search, returning `false` cancels the
+/// solving process.
+pub type ProgressHook = Box<dyn FnMut(Progress) -> bool + Send>;
+
+/// Invokes the progress hook every `interval` visited nodes and keeps track of the cancellation.
+#[derive(Default)]
+pub struct ProgressReporter {
+    hook: Option<ProgressHook>,
+    interval: u64,
+    next_report: u64,
+    cancelled: bool,
+}
+
+impl ProgressReporter {
+    pub fn new(hook: ProgressHook, interval: u64) -> Self {
+        Self {
+            hook: Some(hook),
+            interval,
+            next_report: interval,
+            cancelled: false,
+        }
+    }
+
+    /// Reports the progress to the hook once the next interval is reached. Returns `false` if the
+    /// solving process is cancelled and the search should be aborted.
+    #[inline]
+    pub fn tick(&mut self, visited_nodes: u64, depth: usize) -> bool {
+        if let Some(hook) = self.hook.as_mut() {
+            if !self.cancelled && visited_nodes >= self.next_report {
+                self.next_report = visited_nodes + self.interval;
+                self.cancelled = !hook(Progress {
+                    visited_nodes,
+                    depth,
+                });
+            }
+        }
+
+        !self.cancelled
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("hook", &self.hook.is_some())
+            .field("interval", &self.interval)
+            .field("cancelled", &self.cancelled)
+            .finish()
+    }
+}
+
+pub trait SudokuSolver: Send {
     fn solve(&mut self) -> (bool, u64);
     fn get_inner_grid(&self) -> Vec<Vec<u8>>;
+    /// Returns the inner Sudoku, retaining the clue mask of the original puzzle.
+    fn get_sudoku(&self) -> &Sudoku;
     fn grid_to_string(&self) -> String;
+    fn set_progress_reporter(&mut self, reporter: ProgressReporter);
+    fn is_cancelled(&self) -> bool;
+    /// Returns the counters of the work done by the last `solve` call, the `decisions` of which
+    /// are also returned by it as the visited nodes.
+    fn node_counts(&self) -> NodeCounts;
+
+    /// Returns the peak length of the undo trail of the backtracking search, `0` for solvers
+    /// without one.
+    fn max_trail_len(&self) -> usize {
+        0
+    }
+
+    /// Returns the contradiction which proved the puzzle unsolvable before any search during the
+    /// last `solve` call, `None` for solvers without a propagation phase.
+    fn contradiction(&self) -> Option<Contradiction> {
+        None
+    }
+
+    /// Returns the number of applications of the propagation techniques during the last `solve`
+    /// call, all zero for solvers without a propagation phase.
+    fn techniques(&self) -> TechniqueCounts {
+        TechniqueCounts::default()
+    }
+
+    /// Returns the number of the values removed by maintaining arc consistency during the search
+    /// of the last `solve` call, zero for the solvers that don't.
+    fn mac_prunings(&self) -> u64 {
+        0
+    }
+
+    /// Returns the limit which stopped the search of the last `solve` call before it finished,
+    /// `None` for the solvers without limits.
+    fn limit_reached(&self) -> Option<SearchLimit> {
+        None
+    }
+
+    /// Returns the counters of the backtracking search during the last `solve` call, `None` for
+    /// the solvers which don't count them.
+    fn search_stats(&self) -> Option<SearchStats> {
+        None
+    }
+
+    /// Returns the counters of the dancing links and the size of the exact cover matrix after
+    /// the last `solve` call, `None` for the solvers without one.
+    fn dlx_stats(&self) -> Option<DlxStats> {
+        None
+    }
+
+    /// Returns whether the last `solve` call stopped after the propagation with the grid only
+    /// partially filled, `false` for the solvers which always search.
+    fn is_incomplete(&self) -> bool {
+        false
+    }
+
+    /// Returns the uniqueness of the solution found by the last `solve` call if the solver
+    /// searched for a second one by itself, `Uniqueness::Unknown` otherwise.
+    fn uniqueness(&self) -> Uniqueness {
+        Uniqueness::Unknown
+    }
+
+    /// Returns whether the last `solve` call caught a bookkeeping bug in a solution it extracted,
+    /// which is then discarded, `false` for the solvers which don't verify their solutions.
+    fn is_corrupted(&self) -> bool {
+        false
+    }
+
+    /// Returns the distinct solutions found during the solving process, defaults to the inner
+    /// grid for solvers which stop after the first solution.
+    fn solutions(&self) -> Vec<String> {
+        vec![self.grid_to_string()]
+    }
 }
 
 impl Debug for dyn SudokuSolver {
@@ -38,32 +585,347 @@ impl Debug for dyn SudokuSolver {
 #[derive(Debug)]
 pub struct Solver {
     solver: Box<dyn SudokuSolver>,
+    /// Requested solver type before resolving `SolverType::Auto`.
+    requested_solver_type: SolverType,
+    solver_type: SolverType,
+    options: SolveOptions,
+    /// Number of given cells in the assigned Sudoku.
+    clues: usize,
+    /// Copy of the assigned Sudoku before solving.
+    puzzle: Sudoku,
     metadata: Metadata,
+    propagation: Option<Propagation>,
+    solutions: Vec<String>,
+    /// Solver of the second-solution probe, only constructed if `check_unique` is set.
+    unique_probe: Option<DlxSolver>,
+    uniqueness: Uniqueness,
+    /// Whether the assigned Sudoku is a valid solution agreeing with the pencil marks if it has
+    /// no empty cells, `None` otherwise.
+    complete: Option<bool>,
 }
 
 impl Solver {
-    pub fn new(sudoku: Sudoku, solver_type_str: &str) -> Self {
-        Self {
-            solver: match solver_type_str.to_lowercase().as_str() {
-                "dfs" => Box::new(DfsSolver::new(sudoku)),
-                _ => Box::new(DlxSolver::new(sudoku)), // Always default to DLX
-            },
+    #[allow(dead_code)]
+    pub fn new(sudoku: Sudoku, solver_type: SolverType) -> Result<Self, SudokuError> {
+        Self::with_options(sudoku, solver_type, SolveOptions::default())
+    }
+
+    /// Constructs the solver of the given type for the Sudoku, returning `Err` if the solver (or
+    /// the second-solution probe) can't be constructed from it, e.g. the exact cover matrix of
+    /// DLX.
+    pub fn with_options(
+        sudoku: Sudoku,
+        solver_type: SolverType,
+        options: SolveOptions,
+    ) -> Result<Self, SudokuError> {
+        Self::with_candidates(sudoku, solver_type, options, None)
+    }
+
+    /// Constructs the solver like `with_options`, the DLX search (and the second-solution probe)
+    /// being restricted to the digits allowed by the pencil marks of the `candidates` if any, see
+    /// `DlxSolver::with_candidates`. Only DLX is able to honor them, so it's used for the
+    /// entries with pencil marks regardless of the requested `solver_type` and `propagate_only`.
+    pub fn with_candidates(
+        sudoku: Sudoku,
+        solver_type: SolverType,
+        options: SolveOptions,
+        candidates: Option<[u16; 81]>,
+    ) -> Result<Self, SudokuError> {
+        Self::with_template(sudoku, solver_type, options, candidates, None)
+    }
+
+    /// Constructs the solver like `with_candidates`, the DLX matrices (and the one of the
+    /// second-solution probe) of the grids without pencil marks being copied from the `template`
+    /// if it fits them instead of built from scratch, see `DlxSolver::with_template`.
+    pub fn with_template(
+        sudoku: Sudoku,
+        solver_type: SolverType,
+        options: SolveOptions,
+        candidates: Option<[u16; 81]>,
+        template: Option<&MatrixTemplate>,
+    ) -> Result<Self, SudokuError> {
+        let requested_solver_type = solver_type;
+        let solver_type = match solver_type {
+            // The search of an X-Sudoku is left to DLX, DFS only propagating its diagonals
+            _ if sudoku.variant() == Variant::X => SolverType::Dlx,
+            // Only DLX is able to honor the pencil marks
+            _ if candidates.is_some() => SolverType::Dlx,
+            // Only DFS has a propagation phase to stop after
+            _ if options.propagate_only => SolverType::Dfs,
+            // Only DLX is able to enumerate multiple solutions
+            SolverType::Auto if options.max_solutions > 1 => SolverType::Dlx,
+            solver_type => solver_type.resolve(&sudoku),
+        };
+        let propagation = (options.include_propagated || options.include_candidates)
+            .then(|| Propagation::new(&sudoku));
+        let clues = sudoku.clue_count();
+        let puzzle = sudoku.clone();
+        let size = sudoku.size();
+        let complete = sudoku.is_solved().then(|| {
+            sudoku.is_valid(None)
+                && candidates.is_none_or(|candidates| {
+                    Pos::all(size).all(|pos| {
+                        candidates[pos.index(size)] & (1 << sudoku.get_grid_value(pos)) != 0
+                    })
+                })
+        });
+
+        // A complete grid is concluded by `solve` without searching, so neither the exact cover
+        // matrix nor the probe is built for it
+        let (solver, unique_probe): (Box<dyn SudokuSolver>, _) = match complete {
+            Some(_) => (Box::new(DfsSolver::new(sudoku)), None),
+            None => Self::build(sudoku, solver_type, &options, candidates.as_ref(), template)?,
+        };
+
+        Ok(Self {
+            solver,
+            requested_solver_type,
+            solver_type,
+            options,
+            clues,
             metadata: Metadata::default(),
-        }
+            propagation,
+            puzzle,
+            solutions: Vec::new(),
+            unique_probe,
+            uniqueness: Uniqueness::Unknown,
+            complete,
+        })
     }
 
-    pub fn solve(&mut self) -> bool {
-        let cpu_time = Instant::now();
+    /// Builds the solver of the resolved `solver_type` and the second-solution probe if
+    /// `check_unique` is set, both restricted to the pencil marks of the `candidates` if any, or
+    /// else copied from the `template` if it fits the grid.
+    fn build(
+        sudoku: Sudoku,
+        solver_type: SolverType,
+        options: &SolveOptions,
+        candidates: Option<&[u16; 81]>,
+        template: Option<&MatrixTemplate>,
+    ) -> Result<(Box<dyn SudokuSolver>, Option<DlxSolver>), SudokuError> {
+        let dlx = |sudoku: Sudoku, max_solutions| match (candidates, template) {
+            (Some(candidates), _) => DlxSolver::with_candidates(sudoku, max_solutions, candidates),
+            (None, Some(template)) if template.fits(&sudoku) => {
+                DlxSolver::with_template(template, &sudoku, max_solutions)
+            }
+            (None, _) => DlxSolver::with_max_solutions(sudoku, max_solutions),
+        };
+        let unique_probe = options
+            .check_unique
+            .then(|| dlx(sudoku.clone(), 1))
+            .transpose()?;
+
+        let solver: Box<dyn SudokuSolver> = match solver_type {
+            SolverType::Dfs => Box::new(DfsSolver::with_options(
+                sudoku,
+                DfsOptions {
+                    max_nodes: options.node_limit,
+                    timeout: options.timeout_ms.map(Duration::from_millis),
+                    seed: options.seed,
+                    restarts: None,
+                    propagate_only: options.propagate_only,
+                    second_solution_budget: options
+                        .detect_multiple
+                        .then(|| options.node_limit.unwrap_or(UNIQUE_PROBE_NODE_BUDGET)),
+                },
+            )),
+            SolverType::Dlx | SolverType::Auto => {
+                let mut dlx = dlx(sudoku, options.max_solutions)?;
+                dlx.set_limits(
+                    options.node_limit,
+                    options.timeout_ms.map(Duration::from_millis),
+                );
+                dlx.set_parallel(options.parallel);
+                dlx.set_column_selection(options.column_selection);
+                Box::new(dlx)
+            }
+        };
+
+        Ok((solver, unique_probe))
+    }
+
+    /// Runs the assigned solver and verifies the results, distinguishing puzzles without a
+    /// solution (`Outcome::Unsolvable`) from solver failures (`Outcome::InternalError`). DLX
+    /// enumerates up to `max_solutions` distinct solutions, while DFS stops after the first one.
+    /// A grid without empty cells is concluded without running either of them.
+    pub fn solve(&mut self) -> Outcome {
+        if let Some(valid) = self.complete {
+            return self.solve_complete(valid);
+        }
+
+        let stopwatch = Stopwatch::start();
         let (res, visited_nodes) = self.solver.solve();
+        let (wall_time, cpu_time) = stopwatch.elapsed();
         self.metadata.visited_nodes = visited_nodes;
-        self.metadata.cpu_time_ms = cpu_time.elapsed().as_millis();
+        self.metadata.node_counts = self.solver.node_counts();
+        self.metadata.cpu_time = cpu_time;
+        self.metadata.wall_time = wall_time;
+        self.metadata.max_trail_len = self.solver.max_trail_len();
+        self.metadata.techniques = self.solver.techniques();
+        self.metadata.mac_prunings = self.solver.mac_prunings();
+        self.metadata.limit_reached = self.solver.limit_reached();
+        self.metadata.search_stats = self.solver.search_stats();
+        self.metadata.dlx_stats = self.solver.dlx_stats();
+
+        if self.solver.is_cancelled() {
+            return Outcome::Cancelled;
+        }
+
+        if let Some(limit) = self.metadata.limit_reached {
+            return Outcome::LimitReached(limit);
+        }
 
-        res
+        if self.solver.is_corrupted() {
+            return Outcome::InternalError;
+        }
+
+        if self.solver.is_incomplete() {
+            return Outcome::Incomplete;
+        }
+
+        if !res {
+            return Outcome::Unsolvable;
+        }
+
+        // The solutions must also keep the givens of the puzzle intact
+        self.solutions = self.solver.solutions();
+        let verified = self.solutions.iter().all(|solution| {
+            matches!(
+                Sudoku::new(solution.clone()),
+                Ok(sudoku) if sudoku.is_solution_of(&self.puzzle)
+            )
+        });
+
+        if !verified {
+            return Outcome::InternalError;
+        }
+
+        self.uniqueness = self.check_uniqueness();
+
+        Outcome::Solved
+    }
+
+    /// Concludes the solving of a grid without empty cells, which is its own unique solution if
+    /// it's `valid` and has none otherwise, without visiting any nodes.
+    fn solve_complete(&mut self, valid: bool) -> Outcome {
+        self.metadata = Metadata::default();
+
+        if !valid {
+            return Outcome::Unsolvable;
+        }
+
+        self.metadata.already_solved = true;
+        self.solutions = vec![self.puzzle.grid_to_string()];
+
+        if self.options.check_unique || self.options.detect_multiple {
+            self.uniqueness = Uniqueness::Unique;
+        }
+
+        Outcome::Solved
+    }
+
+    /// Determines the uniqueness of the solution if `check_unique` or `detect_multiple` is set,
+    /// which is already known if multiple solutions were enumerated or DFS detected it, and probed
+    /// for a second solution otherwise.
+    fn check_uniqueness(&mut self) -> Uniqueness {
+        if self.solutions.len() > 1 {
+            return Uniqueness::Multiple;
+        }
+
+        // Detected by DFS itself if `detect_multiple` is set, unless it ran out of its budget
+        if let detected @ (Uniqueness::Unique | Uniqueness::Multiple) = self.solver.uniqueness() {
+            return detected;
+        }
+
+        let budget = self.options.node_limit.unwrap_or(UNIQUE_PROBE_NODE_BUDGET);
+
+        Uniqueness::from_probe(
+            self.unique_probe
+                .as_mut()
+                .and_then(|probe| probe.is_unique(Some(budget))),
+        )
+    }
+
+    /// Returns the solver type used for solving, i.e. `SolverType::Auto` is already resolved.
+    pub fn solver_type(&self) -> SolverType {
+        self.solver_type
+    }
+
+    pub fn requested_solver_type(&self) -> SolverType {
+        self.requested_solver_type
+    }
+
+    pub fn options(&self) -> SolveOptions {
+        self.options
+    }
+
+    /// Sets the hook invoked every `interval` visited nodes during the search, see `ProgressHook`.
+    pub fn set_progress_hook(&mut self, hook: ProgressHook, interval: u64) {
+        self.solver
+            .set_progress_reporter(ProgressReporter::new(hook, interval));
+    }
+
+    /// Makes the search observe the shared cancellation `flag`, the solving process being aborted
+    /// with `Outcome::Cancelled` once it's set. Replaces the progress hook.
+    pub fn set_cancel_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.set_progress_hook(
+            Box::new(move |_| !flag.load(Ordering::Relaxed)),
+            CANCEL_CHECK_INTERVAL_NODES,
+        );
+    }
+
+    /// Returns the uniqueness of the solution, `Uniqueness::Unknown` unless `check_unique` or
+    /// `detect_multiple` is set.
+    pub fn uniqueness(&self) -> Uniqueness {
+        self.uniqueness
+    }
+
+    pub fn clues(&self) -> usize {
+        self.clues
+    }
+
+    /// Returns the metadata of the last `solve` call.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns whether the last `solve` call was `Outcome::Incomplete`.
+    pub fn is_incomplete(&self) -> bool {
+        self.solver.is_incomplete()
+    }
+
+    /// Returns the contradiction found by the propagation if the last `solve` call was
+    /// `Outcome::Unsolvable` without searching, only ever found by DFS.
+    pub fn contradiction(&self) -> Option<Contradiction> {
+        self.solver.contradiction()
+    }
+
+    /// Returns the assigned Sudoku as it was before solving.
+    pub fn puzzle(&self) -> &Sudoku {
+        &self.puzzle
+    }
+
+    /// Returns the state of the grid after the constraint propagation, only computed if either
+    /// of the `include_propagated` or `include_candidates` options is set.
+    pub fn propagation(&self) -> Option<&Propagation> {
+        self.propagation.as_ref()
     }
 
     /// Returns the total solving time if the assigned Sudoku is solved, otherwise returns `0u128`.
+    #[allow(dead_code)]
     pub fn total_cpu_time_ms(&self) -> u128 {
-        self.metadata.cpu_time_ms
+        self.metadata.cpu_time().as_millis()
+    }
+
+    /// Returns the total solving time in microseconds if the assigned Sudoku is solved, otherwise
+    /// returns `0u128`.
+    pub fn total_cpu_time_us(&self) -> u128 {
+        self.metadata.cpu_time().as_micros()
+    }
+
+    /// Returns the total wall-clock time of the solving process in microseconds.
+    pub fn total_wall_time_us(&self) -> u128 {
+        self.metadata.wall_time().as_micros()
     }
 
     /// Returns the total amount of nodes visited during the solver process.
@@ -71,7 +933,13 @@ impl Solver {
         self.metadata.visited_nodes
     }
 
-    /// Returns the inner grid converted into a 1D `String`. Supposed to only be used for testing.
+    /// Returns the distinct solutions found by the last `solve` call as 1D `String`s, the first
+    /// being the same as the inner grid.
+    pub fn solutions(&self) -> &[String] {
+        &self.solutions
+    }
+
+    /// Returns the inner grid converted into a 1D `String`.
     #[allow(dead_code)]
     pub fn grid_to_string(&self) -> String {
         self.solver.grid_to_string()
@@ -79,35 +947,802 @@ impl Solver {
 
     /// Returns the inner grid. Notably doesn't check whether the solving process has finished and
     /// might return unexpected results.
+    #[allow(dead_code)]
     pub fn get_inner_grid(&self) -> Vec<Vec<u8>> {
         self.solver.get_inner_grid()
     }
+
+    /// Returns the inner Sudoku with the same caveats as `get_inner_grid`.
+    pub fn get_sudoku(&self) -> &Sudoku {
+        self.solver.get_sudoku()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
+
     use super::*;
 
     const UNSOLVED_GRID: &str =
         "509003407001547893473910560057030684102860309836704105390076201010382040204000730";
     const SOLVED_GRID: &str =
         "589623417621547893473918562957231684142865379836794125398476251715382946264159738";
+    // Meets the default Sudoku constraints, but contains a contradiction
+    const UNSOLVABLE_GRID: &str =
+        "500020410620000000400010062007230604006000379030090005098006001015300940004100738";
+    // Deadly pattern of four cells, which can be filled in exactly two ways
+    const TWO_SOLUTIONS_GRID: &str =
+        "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+    // 16x16 grid with 4x4 squares, the values from 10 to 16 represented with `A` to `G`
+    const HEX_UNSOLVED_GRID: &str = "008A03041F05200DF050A0080CD2070BCG00E10500B0890A00400G0209A85F1009B03000F0000200070000000000A0F000EG1F5A703008960F00698B02GE0473G0C000100080000000000A000040700060058037E00FCGD40B780D000659F100000702E180030000B80074DG0A060E2C0560980320C1GD07000000A04D703000";
+    const HEX_SOLVED_GRID: &str = "968AB3741FE52CGDF15EA698GCD2473BCG2DE1F537B4896A734BDGC269A85F1E89B6374DF51AE2CG47D3GC2E986BA5F12CEG1F5A743DB8965FA1698BC2GED473GDC42E1FB38796A51EF25A69DG4C73B86A958B37E12FCGD43B784DGCA659F1E2D4G7C2E18B936A5FB83974DG5AF61E2CA56F98B32EC1GD47E21CF5A64D7G3B89";
 
     #[test]
     fn test_dfs() {
         let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
-        let mut solver = Solver::new(sudoku, "dfs");
+        let mut solver = Solver::new(sudoku, SolverType::Dfs).unwrap();
 
-        assert!(solver.solve());
+        assert_eq!(solver.solve(), Outcome::Solved);
         assert_eq!(solver.grid_to_string().as_str(), SOLVED_GRID);
     }
 
+    #[test]
+    fn test_dfs_small_grid() {
+        let sudoku = Sudoku::new(String::from("1030040220404003")).unwrap();
+        let mut solver = Solver::new(sudoku, SolverType::Auto).unwrap();
+
+        assert_eq!(solver.solver_type(), SolverType::Dfs);
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(solver.grid_to_string().as_str(), "1234341223414123");
+    }
+
+    #[test]
+    fn test_hex_grid() {
+        let sudoku = Sudoku::new(String::from(HEX_UNSOLVED_GRID)).unwrap();
+        // Auto isn't tuned for other than 9x9 grids
+        assert_eq!(SolverType::Auto.resolve(&sudoku), SolverType::Dfs);
+
+        for solver_type in [SolverType::Dfs, SolverType::Dlx, SolverType::Auto] {
+            let mut solver = Solver::new(sudoku.clone(), solver_type).unwrap();
+
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert_eq!(solver.grid_to_string().as_str(), HEX_SOLVED_GRID);
+        }
+    }
+
+    #[test]
+    fn test_trail_len() {
+        let sudoku = Sudoku::new(String::from(
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000",
+        ))
+        .unwrap();
+        let mut dfs = Solver::new(sudoku.clone(), SolverType::Dfs).unwrap();
+        let mut dlx = Solver::new(sudoku, SolverType::Dlx).unwrap();
+
+        assert_eq!(dfs.solve(), Outcome::Solved);
+        assert_eq!(dlx.solve(), Outcome::Solved);
+        // Every assignment records at least the remaining domain of the cell itself
+        assert!(dfs.metadata().max_trail_len() > 0);
+        assert_eq!(dlx.metadata().max_trail_len(), 0);
+    }
+
+    #[test]
+    fn test_hidden_singles() {
+        // Resolved by the propagation once the hidden singles are included
+        let sudoku = Sudoku::new(String::from(
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+        ))
+        .unwrap();
+        let mut dfs = Solver::new(sudoku.clone(), SolverType::Dfs).unwrap();
+        let mut dlx = Solver::new(sudoku, SolverType::Dlx).unwrap();
+
+        assert_eq!(dfs.solve(), Outcome::Solved);
+        assert_eq!(dfs.total_visited_nodes(), 0);
+        assert!(dfs.metadata().techniques().hidden_singles > 0);
+
+        assert_eq!(dlx.solve(), Outcome::Solved);
+        assert_eq!(dlx.metadata().techniques(), TechniqueCounts::default());
+        assert_eq!(dfs.grid_to_string(), dlx.grid_to_string());
+    }
+
     #[test]
     fn test_dlx() {
         let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
-        let mut solver = Solver::new(sudoku, "dlx");
+        let mut solver = Solver::new(sudoku, SolverType::Dlx).unwrap();
 
-        assert!(solver.solve());
+        assert_eq!(solver.solve(), Outcome::Solved);
         assert_eq!(solver.grid_to_string().as_str(), SOLVED_GRID);
     }
+
+    #[test]
+    fn test_dfs_dlx_equal() {
+        let puzzle = Sudoku::new(String::from(
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000",
+        ))
+        .unwrap();
+        let solved = [SolverType::Dfs, SolverType::Dlx].map(|solver_type| {
+            let mut solver = Solver::new(puzzle.clone(), solver_type).unwrap();
+            assert_eq!(solver.solve(), Outcome::Solved);
+            solver.get_sudoku().clone()
+        });
+
+        assert_eq!(solved[0], solved[1]);
+        assert!(solved[0].is_solution_of(&puzzle));
+
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut dfs = Solver::new(sudoku.clone(), SolverType::Dfs).unwrap();
+        let mut dlx = Solver::new(sudoku, SolverType::Dlx).unwrap();
+        dfs.solve();
+        dlx.solve();
+
+        assert_eq!(dfs.get_sudoku(), dlx.get_sudoku());
+        assert_eq!(dfs.get_sudoku(), &SOLVED_GRID.parse::<Sudoku>().unwrap());
+    }
+
+    #[test]
+    fn test_auto() {
+        // Heavily clued puzzle completed by the naked singles
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut solver = Solver::new(sudoku, SolverType::Auto).unwrap();
+
+        assert_eq!(solver.requested_solver_type(), SolverType::Auto);
+        assert_eq!(solver.solver_type(), SolverType::Dfs);
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(solver.grid_to_string().as_str(), SOLVED_GRID);
+
+        // Sparse 17-clue puzzle
+        let sudoku = Sudoku::new(String::from(
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000",
+        ))
+        .unwrap();
+        let mut solver = Solver::new(sudoku, SolverType::Auto).unwrap();
+
+        assert_eq!(solver.solver_type(), SolverType::Dlx);
+        assert_eq!(solver.solve(), Outcome::Solved);
+    }
+
+    #[test]
+    fn test_propagation() {
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let solver = Solver::new(sudoku, SolverType::Dlx).unwrap();
+        assert!(solver.propagation().is_none());
+
+        let options = SolveOptions {
+            include_candidates: true,
+            ..SolveOptions::default()
+        };
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let solver = Solver::with_options(sudoku, SolverType::Dlx, options).unwrap();
+        let propagation = solver.propagation().unwrap();
+        let solved = SOLVED_GRID.as_bytes();
+
+        // Resolved cells and the remaining candidates must agree with the unique solution
+        for (pos, value) in propagation.grid.rows().flatten() {
+            assert!(value == 0 || value == solved[pos.index(9)] - b'0');
+        }
+
+        for (pos, candidates) in propagation.candidates.iter() {
+            assert!(candidates.contains(&(solved[pos.index(9)] - b'0')));
+        }
+
+        // Too hard to be resolved by the propagation alone
+        let sudoku = Sudoku::new(String::from(
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000",
+        ))
+        .unwrap();
+        let solver = Solver::with_options(sudoku, SolverType::Dlx, options).unwrap();
+        let propagation = solver.propagation().unwrap();
+
+        assert!(!propagation.candidates.is_empty());
+        for (&pos, candidates) in propagation.candidates.iter() {
+            assert_eq!(propagation.grid.get_grid_value(pos), 0);
+            assert!(!candidates.is_empty());
+            assert!(candidates.iter().all(|c| (1..=9).contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_multiple_solutions() {
+        let options = SolveOptions {
+            max_solutions: 16,
+            ..SolveOptions::default()
+        };
+        let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+        let mut solver = Solver::with_options(sudoku, SolverType::Auto, options).unwrap();
+
+        assert_eq!(solver.solver_type(), SolverType::Dlx);
+        assert_eq!(solver.solve(), Outcome::Solved);
+
+        let solutions = solver.solutions();
+        assert_eq!(solutions.len(), 2);
+        assert_ne!(solutions[0], solutions[1]);
+        assert!(solutions.contains(&String::from(SOLVED_GRID)));
+        assert_eq!(solutions[0], solver.grid_to_string());
+
+        // Stops the enumeration once the maximum is reached
+        let options = SolveOptions {
+            max_solutions: 1,
+            ..SolveOptions::default()
+        };
+        let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+        let mut solver = Solver::with_options(sudoku, SolverType::Dlx, options).unwrap();
+
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(solver.solutions().len(), 1);
+    }
+
+    #[test]
+    fn test_with_template() {
+        let template = MatrixTemplate::new(9).unwrap();
+        let options = SolveOptions {
+            check_unique: true,
+            ..SolveOptions::default()
+        };
+
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut built = Solver::with_options(sudoku.clone(), SolverType::Dlx, options).unwrap();
+        let mut copied =
+            Solver::with_template(sudoku, SolverType::Dlx, options, None, Some(&template)).unwrap();
+
+        assert_eq!(copied.solve(), built.solve());
+        assert_eq!(copied.solutions(), built.solutions());
+        assert_eq!(copied.uniqueness(), Uniqueness::Unique);
+        assert_eq!(
+            copied.metadata().node_counts(),
+            built.metadata().node_counts()
+        );
+        // Every row of the template is appended, the pruned ones being removed afterwards
+        assert_eq!(copied.metadata().dlx_stats().unwrap().rows_appended, 729);
+        assert!(built.metadata().dlx_stats().unwrap().rows_appended < 729);
+
+        // Built from scratch for the grids the template doesn't fit
+        let sudoku = Sudoku::new(String::from(HEX_UNSOLVED_GRID)).unwrap();
+        let mut solver =
+            Solver::with_template(sudoku, SolverType::Dlx, options, None, Some(&template)).unwrap();
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(solver.solutions(), [HEX_SOLVED_GRID]);
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        assert_eq!(
+            DlxSolver::new(sudoku).unwrap().count_solutions(2, None),
+            Some(1)
+        );
+
+        let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+        assert_eq!(
+            DlxSolver::new(sudoku).unwrap().count_solutions(2, None),
+            Some(2)
+        );
+
+        let sudoku = Sudoku::new(String::from(UNSOLVABLE_GRID)).unwrap();
+        assert_eq!(
+            DlxSolver::new(sudoku).unwrap().count_solutions(2, None),
+            Some(0)
+        );
+
+        let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+        assert_eq!(
+            DlxSolver::new(sudoku).unwrap().count_solutions(2, Some(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_uniqueness() {
+        let options = SolveOptions {
+            check_unique: true,
+            ..SolveOptions::default()
+        };
+
+        for solver_type in [SolverType::Dfs, SolverType::Dlx] {
+            let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+            let mut solver = Solver::with_options(sudoku, solver_type, options).unwrap();
+            solver.solve();
+            assert_eq!(solver.uniqueness(), Uniqueness::Unique);
+
+            let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+            let mut solver = Solver::with_options(sudoku, solver_type, options).unwrap();
+            solver.solve();
+            assert_eq!(solver.uniqueness(), Uniqueness::Multiple);
+
+            // Unchecked by default
+            let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+            solver.solve();
+            assert_eq!(solver.uniqueness(), Uniqueness::Unknown);
+        }
+
+        // Probe running out of its node budget, which also limits the search of the solver
+        let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+        assert_eq!(
+            Uniqueness::probe(&sudoku, None, Some(1)),
+            Uniqueness::Unknown
+        );
+
+        // Neither unique nor multiple without any solution
+        let sudoku = Sudoku::new(String::from(UNSOLVABLE_GRID)).unwrap();
+        assert_eq!(Uniqueness::probe(&sudoku, None, None), Uniqueness::Unknown);
+    }
+
+    #[test]
+    fn test_detect_multiple() {
+        let options = SolveOptions {
+            detect_multiple: true,
+            ..SolveOptions::default()
+        };
+        let searched =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+
+        for (grid, uniqueness) in [
+            (searched, Uniqueness::Unique),
+            (TWO_SOLUTIONS_GRID, Uniqueness::Multiple),
+        ] {
+            let sudoku = Sudoku::new(String::from(grid)).unwrap();
+            let mut first = Solver::new(sudoku.clone(), SolverType::Dfs).unwrap();
+            assert_eq!(first.solve(), Outcome::Solved);
+            assert_eq!(first.uniqueness(), Uniqueness::Unknown);
+
+            // Keeps the first solution found, the search for the second one visiting more nodes
+            let mut solver = Solver::with_options(sudoku, SolverType::Dfs, options).unwrap();
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert_eq!(solver.uniqueness(), uniqueness);
+            assert_eq!(solver.grid_to_string(), first.grid_to_string());
+            assert_eq!(solver.solutions().len(), 1);
+            assert!(solver.metadata().visited_nodes() >= first.metadata().visited_nodes());
+        }
+
+        // Node limit spent before the second solution is ruled out
+        let options = SolveOptions {
+            node_limit: Some(200),
+            ..options
+        };
+        let sudoku = Sudoku::new(String::from(searched)).unwrap();
+        let mut solver = Solver::with_options(sudoku, SolverType::Dfs, options).unwrap();
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(solver.uniqueness(), Uniqueness::Unknown);
+        assert_eq!(solver.metadata().limit_reached(), None);
+    }
+
+    #[test]
+    fn test_parallel() {
+        let options = SolveOptions {
+            parallel: true,
+            ..SolveOptions::default()
+        };
+
+        // Same solution as the sequential search for the proper puzzles
+        for (grid, solved) in [
+            (UNSOLVED_GRID, SOLVED_GRID),
+            (HEX_UNSOLVED_GRID, HEX_SOLVED_GRID),
+        ] {
+            let sudoku = Sudoku::new(String::from(grid)).unwrap();
+            let mut solver = Solver::with_options(sudoku, SolverType::Dlx, options).unwrap();
+
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert_eq!(solver.grid_to_string(), solved);
+        }
+
+        let sudoku = Sudoku::new(String::from(TWO_SOLUTIONS_GRID)).unwrap();
+        let mut solver = Solver::with_options(sudoku, SolverType::Dlx, options).unwrap();
+        assert_eq!(solver.solve(), Outcome::Solved);
+
+        let sudoku = Sudoku::new(String::from(UNSOLVABLE_GRID)).unwrap();
+        let mut solver = Solver::with_options(sudoku, SolverType::Dlx, options).unwrap();
+        assert_eq!(solver.solve(), Outcome::Unsolvable);
+    }
+
+    #[test]
+    fn test_uniqueness_serialization() {
+        for (uniqueness, json) in [
+            (Uniqueness::Unique, "true"),
+            (Uniqueness::Multiple, "false"),
+            (Uniqueness::Unknown, "\"unknown\""),
+        ] {
+            assert_eq!(serde_json::to_string(&uniqueness).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<Uniqueness>(json).unwrap(),
+                uniqueness
+            );
+        }
+
+        assert!(serde_json::from_str::<Uniqueness>("\"maybe\"").is_err());
+    }
+
+    #[test]
+    fn test_node_counts() {
+        // Left to the search by the propagation of DFS, but solved without backtracking by DLX
+        let searched =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+        let expected = [
+            // Resolved by the propagation of DFS, and without backtracking by DLX
+            (UNSOLVED_GRID, SolverType::Dfs, (0, 0, 0)),
+            (UNSOLVED_GRID, SolverType::Dlx, (81, 96, 13284)),
+            (searched, SolverType::Dfs, (182, 873, 6127)),
+            (searched, SolverType::Dlx, (81, 318, 13284)),
+        ];
+
+        for (grid, solver_type, (decisions, propagations, scans)) in expected {
+            let sudoku = Sudoku::new(String::from(grid)).unwrap();
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert_eq!(
+                solver.metadata().node_counts(),
+                NodeCounts {
+                    decisions,
+                    propagations,
+                    scans,
+                },
+                "{:?}",
+                solver_type
+            );
+            assert_eq!(solver.total_visited_nodes(), decisions);
+        }
+    }
+
+    #[test]
+    fn test_dlx_stats() {
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut solver = Solver::new(sudoku.clone(), SolverType::Dlx).unwrap();
+
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(
+            solver.metadata().dlx_stats(),
+            Some(DlxStats {
+                covers: 324,
+                uncovers: 324,
+                rows_appended: 96,
+                // The head, the 324 column heads and 4 items for each of the rows
+                arena_nodes: 1 + 324 + 96 * 4,
+            })
+        );
+
+        let mut solver = Solver::new(sudoku, SolverType::Dfs).unwrap();
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert_eq!(solver.metadata().dlx_stats(), None);
+    }
+
+    #[test]
+    fn test_progress_hook() {
+        // Takes over a hundred nodes with either of the solvers
+        let grid =
+            "000000015020060000000000408003000900000100000000008000150400000000070300800000060";
+
+        for solver_type in [SolverType::Dfs, SolverType::Dlx] {
+            let reports = Arc::new(Mutex::new(Vec::new()));
+            let hook_reports = reports.clone();
+
+            let sudoku = Sudoku::new(String::from(grid)).unwrap();
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+            solver.set_progress_hook(
+                Box::new(move |progress| {
+                    hook_reports.lock().unwrap().push(progress);
+                    true
+                }),
+                50,
+            );
+
+            assert_eq!(solver.solve(), Outcome::Solved);
+
+            let reports = reports.lock().unwrap();
+            assert!(!reports.is_empty());
+            assert!(reports
+                .windows(2)
+                .all(|w| w[0].visited_nodes < w[1].visited_nodes));
+            assert!(reports
+                .iter()
+                .all(|p| p.visited_nodes <= solver.total_visited_nodes()));
+            assert!(reports.iter().all(|p| p.depth <= 81));
+        }
+    }
+
+    #[test]
+    fn test_progress_hook_cancel() {
+        let grid =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+
+        for solver_type in [SolverType::Dfs, SolverType::Dlx] {
+            let sudoku = Sudoku::new(String::from(grid)).unwrap();
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+            solver.set_progress_hook(Box::new(|_| false), 10);
+
+            assert_eq!(solver.solve(), Outcome::Cancelled);
+            assert!(solver.total_visited_nodes() < 100);
+        }
+    }
+
+    #[test]
+    fn test_cancel_flag() {
+        // Takes tens of thousands of nodes with DFS
+        let grid =
+            "000002300100500000007000000400000017030800000000000600006470000020000900000010000";
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let sudoku = Sudoku::new(String::from(grid)).unwrap();
+        let mut solver = Solver::new(sudoku, SolverType::Dfs).unwrap();
+        solver.set_cancel_flag(flag.clone());
+        flag.store(true, Ordering::Relaxed);
+
+        assert_eq!(solver.solve(), Outcome::Cancelled);
+        assert!(solver.total_visited_nodes() < 2 * CANCEL_CHECK_INTERVAL_NODES);
+
+        // Unaffected as long as the flag isn't set
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut solver = Solver::new(sudoku, SolverType::Dlx).unwrap();
+        solver.set_cancel_flag(Arc::new(AtomicBool::new(false)));
+
+        assert_eq!(solver.solve(), Outcome::Solved);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let grid =
+            "000002300100500000007000000400000017030800000000000600006470000020000900000010000";
+        let options = SolveOptions {
+            node_limit: Some(100),
+            ..Default::default()
+        };
+
+        let sudoku = Sudoku::new(String::from(grid)).unwrap();
+
+        for solver_type in [SolverType::Dfs, SolverType::Dlx] {
+            let mut solver = Solver::with_options(sudoku.clone(), solver_type, options).unwrap();
+
+            assert_eq!(solver.solve(), Outcome::LimitReached(SearchLimit::Nodes));
+            assert_eq!(solver.total_visited_nodes(), 100);
+            assert_eq!(solver.metadata().limit_reached(), Some(SearchLimit::Nodes));
+            assert!(!solver.get_sudoku().is_solved());
+
+            // Still solved within a generous limit
+            let options = SolveOptions {
+                node_limit: Some(1_000_000),
+                ..Default::default()
+            };
+            let mut solver = Solver::with_options(sudoku.clone(), solver_type, options).unwrap();
+
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert_eq!(solver.metadata().limit_reached(), None);
+        }
+    }
+
+    #[test]
+    fn test_timeout() {
+        let grid =
+            "000002300100500000007000000400000017030800000000000600006470000020000900000010000";
+        let options = SolveOptions {
+            timeout_ms: Some(1),
+            ..Default::default()
+        };
+
+        let sudoku = Sudoku::new(String::from(grid)).unwrap();
+        let mut solver = Solver::with_options(sudoku.clone(), SolverType::Dfs, options).unwrap();
+
+        assert_eq!(solver.solve(), Outcome::LimitReached(SearchLimit::Time));
+        assert!(solver.metadata().wall_time() < Duration::from_secs(1));
+        assert!(!solver.get_sudoku().is_solved());
+
+        let options = SolveOptions {
+            timeout_ms: Some(60_000),
+            ..Default::default()
+        };
+        let mut solver = Solver::with_options(sudoku, SolverType::Dfs, options).unwrap();
+
+        assert_eq!(solver.solve(), Outcome::Solved);
+    }
+
+    #[test]
+    fn test_propagate_only() {
+        let options = SolveOptions {
+            propagate_only: true,
+            ..Default::default()
+        };
+
+        // Fully resolved by the propagation, which only DFS has
+        let resolved =
+            "000000012008030000000000040120500000000004700060000000507000300000620000000100000";
+        let sudoku = Sudoku::new(String::from(resolved)).unwrap();
+        let mut solver = Solver::with_options(sudoku.clone(), SolverType::Dlx, options).unwrap();
+
+        assert_eq!(solver.solver_type(), SolverType::Dfs);
+        assert_eq!(solver.solve(), Outcome::Solved);
+        assert!(!solver.is_incomplete());
+        assert_eq!(solver.total_visited_nodes(), 0);
+        assert!(solver.get_sudoku().is_solution_of(&sudoku));
+
+        // Takes a search after the propagation, which fills in only some of the cells
+        let unresolved =
+            "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+        let sudoku = Sudoku::new(String::from(unresolved)).unwrap();
+        let mut solver = Solver::with_options(sudoku.clone(), SolverType::Dfs, options).unwrap();
+        let propagation = Propagation::new(&sudoku);
+
+        assert_eq!(solver.solve(), Outcome::Incomplete);
+        assert!(solver.is_incomplete());
+        assert_eq!(solver.total_visited_nodes(), 0);
+        assert!(!solver.get_sudoku().is_solved());
+        assert!(solver.get_sudoku().is_valid(None));
+        assert_eq!(solver.get_inner_grid(), propagation.grid.clone_grid());
+        assert!(solver.solutions().is_empty());
+
+        let mut full = Solver::new(sudoku, SolverType::Dfs).unwrap();
+        assert_eq!(full.solve(), Outcome::Solved);
+    }
+
+    /// Solver returning the solutions of DLX with the digits 1 and 2 swapped, i.e. valid grids
+    /// disagreeing with the givens.
+    struct RelabelingSolver(DlxSolver);
+
+    impl SudokuSolver for RelabelingSolver {
+        fn solve(&mut self) -> (bool, u64) {
+            self.0.solve()
+        }
+
+        fn get_inner_grid(&self) -> Vec<Vec<u8>> {
+            self.0.get_inner_grid()
+        }
+
+        fn get_sudoku(&self) -> &Sudoku {
+            self.0.get_sudoku()
+        }
+
+        fn grid_to_string(&self) -> String {
+            self.0.grid_to_string()
+        }
+
+        fn set_progress_reporter(&mut self, reporter: ProgressReporter) {
+            self.0.set_progress_reporter(reporter);
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.0.is_cancelled()
+        }
+
+        fn node_counts(&self) -> NodeCounts {
+            self.0.node_counts()
+        }
+
+        fn solutions(&self) -> Vec<String> {
+            self.0
+                .solutions()
+                .iter()
+                .map(|solution| {
+                    solution
+                        .chars()
+                        .map(|ch| match ch {
+                            '1' => '2',
+                            '2' => '1',
+                            ch => ch,
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_altered_given_internal_error() {
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut solver = Solver::new(sudoku.clone(), SolverType::Dlx).unwrap();
+        solver.solver = Box::new(RelabelingSolver(DlxSolver::new(sudoku).unwrap()));
+
+        assert_eq!(solver.solve(), Outcome::InternalError);
+    }
+
+    #[test]
+    fn test_corrupt_solution_internal_error() {
+        let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+        let mut solver = Solver::new(sudoku.clone(), SolverType::Dlx).unwrap();
+        let mut dlx = DlxSolver::new(sudoku).unwrap();
+        // Every row fills in a 1, so the extracted solution alters the givens
+        for (_, k) in dlx.subset_data_mut() {
+            *k = 0;
+        }
+        solver.solver = Box::new(dlx);
+
+        assert_eq!(solver.solve(), Outcome::InternalError);
+        assert!(solver.solutions.is_empty());
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        for solver_type in SolverType::VARIANTS {
+            let sudoku = Sudoku::new(String::from(UNSOLVABLE_GRID)).unwrap();
+            assert!(sudoku.is_valid(None));
+
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+            assert_eq!(solver.solve(), Outcome::Unsolvable);
+        }
+    }
+
+    #[test]
+    fn test_already_solved() {
+        for solver_type in SolverType::VARIANTS {
+            let sudoku = Sudoku::new(String::from(SOLVED_GRID)).unwrap();
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert!(solver.metadata().already_solved());
+            assert_eq!(solver.total_visited_nodes(), 0);
+            assert_eq!(solver.solutions(), [String::from(SOLVED_GRID)]);
+
+            // Neither the matrix of DLX nor the probe is built for a complete grid
+            let options = SolveOptions {
+                check_unique: true,
+                ..Default::default()
+            };
+            let sudoku = Sudoku::new(String::from(SOLVED_GRID)).unwrap();
+            let mut solver = Solver::with_options(sudoku, solver_type, options).unwrap();
+            assert!(solver.unique_probe.is_none());
+            assert_eq!(solver.solver.dlx_stats(), None);
+            assert_eq!(solver.solve(), Outcome::Solved);
+            assert_eq!(solver.uniqueness(), Uniqueness::Unique);
+
+            // Swapping the first two cells breaks the columns
+            let invalid = format!(
+                "{}{}{}",
+                &SOLVED_GRID[1..2],
+                &SOLVED_GRID[..1],
+                &SOLVED_GRID[2..]
+            );
+            let mut solver = Solver::new(Sudoku::new(invalid).unwrap(), solver_type).unwrap();
+            assert_eq!(solver.solve(), Outcome::Unsolvable);
+            assert!(!solver.metadata().already_solved());
+        }
+    }
+
+    #[test]
+    fn test_unsolvable_contradiction() {
+        // Both of the last two cells of the top row are down to 8, as columns 7 and 8 contain 9
+        let mut grid = format!("123456700{}", "0".repeat(72)).into_bytes();
+        grid[3 * 9 + 7] = b'9';
+        grid[6 * 9 + 8] = b'9';
+        let sudoku = Sudoku::new(String::from_utf8(grid).unwrap()).unwrap();
+        assert!(sudoku.is_valid(None));
+
+        let mut dfs = Solver::new(sudoku.clone(), SolverType::Dfs).unwrap();
+        assert_eq!(dfs.solve(), Outcome::Unsolvable);
+        assert_eq!(dfs.total_visited_nodes(), 0);
+        assert!(dfs.contradiction().is_some());
+
+        // DLX has no propagation phase and searches for the same answer
+        let mut dlx = Solver::new(sudoku, SolverType::Dlx).unwrap();
+        assert_eq!(dlx.solve(), Outcome::Unsolvable);
+        assert_eq!(dlx.contradiction(), None);
+
+        let mut solved = Solver::new(
+            Sudoku::new(String::from(UNSOLVED_GRID)).unwrap(),
+            SolverType::Dfs,
+        )
+        .unwrap();
+        assert_eq!(solved.solve(), Outcome::Solved);
+        assert_eq!(solved.contradiction(), None);
+    }
+
+    #[test]
+    fn test_cpu_time_within_wall_time() {
+        // Tolerance for the differing granularities of the two clocks
+        const TOLERANCE_US: u128 = 1000;
+
+        for solver_type in SolverType::VARIANTS {
+            let sudoku = Sudoku::new(String::from(UNSOLVED_GRID)).unwrap();
+            let mut solver = Solver::new(sudoku, solver_type).unwrap();
+            solver.solve();
+
+            assert!(solver.total_cpu_time_us() <= solver.total_wall_time_us() + TOLERANCE_US);
+        }
+    }
+
+    #[test]
+    fn test_solver_type_from_str() {
+        assert_eq!("dfs".parse::<SolverType>(), Ok(SolverType::Dfs));
+        assert_eq!("DLX".parse::<SolverType>(), Ok(SolverType::Dlx));
+        assert_eq!("Dfs".parse::<SolverType>(), Ok(SolverType::Dfs));
+
+        let e = "dsf".parse::<SolverType>().unwrap_err();
+        assert!(e.contains("'dfs'") && e.contains("'dlx'") && e.contains("'auto'"));
+        assert_eq!("Auto".parse::<SolverType>(), Ok(SolverType::Auto));
+    }
 }
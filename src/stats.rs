@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::sudoku::{Pos, Sudoku};
+
+/// Structural statistics of a puzzle for curating datasets, as returned by `Sudoku::statistics`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleStats {
+    /// Number of given (non-empty) cells.
+    pub clues: usize,
+    /// Number of clues on each row from top to bottom.
+    pub clues_per_row: Vec<usize>,
+    /// Number of clues on each column from left to right.
+    pub clues_per_column: Vec<usize>,
+    /// Number of clues in each square in row-major order.
+    pub clues_per_box: Vec<usize>,
+    /// Number of empty cells with a single legal digit given the clues.
+    pub naked_singles: usize,
+    /// Number of empty cells with exactly two legal digits given the clues.
+    pub bivalue_cells: usize,
+    /// Whether the clue pattern is preserved by a half turn of the grid.
+    pub rotationally_symmetric: bool,
+}
+
+impl PuzzleStats {
+    /// Computes the statistics of the grid in a single pass over its cells, the candidates of the
+    /// empty cells being derived from `Sudoku::candidates_for`.
+    pub fn of(sudoku: &Sudoku) -> Self {
+        let size = sudoku.size();
+        let dim_sqr = size.isqrt();
+        let mut stats = Self {
+            clues: 0,
+            clues_per_row: vec![0; size],
+            clues_per_column: vec![0; size],
+            clues_per_box: vec![0; size],
+            naked_singles: 0,
+            bivalue_cells: 0,
+            rotationally_symmetric: true,
+        };
+
+        for pos in Pos::all(size) {
+            let is_clue = sudoku.get_grid_value(pos) != 0;
+            let mirrored = Pos::from_row_col(size - 1 - pos.row(), size - 1 - pos.col());
+            stats.rotationally_symmetric &= is_clue == (sudoku.get_grid_value(mirrored) != 0);
+
+            if is_clue {
+                stats.clues += 1;
+                stats.clues_per_row[pos.row()] += 1;
+                stats.clues_per_column[pos.col()] += 1;
+                stats.clues_per_box[pos.box_index(dim_sqr)] += 1;
+                continue;
+            }
+
+            match sudoku.candidates_for(pos).count_ones() {
+                1 => stats.naked_singles += 1,
+                2 => stats.bivalue_cells += 1,
+                _ => {}
+            }
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_puzzle() {
+        let sudoku =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+                .parse::<Sudoku>()
+                .unwrap();
+
+        assert_eq!(
+            PuzzleStats::of(&sudoku),
+            PuzzleStats {
+                clues: 30,
+                clues_per_row: vec![3, 4, 3, 3, 4, 3, 3, 4, 3],
+                clues_per_column: vec![5, 3, 1, 3, 6, 3, 1, 3, 5],
+                clues_per_box: vec![5, 4, 1, 3, 4, 3, 1, 4, 5],
+                naked_singles: 4,
+                bivalue_cells: 13,
+                rotationally_symmetric: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_asymmetric_puzzle() {
+        let sudoku =
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let stats = sudoku.statistics();
+
+        assert_eq!(stats.clues, 17);
+        assert_eq!(stats.clues_per_box.iter().sum::<usize>(), 17);
+        assert!(!stats.rotationally_symmetric);
+        assert!(stats.naked_singles + stats.bivalue_cells <= 81 - 17);
+
+        let empty = "0".repeat(16).parse::<Sudoku>().unwrap();
+        assert!(empty.statistics().rotationally_symmetric);
+    }
+}
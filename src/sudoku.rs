@@ -1,135 +1,2791 @@
-use std::{collections::HashSet, error::Error, hash::Hash};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::OnceLock,
+};
 
-use serde::{Deserialize, Serialize};
+use rand::{seq::SliceRandom, Rng};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::{constraint, dlx::ExactCoverError, stats::PuzzleStats};
+
+/// Units of the grid in which every digit must be unique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Row,
+    Column,
+    Square,
+    /// Main diagonal (index 0) or anti-diagonal (index 1) of an X-Sudoku.
+    Diagonal,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row => write!(f, "row"),
+            Unit::Column => write!(f, "column"),
+            Unit::Square => write!(f, "square"),
+            Unit::Diagonal => write!(f, "diagonal"),
+        }
+    }
+}
+
+/// Cell of the grid as its position and value.
+pub type Cell = (Pos, u8);
+
+/// Position of a cell as its row and column, both indexed from 0. Ordered row-major, and
+/// serialized as a `[row, column]` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "(usize, usize)", into = "(usize, usize)")]
+pub struct Pos {
+    row: usize,
+    col: usize,
+}
+
+impl Pos {
+    pub const fn from_row_col(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    /// Returns the position of the `k`th cell of a grid with `size` rows in row-major order.
+    pub const fn from_index(k: usize, size: usize) -> Self {
+        Self::from_row_col(k / size, k % size)
+    }
+
+    /// Returns the position of the `k`th cell in row-major order of the `index`th square of a grid
+    /// with squares of `dim_sqr` rows, the inverse of `box_index`.
+    pub const fn from_box(index: usize, k: usize, dim_sqr: usize) -> Self {
+        Self::from_row_col(
+            (index / dim_sqr) * dim_sqr + k / dim_sqr,
+            (index % dim_sqr) * dim_sqr + k % dim_sqr,
+        )
+    }
+
+    pub const fn row(self) -> usize {
+        self.row
+    }
+
+    pub const fn col(self) -> usize {
+        self.col
+    }
+
+    /// Returns the row-major index of the cell in a grid with `size` rows.
+    pub const fn index(self, size: usize) -> usize {
+        self.row * size + self.col
+    }
+
+    /// Returns the row-major index of the square containing the cell in a grid with squares of
+    /// `dim_sqr` rows, e.g. 3 for 9x9 grids.
+    pub const fn box_index(self, dim_sqr: usize) -> usize {
+        (self.row / dim_sqr) * dim_sqr + self.col / dim_sqr
+    }
+
+    /// Iterates over every position of a grid with `size` rows in row-major order.
+    pub fn all(size: usize) -> impl Iterator<Item = Pos> {
+        (0..size * size).map(move |k| Self::from_index(k, size))
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self::from_row_col(row, col)
+    }
+}
+
+impl From<Pos> for (usize, usize) {
+    fn from(pos: Pos) -> Self {
+        (pos.row, pos.col)
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+const UNITS: [Unit; 3] = [Unit::Row, Unit::Column, Unit::Square];
+
+/// Rules of the puzzle on top of the rows, columns, and squares.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    #[default]
+    Classic,
+    /// X-Sudoku, in which both of the main diagonals contain every digit exactly once as well.
+    /// Only encoded by the DLX solver.
+    X,
+}
+
+/// Symmetry of the grid preserving the validity of the puzzle, which can be recorded and then
+/// replayed with `Sudoku::transform_all` or undone with `inverse_of`. The bands and stacks are the
+/// groups of rows and columns sharing the same squares, e.g. 3 of 3 rows or columns for 9x9 grids,
+/// and are indexed along with the rows and columns within them from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transformation {
+    /// Reflects the grid over its main diagonal, swapping the rows with the columns.
+    Transpose,
+    /// Rotates the grid a quarter turn clockwise.
+    Rotate90,
+    /// Rotates the grid a quarter turn counterclockwise, the inverse of `Rotate90`.
+    Rotate270,
+    /// Reflects the grid over its vertical axis, reversing the order of the columns.
+    MirrorHorizontal,
+    SwapRowsWithinBand {
+        band: usize,
+        a: usize,
+        b: usize,
+    },
+    SwapBands {
+        a: usize,
+        b: usize,
+    },
+    SwapColsWithinStack {
+        stack: usize,
+        a: usize,
+        b: usize,
+    },
+    SwapStacks {
+        a: usize,
+        b: usize,
+    },
+}
+
+#[allow(dead_code)]
+impl Transformation {
+    /// Returns the transformation undoing this one, every transformation other than the
+    /// rotations being its own inverse.
+    pub fn inverse(self) -> Self {
+        match self {
+            Transformation::Rotate90 => Transformation::Rotate270,
+            Transformation::Rotate270 => Transformation::Rotate90,
+            transformation => transformation,
+        }
+    }
+
+    /// Returns the sequence undoing the whole `sequence`, i.e. the inverses in reverse order.
+    pub fn inverse_of(sequence: &[Self]) -> Vec<Self> {
+        sequence.iter().rev().map(|t| t.inverse()).collect()
+    }
+
+    /// Checks that the bands, stacks, and the rows or columns within them exist in a grid with
+    /// squares of `dim_sqr` rows.
+    fn is_within(self, dim_sqr: usize) -> bool {
+        let within = |indices: &[usize]| indices.iter().all(|&k| k < dim_sqr);
+
+        match self {
+            Transformation::Transpose
+            | Transformation::Rotate90
+            | Transformation::Rotate270
+            | Transformation::MirrorHorizontal => true,
+            Transformation::SwapRowsWithinBand { band: x, a, b }
+            | Transformation::SwapColsWithinStack { stack: x, a, b } => within(&[x, a, b]),
+            Transformation::SwapBands { a, b } | Transformation::SwapStacks { a, b } => {
+                within(&[a, b])
+            }
+        }
+    }
+
+    /// Returns the position of the cell moved to `pos` by the transformation of a grid with
+    /// `size` rows and squares of `dim_sqr` rows.
+    fn source(self, pos: Pos, size: usize, dim_sqr: usize) -> Pos {
+        let (i, j) = (pos.row(), pos.col());
+        let swap = |k: usize, a: usize, b: usize| match k {
+            _ if k == a => b,
+            _ if k == b => a,
+            _ => k,
+        };
+        let within_group = |k: usize, group: usize, a: usize, b: usize| match k / dim_sqr {
+            g if g == group => g * dim_sqr + swap(k % dim_sqr, a, b),
+            _ => k,
+        };
+        let groups = |k: usize, a: usize, b: usize| swap(k / dim_sqr, a, b) * dim_sqr + k % dim_sqr;
+
+        let (row, col) = match self {
+            Transformation::Transpose => (j, i),
+            Transformation::Rotate90 => (size - 1 - j, i),
+            Transformation::Rotate270 => (j, size - 1 - i),
+            Transformation::MirrorHorizontal => (i, size - 1 - j),
+            Transformation::SwapRowsWithinBand { band, a, b } => (within_group(i, band, a, b), j),
+            Transformation::SwapBands { a, b } => (groups(i, a, b), j),
+            Transformation::SwapColsWithinStack { stack, a, b } => {
+                (i, within_group(j, stack, a, b))
+            }
+            Transformation::SwapStacks { a, b } => (i, groups(j, a, b)),
+        };
+
+        Pos::from_row_col(row, col)
+    }
+}
+
+/// Options of `Sudoku::to_string_with`, the defaults matching `grid_to_string`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Character of the empty cells, only `0`, `.`, and `-` can be parsed back with `FromStr`.
+    pub empty: char,
+    /// Separator inserted between the rows, all of them on the same line if `None`.
+    pub row_separator: Option<char>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            empty: '0',
+            row_separator: None,
+        }
+    }
+}
+
+/// Formats of the grid strings selectable by the API clients, each of them parsable back with
+/// `FromStr`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// Single line with the empty cells as `0`, same as `grid_to_string`.
+    #[default]
+    FlatZero,
+    /// Single line with the empty cells as `.`, as used by e.g. the SDM files.
+    FlatDot,
+    /// Every row on its own line with the empty cells as `.`.
+    Multiline,
+}
+
+impl OutputFormat {
+    pub fn options(self) -> FormatOptions {
+        match self {
+            OutputFormat::FlatZero => FormatOptions::default(),
+            OutputFormat::FlatDot => FormatOptions {
+                empty: '.',
+                row_separator: None,
+            },
+            OutputFormat::Multiline => FormatOptions {
+                empty: '.',
+                row_separator: Some('\n'),
+            },
+        }
+    }
+}
+
+/// Cell differing between two grids of the same size, as listed by `Sudoku::diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellDiff {
+    pub pos: Pos,
+    /// Value of the cell in the grid compared from, `0` if empty.
+    pub from: u8,
+    /// Value of the cell in the grid compared to, `0` if empty.
+    pub to: u8,
+}
+
+/// Digit repeated within a single unit of the grid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conflict {
+    pub unit: Unit,
+    /// Index of the unit, the squares indexed in row-major order.
+    pub index: usize,
+    pub value: u8,
+    /// Positions of the cells containing the repeated digit.
+    pub cells: Vec<Pos>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells = self
+            .cells
+            .iter()
+            .map(Pos::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(
+            f,
+            "value {} repeated in {} {} at {}",
+            self.value, self.unit, self.index, cells
+        )
+    }
+}
+
+/// Errors of constructing, validating, and solving a `Sudoku` from malformed input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SudokuError {
+    /// The number of cells in the input isn't the square of a supported size, e.g. 81 for 9x9.
+    WrongCellCount { cells: usize },
+    /// The input string contains a character that isn't a digit, a blank, or a separator.
+    InvalidCharacter { index: usize, ch: char },
+    /// The number of rows in the input grid isn't a supported size, i.e. 4, 9, 16, or 25.
+    WrongRowCount { rows: usize },
+    /// The row of the input grid doesn't consist of as many cells as there are rows.
+    WrongRowLength {
+        row: usize,
+        cells: usize,
+        expected: usize,
+    },
+    /// The cell of the input grid contains a value outside of the range 0-`max`.
+    ValueOutOfRange {
+        row: usize,
+        col: usize,
+        value: u8,
+        max: u8,
+    },
+    /// The grid contains digits repeated within its units, contains every conflict found.
+    ConstraintViolation(Vec<Conflict>),
+    /// The move targets a position outside of the grid.
+    #[allow(dead_code)]
+    PositionOutOfBounds { row: usize, col: usize },
+    /// The move sets a value that isn't a digit between 1-`max`.
+    #[allow(dead_code)]
+    InvalidDigit { value: u8, max: u8 },
+    /// The move targets a cell given in the original puzzle.
+    #[allow(dead_code)]
+    GivenCell { row: usize, col: usize },
+    /// The relabeling isn't a permutation of the digits 1-`max`.
+    #[allow(dead_code)]
+    InvalidPermutation { perm: Vec<u8>, max: u8 },
+    /// The grid isn't of the size of the data combined with it, e.g. the pencil marks of a 9x9
+    /// grid or a matrix template built for another size.
+    SizeMismatch { size: usize, expected: usize },
+    /// The grid is of a variant the operation doesn't support.
+    UnsupportedVariant(Variant),
+    /// The DLX matrix of the grid can't be built.
+    Matrix(ExactCoverError),
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SudokuError::WrongCellCount { cells } => write!(
+                f,
+                "Malformed input string that contains {} cells instead of {}",
+                cells,
+                supported_sizes_text(|size| size * size)
+            ),
+            SudokuError::InvalidCharacter { index, ch } => write!(
+                f,
+                "Malformed input string that contains an invalid character '{}' at index {}",
+                ch, index
+            ),
+            SudokuError::WrongRowCount { rows } => write!(
+                f,
+                "Malformed input grid that contains {} rows instead of {}",
+                rows,
+                supported_sizes_text(|size| size)
+            ),
+            SudokuError::WrongRowLength {
+                row,
+                cells,
+                expected,
+            } => write!(
+                f,
+                "Malformed input grid that contains {} cells instead of {} on row {}",
+                cells, expected, row
+            ),
+            SudokuError::ValueOutOfRange {
+                row,
+                col,
+                value,
+                max,
+            } => write!(
+                f,
+                "Malformed input grid that contains the value {} outside of the range 0-{} at row {}, column {}",
+                value, max, row, col
+            ),
+            SudokuError::ConstraintViolation(conflicts) => {
+                write!(f, "Default Sudoku constraints not met")?;
+
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 { "," } else { ";" }, conflict)?;
+                }
+
+                Ok(())
+            }
+            SudokuError::PositionOutOfBounds { row, col } => write!(
+                f,
+                "Position at row {}, column {} is outside of the grid",
+                row, col
+            ),
+            SudokuError::InvalidDigit { value, max } => {
+                write!(f, "Value {} is not a digit between 1-{}", value, max)
+            }
+            SudokuError::GivenCell { row, col } => write!(
+                f,
+                "Cell at row {}, column {} is given in the original puzzle",
+                row, col
+            ),
+            SudokuError::InvalidPermutation { perm, max } => write!(
+                f,
+                "Relabeling {:?} is not a permutation of the digits 1-{}",
+                perm, max
+            ),
+            SudokuError::SizeMismatch { size, expected } => write!(
+                f,
+                "Grid of size {}x{} where a {}x{} one is expected",
+                size, size, expected, expected
+            ),
+            SudokuError::UnsupportedVariant(variant) => {
+                write!(f, "Sudoku variant {:?} is not supported", variant)
+            }
+            SudokuError::Matrix(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for SudokuError {}
+
+impl From<ExactCoverError> for SudokuError {
+    fn from(e: ExactCoverError) -> Self {
+        SudokuError::Matrix(e)
+    }
+}
+
+/// Occurrences of the digits within the units of a grid, the rows first, then the columns and
+/// the squares, kept in sync with the cells by every mutation of `Sudoku` so the legal digits of
+/// a cell can be looked up without scanning its peers.
+#[derive(Debug, Clone)]
+struct UnitTally {
+    size: usize,
+    dim_sqr: usize,
+    /// Number of occurrences of the digit `d` in the unit `slot` at `slot * (size + 1) + d`.
+    counts: Vec<u8>,
+    /// Digits present in each unit as a bitmask, bit `d` being set for the digit `d`.
+    masks: Vec<u32>,
+}
+
+impl UnitTally {
+    fn new(cells: &[u8], size: usize) -> Self {
+        let mut tally = Self {
+            size,
+            dim_sqr: size.isqrt(),
+            counts: vec![0; 3 * size * (size + 1)],
+            masks: vec![0; 3 * size],
+        };
+
+        for (k, &value) in cells.iter().enumerate() {
+            tally.add(Pos::from_index(k, size), value);
+        }
+
+        tally
+    }
+
+    /// Returns the indices of the row, column, and square of the cell at `pos`.
+    fn slots(&self, pos: Pos) -> [usize; 3] {
+        [
+            pos.row(),
+            self.size + pos.col(),
+            2 * self.size + pos.box_index(self.dim_sqr),
+        ]
+    }
+
+    fn add(&mut self, pos: Pos, value: u8) {
+        if value == 0 {
+            return;
+        }
+
+        for slot in self.slots(pos) {
+            self.counts[slot * (self.size + 1) + value as usize] += 1;
+            self.masks[slot] |= 1 << value;
+        }
+    }
+
+    fn remove(&mut self, pos: Pos, value: u8) {
+        if value == 0 {
+            return;
+        }
+
+        for slot in self.slots(pos) {
+            let count = &mut self.counts[slot * (self.size + 1) + value as usize];
+            *count -= 1;
+
+            if *count == 0 {
+                self.masks[slot] &= !(1 << value);
+            }
+        }
+    }
+
+    /// Returns the digits present on the row, column, or square of the cell at `pos` as a
+    /// bitmask, including the value of the cell itself.
+    fn seen(&self, pos: Pos) -> u32 {
+        self.slots(pos)
+            .into_iter()
+            .fold(0, |seen, slot| seen | self.masks[slot])
+    }
+
+    /// Checks whether none of the units of the cell at `pos` contains `value` more than once.
+    fn is_single(&self, pos: Pos, value: u8) -> bool {
+        self.slots(pos)
+            .into_iter()
+            .all(|slot| self.counts[slot * (self.size + 1) + value as usize] <= 1)
+    }
+}
+
+/// Sudoku grid of any supported size, serialized as its canonical 1D string (see
+/// `grid_to_string`), or as a 2D array of values with `#[serde(with = "sudoku::nested")]`.
+#[derive(Debug, Clone)]
 pub struct Sudoku {
-    grid: Vec<Vec<u8>>,
+    /// Row-major values of the cells, `0` for the empty ones.
+    cells: Vec<u8>,
     size: usize,
     dim_sqr: usize,
+    /// Row-major mask of the cells given in the original puzzle.
+    givens: Vec<bool>,
+    tally: UnitTally,
+    variant: Variant,
 }
 
 impl Sudoku {
-    /// Constructs a new struct by parsing the 1D string of the Sudoku grid.
-    pub fn new(raw: String) -> Result<Self, Box<dyn Error>> {
-        let grid = raw
-            .chars()
-            .map(|ch| ch.to_digit(10).unwrap() as u8) // Validated beforehand to match the radix
-            .collect::<Vec<u8>>()
-            .chunks(9)
-            .map(|chunk| chunk.to_vec())
-            .collect::<Vec<Vec<u8>>>();
-        let size = grid.len();
-        let dim_sqr = grid.len() / 3;
-
-        // This shouldn't happen anyway due to the constraints being checked on request level
-        if size != 9 && dim_sqr != 3 {
-            return Err(
-                "Malformed input string that does not match Sudoku's default size constraints"
-                    .into(),
-            );
+    /// Constructs a new struct by parsing the string of the Sudoku grid in any of the formats
+    /// accepted by the `FromStr` implementation.
+    pub fn new(raw: String) -> Result<Self, SudokuError> {
+        raw.parse()
+    }
+
+    /// Constructs a new struct from a 2D grid, validating that it consists of `n²` rows of `n²`
+    /// cells (e.g. 9 rows of 9 cells) with values between `0` (empty) and the number of rows.
+    pub fn from_grid(grid: Vec<Vec<u8>>) -> Result<Self, SudokuError> {
+        if grid_size(grid.len() * grid.len()).is_none() {
+            return Err(SudokuError::WrongRowCount { rows: grid.len() });
+        }
+
+        for (i, row) in grid.iter().enumerate() {
+            if row.len() != grid.len() {
+                return Err(SudokuError::WrongRowLength {
+                    row: i,
+                    cells: row.len(),
+                    expected: grid.len(),
+                });
+            }
+        }
+
+        Self::from_cells(grid.into_iter().flatten().collect())
+    }
+
+    /// Validation core shared by all of the constructors, checks that the number of row-major 1D
+    /// `cells` is the square of a supported size, and that the values are between `0` (empty) and
+    /// the size of the grid.
+    fn from_cells(cells: Vec<u8>) -> Result<Self, SudokuError> {
+        let size =
+            grid_size(cells.len()).ok_or(SudokuError::WrongCellCount { cells: cells.len() })?;
+        let max = size as u8;
+
+        if let Some((k, &value)) = cells.iter().enumerate().find(|(_, &value)| value > max) {
+            return Err(SudokuError::ValueOutOfRange {
+                row: k / size,
+                col: k % size,
+                value,
+                max,
+            });
         }
 
         Ok(Self {
-            grid,
+            givens: cells.iter().map(|&value| value != 0).collect(),
+            tally: UnitTally::new(&cells, size),
+            cells,
             size,
-            dim_sqr,
+            dim_sqr: size.isqrt(),
+            variant: Variant::Classic,
         })
     }
 
+    /// Sets the rules of the puzzle, `Variant::Classic` by default, which the validity checks
+    /// and the DLX solver follow.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Returns the number of rows (and columns) of the grid.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Returns a copy of the grid as a 2D vector of rows, kept for the consumers of the original
+    /// representation. Prefer `as_flat` where the row-major cells suffice.
     pub fn clone_grid(&self) -> Vec<Vec<u8>> {
-        self.grid.clone()
+        self.cells
+            .chunks(self.size)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    /// Returns the row-major values of the cells without copying, `0` for the empty ones.
+    pub fn as_flat(&self) -> &[u8] {
+        &self.cells
+    }
+
+    /// Returns the row-major index of the cell at `pos`.
+    fn index(&self, pos: Pos) -> usize {
+        pos.index(self.size)
+    }
+
+    /// Returns the number of given (non-empty) cells in the grid.
+    pub fn clue_count(&self) -> usize {
+        self.rows()
+            .flatten()
+            .filter(|&(_, value)| value != 0)
+            .count()
+    }
+
+    /// Iterates over the positions of the empty cells in row-major order.
+    pub fn empty_positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.rows()
+            .flatten()
+            .filter(|&(_, value)| value == 0)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Iterates over the rows of the grid, each yielding its cells as `(position, value)`.
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        self.units(Unit::Row)
+    }
+
+    /// Iterates over the columns of the grid, each yielding its cells as `(position, value)`.
+    #[allow(dead_code)]
+    pub fn cols(&self) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        self.units(Unit::Column)
+    }
+
+    /// Iterates over the 3x3 squares of the grid in row-major order, each yielding its cells in
+    /// row-major order as `(position, value)`.
+    #[allow(dead_code)]
+    pub fn boxes(&self) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        self.units(Unit::Square)
+    }
+
+    fn units(&self, unit: Unit) -> impl Iterator<Item = impl Iterator<Item = Cell> + '_> + '_ {
+        (0..self.size).map(move |index| self.unit_values(unit, index))
+    }
+
+    /// Iterates over the cells of the `index`th unit of the grid as `(position, value)`.
+    pub(crate) fn unit_values(&self, unit: Unit, index: usize) -> impl Iterator<Item = Cell> + '_ {
+        self.unit_cells(unit, index)
+            .map(|pos| (pos, self.cells[self.index(pos)]))
+    }
+
+    /// Renders the grid in the pretty format of the `Display` implementation, which can be parsed
+    /// back with `FromStr`.
+    pub fn to_pretty_string(&self) -> String {
+        self.to_string()
     }
 
-    /// Converts the inner `Vec<Vec<u8>>` representation of the grid into 1D `String`.
+    /// Converts the grid into its 1D `String` representation, the values above 9 being represented
+    /// with the letters starting from `A` (10), e.g. up to `G` (16) for 16x16 grids.
     pub fn grid_to_string(&self) -> String {
-        self.grid
-            .iter()
-            .flat_map(|row| row.iter())
-            .map(|&num| num.to_string())
-            .collect()
+        self.to_string_with(FormatOptions::default())
+    }
+
+    /// Converts the grid into a `String` with the empty cell character and the row separator of
+    /// the `options`, the values being represented the same as in `grid_to_string`.
+    pub fn to_string_with(&self, options: FormatOptions) -> String {
+        let mut s = String::with_capacity(self.cells.len() + self.size);
+
+        for (i, row) in self.cells.chunks(self.size).enumerate() {
+            if let Some(separator) = options.row_separator.filter(|_| i > 0) {
+                s.push(separator);
+            }
+
+            s.extend(row.iter().map(|&value| match value {
+                0 => options.empty,
+                _ => value_to_char(value),
+            }));
+        }
+
+        s
     }
 
     /// Wrapper for replacing the partially solved grid with a full solution (given in a 1D vector
-    /// format).
+    /// format). Overwriting a given cell with a different value is a bug of the caller, caught by
+    /// a debug assertion, see `try_set_solution` for the checked version.
     pub fn set_solution(&mut self, solution: &[u8]) {
-        for (i, value) in solution.iter().enumerate() {
-            self.set_grid_value((i / 9, i % 9), *value);
-        }
+        debug_assert!(
+            self.altered_given(solution).is_none(),
+            "Solution overwrites a given cell"
+        );
+        self.write_cells(solution);
     }
 
     /// Wrapper for setting a new value to a grid cell. Required as a workaround for struggling
-    /// with the borrow checker.
-    pub fn set_grid_value(&mut self, pos: (usize, usize), value: u8) {
-        let (i, j) = pos;
-        self.grid[i][j] = value;
+    /// with the borrow checker. Overwriting a given cell with a different value is a bug of the
+    /// caller, caught by a debug assertion, see `try_set_grid_value` for the checked version.
+    pub fn set_grid_value(&mut self, pos: Pos, value: u8) {
+        let k = self.index(pos);
+        debug_assert!(
+            !self.givens[k] || self.cells[k] == value,
+            "Overwriting the given cell at {}",
+            pos
+        );
+        self.write_cell(k, value);
+    }
+
+    /// Strict version of `set_solution`, returning `SudokuError::GivenCell` for the first given
+    /// cell the solution would overwrite with a different value, in which case the grid is left
+    /// unchanged.
+    #[allow(dead_code)]
+    pub fn try_set_solution(&mut self, solution: &[u8]) -> Result<(), SudokuError> {
+        if let Some(k) = self.altered_given(solution) {
+            let pos = Pos::from_index(k, self.size);
+
+            return Err(SudokuError::GivenCell {
+                row: pos.row(),
+                col: pos.col(),
+            });
+        }
+
+        self.write_cells(solution);
+
+        Ok(())
+    }
+
+    /// Strict version of `set_grid_value`, returning `SudokuError::GivenCell` if the cell is
+    /// given with a different value, in which case the grid is left unchanged.
+    #[allow(dead_code)]
+    pub fn try_set_grid_value(&mut self, pos: Pos, value: u8) -> Result<(), SudokuError> {
+        let k = self.index(pos);
+
+        if self.givens[k] && self.cells[k] != value {
+            return Err(SudokuError::GivenCell {
+                row: pos.row(),
+                col: pos.col(),
+            });
+        }
+
+        self.write_cell(k, value);
+
+        Ok(())
+    }
+
+    /// Sets `value` to the `k`th cell in row-major order and updates the unit tally accordingly,
+    /// returning the previous value of the cell.
+    fn write_cell(&mut self, k: usize, value: u8) -> u8 {
+        let pos = Pos::from_index(k, self.size);
+        let previous = std::mem::replace(&mut self.cells[k], value);
+        self.tally.remove(pos, previous);
+        self.tally.add(pos, value);
+
+        previous
+    }
+
+    /// Sets the row-major `values` to the leading cells of the grid with `write_cell`.
+    fn write_cells(&mut self, values: &[u8]) {
+        for (k, &value) in values.iter().enumerate() {
+            self.write_cell(k, value);
+        }
+    }
+
+    /// Returns the row-major index of the first given cell `solution` has a different value for.
+    fn altered_given(&self, solution: &[u8]) -> Option<usize> {
+        solution
+            .iter()
+            .zip(&self.cells)
+            .zip(&self.givens)
+            .position(|((new, old), &given)| given && new != old)
+    }
+
+    /// Sets `value` to the cell at `pos` after checking that the position is within the grid,
+    /// the value is a digit between 1-9, the cell isn't given in the original puzzle, and the
+    /// value doesn't conflict with the row, column, or square of the cell. The grid is left
+    /// unchanged if any of the checks fail.
+    #[allow(dead_code)]
+    pub fn apply_move(&mut self, pos: Pos, value: u8) -> Result<(), SudokuError> {
+        self.check_move_target(pos)?;
+
+        if !(1..=self.size as u8).contains(&value) {
+            return Err(SudokuError::InvalidDigit {
+                value,
+                max: self.size as u8,
+            });
+        }
+
+        let k = self.index(pos);
+        let previous = self.write_cell(k, value);
+
+        if !self.is_valid(Some(pos)) {
+            let conflicts = self
+                .find_conflicts()
+                .into_iter()
+                .filter(|conflict| conflict.cells.contains(&pos))
+                .collect();
+            self.write_cell(k, previous);
+
+            return Err(SudokuError::ConstraintViolation(conflicts));
+        }
+
+        Ok(())
+    }
+
+    /// Empties the cell at `pos` after checking that the position is within the grid and the
+    /// cell isn't given in the original puzzle.
+    #[allow(dead_code)]
+    pub fn clear_move(&mut self, pos: Pos) -> Result<(), SudokuError> {
+        self.check_move_target(pos)?;
+        self.write_cell(self.index(pos), 0);
+
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    fn check_move_target(&self, pos: Pos) -> Result<(), SudokuError> {
+        let (row, col) = (pos.row(), pos.col());
+
+        if row >= self.size || col >= self.size {
+            return Err(SudokuError::PositionOutOfBounds { row, col });
+        }
+
+        if self.is_given(pos) {
+            return Err(SudokuError::GivenCell { row, col });
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the cell at `pos` is given in the original puzzle, i.e. was non-empty when
+    /// the grid was constructed. The positions outside of the grid are never given.
+    pub fn is_given(&self, pos: Pos) -> bool {
+        pos.row() < self.size && pos.col() < self.size && self.givens[self.index(pos)]
     }
 
     /// Wrapper for getting a value from a single cell of the grid. Required as a workaround for
     /// struggling with the borrow checker.
-    pub fn get_grid_value(&self, pos: &(usize, usize)) -> u8 {
-        let (i, j) = *pos;
-        self.grid[i][j]
+    pub fn get_grid_value(&self, pos: Pos) -> u8 {
+        self.cells[self.index(pos)]
     }
 
-    /// Converts the inner grid into 1D `String` after filtering the non-zero integers. Returns
-    /// `true` if the length of the created string is `0`, and `false` otherwise.
+    /// Returns `true` if the grid doesn't contain any empty cells, and `false` otherwise.
     #[allow(dead_code)]
     pub fn is_solved(&self) -> bool {
-        self.grid
+        self.empty_positions().next().is_none()
+    }
+
+    /// Checks whether the grid is a complete and valid solution of `puzzle` under the rules of its
+    /// variant, i.e. of the same size and agreeing with every non-empty cell of it.
+    pub fn is_solution_of(&self, puzzle: &Sudoku) -> bool {
+        self.size == puzzle.size
+            && self.is_solved()
+            && constraint::violations(self, self.all_units(puzzle.variant))
+                .next()
+                .is_none()
+            && self
+                .cells
+                .iter()
+                .zip(&puzzle.cells)
+                .all(|(&value, &given)| given == 0 || given == value)
+    }
+
+    /// Lists the cells whose values differ between this grid and `other` in row-major order, the
+    /// values of this grid being the `from` ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grids are of different sizes.
+    pub fn diff(&self, other: &Sudoku) -> Vec<CellDiff> {
+        assert_eq!(self.size, other.size, "Grids of different sizes compared");
+
+        self.cells
             .iter()
-            .flat_map(|row| row.iter())
-            .filter(|&num| num == &0)
-            .map(|&num| num.to_string())
-            .collect::<String>()
-            .is_empty()
+            .zip(&other.cells)
+            .enumerate()
+            .filter(|(_, (from, to))| from != to)
+            .map(|(k, (&from, &to))| CellDiff {
+                pos: Pos::from_index(k, self.size),
+                from,
+                to,
+            })
+            .collect()
+    }
+
+    /// Lists the cells of `solution` left empty in the original `puzzle`, i.e. the ones filled in
+    /// by the solver, the givens altered by it being left out.
+    pub fn filled_by_solver(solution: &Sudoku, puzzle: &Sudoku) -> Vec<CellDiff> {
+        puzzle
+            .diff(solution)
+            .into_iter()
+            .filter(|diff| diff.from == 0)
+            .collect()
     }
 
     /// Checks for default Sudoku constraints, i.e. all numbers on the same row, column, and 3x3
-    /// square are unique. If `pos` is `Some((i, j))`, the process checks are only performed for
-    /// the row, column, and square matching that grid position.
-    pub fn is_valid(&self, pos: Option<(usize, usize)>) -> bool {
+    /// square (and diagonal of an X-Sudoku) are unique. If `pos` is `Some(pos)`, the process
+    /// checks are only performed for the row, column, and square matching that grid position.
+    pub fn is_valid(&self, pos: Option<Pos>) -> bool {
+        self.find_violation(pos).is_none()
+    }
+
+    /// Returns the first unit violating the default Sudoku constraints as `(unit, index)`, the
+    /// rows being checked before the columns and the squares. Limited to the units of the cell
+    /// at `pos` if it's `Some(pos)`, same as `is_valid`.
+    pub fn find_violation(&self, pos: Option<Pos>) -> Option<(Unit, usize)> {
         match pos {
-            Some((i, j)) => {
-                // "Streamlined" version, only goes through the current coordinates' constraints
-                self.check_row(i) && self.check_col(j) && self.check_sqr(i / 3, j / 3)
-            }
-            None => {
-                // Default version, goes through the whole grid
-                (0..self.size).all(|i| self.check_row(i))
-                    && (0..self.size).all(|j| self.check_col(j))
-                    && (0..self.dim_sqr)
-                        .all(|br| (0..self.dim_sqr).all(|bc| self.check_sqr(br, bc)))
-            }
+            // "Streamlined" version, only goes through the current coordinates' constraints
+            Some(pos) => constraint::violations(self, self.units_of(pos)).next(),
+            // Default version, goes through the whole grid
+            None => constraint::violations(self, self.all_units(self.variant)).next(),
+        }
+    }
+
+    /// Checks the default Sudoku constraints of the whole grid, returning
+    /// `SudokuError::ConstraintViolation` listing the conflicts if any of them are violated.
+    pub fn check_constraints(&self) -> Result<(), SudokuError> {
+        let conflicts = self.find_conflicts();
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(SudokuError::ConstraintViolation(conflicts))
         }
     }
 
-    fn check_row(&self, row_idx: usize) -> bool {
-        has_unique_items(self.grid[row_idx].iter().filter(|&&x| x != 0))
+    /// Lists every digit repeated within a unit of the grid, ordered by the rows, columns,
+    /// squares, and diagonals of an X-Sudoku, and then by the repeated value.
+    pub fn find_conflicts(&self) -> Vec<Conflict> {
+        constraint::violations(self, self.all_units(self.variant))
+            .flat_map(|(unit, index)| constraint::unit_conflicts(self, unit, index))
+            .collect()
     }
 
-    fn check_col(&self, col_idx: usize) -> bool {
-        has_unique_items(self.grid.iter().map(|row| row[col_idx]).filter(|&x| x != 0))
+    /// Returns the legal digits of the cell at `pos` given the current values of its peers (the
+    /// other cells on the same row, column, and square) as a bitmask, bit `d` being set if the
+    /// digit `d` is legal. The value of the cell itself is ignored.
+    pub fn candidates_for(&self, pos: Pos) -> u32 {
+        let value = self.cells[self.index(pos)];
+        let mut seen = self.tally.seen(pos);
+
+        // The value of the cell is only seen through the cell itself unless a peer repeats it
+        if value != 0 && self.tally.is_single(pos, value) {
+            seen &= !(1 << value);
+        }
+
+        self.all_digits() & !seen
     }
 
-    fn check_sqr(&self, br_idx: usize, bc_idx: usize) -> bool {
-        let square = self
-            .grid
+    /// Returns the legal digits of every cell in row-major order as bitmasks in the format of
+    /// `candidates_for`, the filled cells only allowing their current value. Looked up from the
+    /// unit tally maintained by the setters, so it's cheap enough for seeding the solvers.
+    pub fn candidate_masks(&self) -> Vec<u32> {
+        let all = self.all_digits();
+
+        self.cells
             .iter()
-            .skip(br_idx * 3)
-            .take(3)
-            .flat_map(|row| row.iter().skip(bc_idx * 3).take(3))
-            .filter(|&x| x != &0);
+            .enumerate()
+            .map(|(k, &value)| match value {
+                0 => all & !self.tally.seen(Pos::from_index(k, self.size)),
+                _ => 1 << value,
+            })
+            .collect()
+    }
 
-        has_unique_items(square)
+    /// Returns the bitmask of the digits 1-`size`.
+    fn all_digits(&self) -> u32 {
+        ((1u32 << self.size) - 1) << 1
+    }
+
+    /// Returns the peers of the cell at `pos`, i.e. the other cells on the same row, column, and
+    /// square (and diagonals of an X-Sudoku), in row-major order. The table is shared by every
+    /// grid of the same size and variant.
+    pub fn peers(&self, pos: Pos) -> &'static [Pos] {
+        let tables = match self.variant {
+            Variant::Classic => &PEERS,
+            Variant::X => &X_PEERS,
+        };
+
+        &tables[self.dim_sqr - *SUPPORTED_DIM_SQRS.start()]
+            .get_or_init(|| peer_table(self.size, self.variant))[self.index(pos)]
+    }
+
+    /// Returns a 64-bit FNV-1a hash of the size and the values of the grid, stable across
+    /// platforms and releases unlike the `Hash` implementation. The input format is already
+    /// normalized away by the parsing, e.g. the `0` and `.` forms of a puzzle share a digest, but
+    /// the transformed variants of it don't.
+    pub fn digest(&self) -> u64 {
+        std::iter::once(self.size as u8)
+            .chain(self.cells.iter().copied())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// Computes the structural statistics of the puzzle, see `PuzzleStats`.
+    pub fn statistics(&self) -> PuzzleStats {
+        PuzzleStats::of(self)
     }
-}
 
-pub fn has_unique_items<T>(iter: T) -> bool
-where
-    T: IntoIterator,
-    T::Item: Eq + Hash,
-{
-    let mut uniq = HashSet::new();
-    iter.into_iter().all(move |x| uniq.insert(x))
+    /// Returns the `candidates_for` bitmasks of all the empty cells keyed by their position.
+    #[allow(dead_code)]
+    pub fn all_candidates(&self) -> BTreeMap<Pos, u32> {
+        self.empty_positions()
+            .map(|pos| (pos, self.candidates_for(pos)))
+            .collect()
+    }
+
+    /// Iterates over the positions of the cells in the `index`th unit of the grid.
+    fn unit_cells(&self, unit: Unit, index: usize) -> impl Iterator<Item = Pos> {
+        let (size, dim_sqr) = (self.size, self.dim_sqr);
+
+        (0..size).map(move |k| match unit {
+            Unit::Row => Pos::from_row_col(index, k),
+            Unit::Column => Pos::from_row_col(k, index),
+            Unit::Square => Pos::from_box(index, k, dim_sqr),
+            Unit::Diagonal if index == 0 => Pos::from_row_col(k, k),
+            Unit::Diagonal => Pos::from_row_col(k, size - 1 - k),
+        })
+    }
+
+    /// Iterates over every unit of the grid under the rules of the `variant` as `(unit, index)`,
+    /// i.e. the rows, the columns, the squares, and then the diagonals of an X-Sudoku.
+    pub(crate) fn all_units(&self, variant: Variant) -> impl Iterator<Item = (Unit, usize)> {
+        let size = self.size;
+        let diagonals = match variant {
+            Variant::Classic => 0,
+            Variant::X => 2,
+        };
+
+        UNITS
+            .into_iter()
+            .flat_map(move |unit| (0..size).map(move |index| (unit, index)))
+            .chain((0..diagonals).map(|index| (Unit::Diagonal, index)))
+    }
+
+    /// Returns the row, column, and square of the cell at `pos` as `(unit, index)`, followed by
+    /// the diagonals of an X-Sudoku containing it.
+    fn units_of(&self, pos: Pos) -> impl Iterator<Item = (Unit, usize)> {
+        let x = self.variant == Variant::X;
+        let diagonals = [
+            (x && pos.row() == pos.col()).then_some((Unit::Diagonal, 0)),
+            (x && pos.row() + pos.col() == self.size - 1).then_some((Unit::Diagonal, 1)),
+        ];
+
+        UNITS
+            .map(|unit| Some((unit, self.unit_index(unit, pos)?)))
+            .into_iter()
+            .chain(diagonals)
+            .flatten()
+    }
+
+    /// Returns the index of the `unit` containing the cell at `pos`, indexed the same way as by
+    /// `unit_cells`. Only the diagonals may miss the cell, giving `None` off both of them and the
+    /// main diagonal for the center cell on both.
+    pub(crate) fn unit_index(&self, unit: Unit, pos: Pos) -> Option<usize> {
+        match unit {
+            Unit::Row => Some(pos.row()),
+            Unit::Column => Some(pos.col()),
+            Unit::Square => Some(pos.box_index(self.dim_sqr)),
+            Unit::Diagonal if pos.row() == pos.col() => Some(0),
+            Unit::Diagonal => (pos.row() + pos.col() == self.size - 1).then_some(1),
+        }
+    }
+}
+
+/// Validity-preserving transformations, each returning a new `Sudoku` with the givens moved along
+/// with their cells. All of them map the rows, columns, and squares onto rows, columns, and
+/// squares as a whole, so the transformed grid is valid (or solved) if and only if the original
+/// one is.
+#[allow(dead_code)]
+impl Sudoku {
+    /// Applies the `transformation` to a copy of the grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bands, stacks, rows, or columns of the transformation are out of range for
+    /// the size of the grid, e.g. 3 or above for 9x9 grids.
+    pub fn transform(&self, transformation: Transformation) -> Sudoku {
+        assert!(
+            transformation.is_within(self.dim_sqr),
+            "{:?} is out of range for a {}x{} grid",
+            transformation,
+            self.size,
+            self.size
+        );
+
+        let mut transformed = self.clone();
+
+        for pos in Pos::all(self.size) {
+            let source = self.index(transformation.source(pos, self.size, self.dim_sqr));
+            let target = self.index(pos);
+            transformed.cells[target] = self.cells[source];
+            transformed.givens[target] = self.givens[source];
+        }
+
+        transformed.tally = UnitTally::new(&transformed.cells, self.size);
+
+        transformed
+    }
+
+    /// Applies the `transformations` to a copy of the grid in order.
+    pub fn transform_all(&self, transformations: &[Transformation]) -> Sudoku {
+        transformations
+            .iter()
+            .fold(self.clone(), |sudoku, &t| sudoku.transform(t))
+    }
+
+    pub fn transpose(&self) -> Sudoku {
+        self.transform(Transformation::Transpose)
+    }
+
+    pub fn rotate90(&self) -> Sudoku {
+        self.transform(Transformation::Rotate90)
+    }
+
+    pub fn mirror_horizontal(&self) -> Sudoku {
+        self.transform(Transformation::MirrorHorizontal)
+    }
+
+    pub fn swap_rows_within_band(&self, band: usize, a: usize, b: usize) -> Sudoku {
+        self.transform(Transformation::SwapRowsWithinBand { band, a, b })
+    }
+
+    pub fn swap_bands(&self, a: usize, b: usize) -> Sudoku {
+        self.transform(Transformation::SwapBands { a, b })
+    }
+
+    pub fn swap_cols_within_stack(&self, stack: usize, a: usize, b: usize) -> Sudoku {
+        self.transform(Transformation::SwapColsWithinStack { stack, a, b })
+    }
+
+    pub fn swap_stacks(&self, a: usize, b: usize) -> Sudoku {
+        self.transform(Transformation::SwapStacks { a, b })
+    }
+
+    /// Replaces every digit `d` of the grid with `perm[d - 1]`, the givens staying in place.
+    /// Returns `SudokuError::InvalidPermutation` unless `perm` contains each of the digits 1-9
+    /// exactly once, which also rejects the grids other than 9x9.
+    pub fn relabel(&self, perm: &[u8; 9]) -> Result<Sudoku, SudokuError> {
+        let max = self.size as u8;
+        let mut sorted = *perm;
+        sorted.sort_unstable();
+
+        if !sorted.iter().copied().eq(1..=max) {
+            return Err(SudokuError::InvalidPermutation {
+                perm: perm.to_vec(),
+                max,
+            });
+        }
+
+        Ok(self.relabel_unchecked(perm))
+    }
+
+    /// Replaces every digit `d` of the grid with `perm[d - 1]` without validating `perm`.
+    fn relabel_unchecked(&self, perm: &[u8]) -> Sudoku {
+        let mut relabeled = self.clone();
+
+        for value in relabeled.cells.iter_mut().filter(|value| **value != 0) {
+            *value = perm[*value as usize - 1];
+        }
+
+        relabeled.tally = UnitTally::new(&relabeled.cells, self.size);
+
+        relabeled
+    }
+
+    /// Returns a random equivalent variant of the grid for data augmentation: a uniformly random
+    /// permutation of the bands, the stacks, and the rows and columns within them, a transposition
+    /// half of the time, and a random relabeling of the digits. The random choices only depend on
+    /// the size of the grid, so identically seeded RNGs shuffle e.g. a puzzle and its solution
+    /// the same way.
+    pub fn shuffle(&self, rng: &mut impl Rng) -> Sudoku {
+        let dim_sqr = self.dim_sqr;
+        let mut transformations = Vec::new();
+
+        if rng.gen_bool(0.5) {
+            transformations.push(Transformation::Transpose);
+        }
+
+        transformations
+            .extend(random_swaps(rng, dim_sqr).map(|(a, b)| Transformation::SwapBands { a, b }));
+        transformations
+            .extend(random_swaps(rng, dim_sqr).map(|(a, b)| Transformation::SwapStacks { a, b }));
+
+        for group in 0..dim_sqr {
+            transformations.extend(
+                random_swaps(rng, dim_sqr).map(|(a, b)| Transformation::SwapRowsWithinBand {
+                    band: group,
+                    a,
+                    b,
+                }),
+            );
+            transformations.extend(
+                random_swaps(rng, dim_sqr).map(|(a, b)| Transformation::SwapColsWithinStack {
+                    stack: group,
+                    a,
+                    b,
+                }),
+            );
+        }
+
+        let mut perm = (1..=self.size as u8).collect::<Vec<_>>();
+        perm.shuffle(rng);
+
+        self.transform_all(&transformations)
+            .relabel_unchecked(&perm)
+    }
+
+    /// Checks whether `other` is the same puzzle up to a relabeling of the digits and the
+    /// transformations, the givens being disregarded. The search backtracks over the row
+    /// permutations keeping the bands together (pruned by the clue counts of the rows) of both
+    /// the grid and its transpose, and then over the column permutations keeping the stacks
+    /// together (pruned by the consistency of the relabeling). Cheap for 9x9 grids, but grows
+    /// quickly with the larger sizes.
+    pub fn is_equivalent_to(&self, other: &Sudoku) -> bool {
+        if self.size != other.size || self.clue_count() != other.clue_count() {
+            return false;
+        }
+
+        let row_clues = |sudoku: &Sudoku, i: usize| {
+            sudoku
+                .unit_values(Unit::Row, i)
+                .filter(|&(_, value)| value != 0)
+                .count()
+        };
+
+        [self.clone(), self.transpose()].iter().any(|sudoku| {
+            search_lines(self.size, self.dim_sqr, &mut Vec::new(), &mut |rows| {
+                let i = rows.len() - 1;
+
+                if row_clues(sudoku, rows[i]) != row_clues(other, i) {
+                    return false;
+                }
+
+                // The columns only depend on the complete row permutation
+                rows.len() < self.size
+                    || search_lines(self.size, self.dim_sqr, &mut Vec::new(), &mut |cols| {
+                        is_relabeling(
+                            rows.iter().enumerate().flat_map(|(i, &source_i)| {
+                                cols.iter().enumerate().map(move |(j, &source_j)| {
+                                    (
+                                        sudoku
+                                            .get_grid_value(Pos::from_row_col(source_i, source_j)),
+                                        other.get_grid_value(Pos::from_row_col(i, j)),
+                                    )
+                                })
+                            }),
+                            self.size,
+                        )
+                    })
+            })
+        })
+    }
+}
+
+// Compared and hashed by the values of the cells only, the same grid being equal regardless of the
+// cells given in the original puzzle
+impl PartialEq for Sudoku {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl Eq for Sudoku {}
+
+impl Hash for Sudoku {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cells.hash(state);
+    }
+}
+
+impl fmt::Display for Sudoku {
+    /// Renders the grid with box-drawing separators between the 3x3 squares and dots for the
+    /// empty cells. The alternate mode (`{:#}`) highlights the givens of the original puzzle by
+    /// bracketing them, e.g. `[5]`, to tell them apart from the solved cells.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alternate = f.alternate();
+        let rows = self
+            .cells
+            .chunks(self.size)
+            .enumerate()
+            .map(|(i, row)| {
+                let mut line = String::new();
+
+                for (j, &value) in row.iter().enumerate() {
+                    if j > 0 && j % self.dim_sqr == 0 {
+                        line.push_str(if alternate { "│" } else { " │ " });
+                    } else if j > 0 && !alternate {
+                        line.push(' ');
+                    }
+
+                    let cell = match value {
+                        0 => '.',
+                        _ => value_to_char(value),
+                    };
+                    let given = self.is_given(Pos::from_row_col(i, j));
+
+                    match (alternate, given) {
+                        (true, true) => line.extend(['[', cell, ']']),
+                        (true, false) => line.extend([' ', cell, ' ']),
+                        (false, _) => line.push(cell),
+                    }
+                }
+
+                line
+            })
+            .collect::<Vec<String>>();
+
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            if i > 0 && i % self.dim_sqr == 0 {
+                // Crossings are aligned with the column separators of the rows
+                let separator = row
+                    .chars()
+                    .map(|ch| if ch == '│' { '┼' } else { '─' })
+                    .collect::<String>();
+                writeln!(f, "{}", separator)?;
+            }
+
+            f.write_str(row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.grid_to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Sudoku {
+    /// Parses any of the string formats accepted by `FromStr`, the non-empty cells becoming the
+    /// givens of the deserialized puzzle.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Alternative serde representation of `Sudoku` as a 2D array of values (`0` for the empty
+/// cells), used with `#[serde(with = "sudoku::nested")]`.
+pub mod nested {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Sudoku;
+
+    #[allow(dead_code)]
+    pub fn serialize<S: Serializer>(sudoku: &Sudoku, serializer: S) -> Result<S::Ok, S::Error> {
+        sudoku.clone_grid().serialize(serializer)
+    }
+
+    #[allow(dead_code)]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Sudoku, D::Error> {
+        let grid = Vec::<Vec<u8>>::deserialize(deserializer)?;
+        Sudoku::from_grid(grid).map_err(de::Error::custom)
+    }
+}
+
+/// Supported dimensions of the squares, i.e. grids from 4x4 up to 25x25. The single character
+/// alphabet of the values runs out above 25x25 grids.
+const SUPPORTED_DIM_SQRS: RangeInclusive<usize> = 2..=5;
+
+/// Peer tables of the supported sizes built on first use, indexed by the dimension of the squares
+/// starting from the smallest supported one.
+static PEERS: [OnceLock<PeerTable>; 4] = [
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+];
+
+/// Peer tables of the X-Sudokus, indexed the same way as `PEERS`.
+static X_PEERS: [OnceLock<PeerTable>; 4] = [
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+];
+
+// Parameters of the 64-bit FNV-1a hash of `Sudoku::digest`
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Row-major peers of every cell of the grid.
+type PeerTable = Vec<Vec<Pos>>;
+
+/// Builds the `PeerTable` of a grid of `size` rows under the rules of the `variant`.
+fn peer_table(size: usize, variant: Variant) -> PeerTable {
+    let dim_sqr = size.isqrt();
+    let diagonals = variant == Variant::X;
+
+    Pos::all(size)
+        .map(|pos| {
+            let square = pos.box_index(dim_sqr);
+            let main = diagonals && pos.row() == pos.col();
+            let anti = diagonals && pos.row() + pos.col() == size - 1;
+            let mut peers = (0..size)
+                .flat_map(|x| {
+                    [
+                        Some(Pos::from_row_col(x, pos.col())),
+                        Some(Pos::from_row_col(pos.row(), x)),
+                        Some(Pos::from_box(square, x, dim_sqr)),
+                        main.then(|| Pos::from_row_col(x, x)),
+                        anti.then(|| Pos::from_row_col(x, size - 1 - x)),
+                    ]
+                })
+                .flatten()
+                .filter(|&peer| peer != pos)
+                .collect::<Vec<_>>();
+
+            peers.sort_unstable();
+            peers.dedup();
+            peers
+        })
+        .collect()
+}
+
+/// Returns the supported size of the grid (i.e. the number of rows) consisting of `cells` cells.
+fn grid_size(cells: usize) -> Option<usize> {
+    SUPPORTED_DIM_SQRS
+        .map(|dim_sqr| dim_sqr * dim_sqr)
+        .find(|&size| size * size == cells)
+}
+
+/// Lists the supported sizes mapped with `f` for the error messages, e.g. "4, 9, 16, or 25".
+fn supported_sizes_text(f: impl Fn(usize) -> usize) -> String {
+    let sizes = SUPPORTED_DIM_SQRS
+        .map(|dim_sqr| f(dim_sqr * dim_sqr).to_string())
+        .collect::<Vec<_>>();
+
+    match sizes.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{}, or {}", rest.join(", "), last),
+        _ => sizes.concat(),
+    }
+}
+
+/// Converts the value of a cell into its character, the values above 9 being represented with
+/// the letters starting from `A` (10).
+pub(crate) fn value_to_char(value: u8) -> char {
+    match value {
+        0..=9 => char::from(b'0' + value),
+        _ => char::from(b'A' + value - 10),
+    }
+}
+
+// Separators of the pretty format, both the ASCII and the box-drawing variants
+const VERTICAL_SEPARATORS: [char; 4] = ['|', '│', '┃', '║'];
+const HORIZONTAL_SEPARATORS: [char; 10] = ['-', '+', '─', '━', '═', '┼', '╋', '╬', '├', '┤'];
+
+impl FromStr for Sudoku {
+    type Err = SudokuError;
+
+    /// Parses the grid from either of the following formats, empty cells being represented with
+    /// `0`, `.`, or `-`, and the values above 9 of the grids larger than 9x9 with the letters
+    /// starting from `A` (10). The size of the grid is derived from the number of cells, e.g. 16
+    /// cells for 4x4 and 256 for 16x16 grids:
+    ///
+    /// - A contiguous 1D string of 81 cells
+    /// - The same with embedded whitespace, e.g. 9 lines of 9 cells
+    /// - The pretty format where the 3x3 squares are separated with `|` between the columns, and
+    ///   lines of `-` & `+` between the rows (or their box-drawing counterparts)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The dashes only separate the rows if the columns are separated as well
+        let pretty = s.contains(VERTICAL_SEPARATORS);
+        let mut cells = Vec::with_capacity(81);
+        // Values are only validated once the size of the grid is known
+        let mut chars = Vec::with_capacity(81);
+        let mut offset = 0;
+
+        for line in s.split_inclusive('\n') {
+            let is_separator = line
+                .chars()
+                .all(|ch| ch.is_whitespace() || HORIZONTAL_SEPARATORS.contains(&ch));
+
+            if !(pretty && is_separator) {
+                for (i, ch) in line.chars().enumerate() {
+                    if ch.is_whitespace() || (pretty && VERTICAL_SEPARATORS.contains(&ch)) {
+                        continue;
+                    }
+
+                    chars.push((offset + i, ch));
+                    cells.push(match ch {
+                        '0' | '.' | '-' => 0,
+                        '1'..='9' => ch as u8 - b'0',
+                        'A'..='Z' | 'a'..='z' => ch.to_ascii_uppercase() as u8 - b'A' + 10,
+                        _ => {
+                            return Err(SudokuError::InvalidCharacter {
+                                index: offset + i,
+                                ch,
+                            })
+                        }
+                    });
+                }
+            }
+
+            offset += line.chars().count();
+        }
+
+        let size =
+            grid_size(cells.len()).ok_or(SudokuError::WrongCellCount { cells: cells.len() })?;
+
+        if let Some((_, &(index, ch))) = cells
+            .iter()
+            .zip(&chars)
+            .find(|(&value, _)| value > size as u8)
+        {
+            return Err(SudokuError::InvalidCharacter { index, ch });
+        }
+
+        Self::from_cells(cells)
+    }
+}
+
+impl TryFrom<[[u8; 9]; 9]> for Sudoku {
+    type Error = SudokuError;
+
+    /// Constructs a new struct from a fixed size 2D grid, validating that the values are between
+    /// `0` (empty) and `9`.
+    fn try_from(grid: [[u8; 9]; 9]) -> Result<Self, Self::Error> {
+        Self::from_cells(grid.concat())
+    }
+}
+
+impl TryFrom<&[u8; 81]> for Sudoku {
+    type Error = SudokuError;
+
+    /// Constructs a new struct from a row-major 1D grid, validating that the values are between
+    /// `0` (empty) and `9`.
+    fn try_from(cells: &[u8; 81]) -> Result<Self, Self::Error> {
+        Self::from_cells(cells.to_vec())
+    }
+}
+
+/// Backtracks over the permutations of `size` lines (rows or columns) keeping the groups of
+/// `dim_sqr` lines (bands or stacks) together, `perm[k]` being the source line of the `k`th one.
+/// `extend` is called after every line added to the partial permutation, pruning it by returning
+/// `false`. Returns `true` once `extend` accepts a complete permutation.
+fn search_lines(
+    size: usize,
+    dim_sqr: usize,
+    perm: &mut Vec<usize>,
+    extend: &mut impl FnMut(&[usize]) -> bool,
+) -> bool {
+    if perm.len() == size {
+        return true;
+    }
+
+    let k = perm.len();
+    let group = |line: usize| line / dim_sqr;
+    let candidates = (0..size)
+        .filter(|line| !perm.contains(line))
+        .filter(|&line| match k % dim_sqr {
+            // The first line of a group starts any of the unused groups
+            0 => perm.iter().all(|&used| group(used) != group(line)),
+            _ => group(line) == group(perm[k - 1]),
+        })
+        .collect::<Vec<_>>();
+
+    for line in candidates {
+        perm.push(line);
+
+        if extend(perm) && search_lines(size, dim_sqr, perm, extend) {
+            return true;
+        }
+
+        perm.pop();
+    }
+
+    false
+}
+
+/// Draws a uniformly random permutation of `n` items as the swaps of a Fisher-Yates shuffle, the
+/// no-op swaps left out.
+fn random_swaps(rng: &mut impl Rng, n: usize) -> impl Iterator<Item = (usize, usize)> {
+    let swaps = (1..n)
+        .rev()
+        .map(|i| (i, rng.gen_range(0..=i)))
+        .filter(|&(i, j)| i != j)
+        .collect::<Vec<_>>();
+
+    swaps.into_iter()
+}
+
+/// Checks whether the `(from, to)` pairs of values are consistent with a single bijection of the
+/// digits 1-`size`, the empty cells only pairing with each other.
+fn is_relabeling(pairs: impl Iterator<Item = (u8, u8)>, size: usize) -> bool {
+    let mut forward = vec![0; size + 1];
+    let mut backward = vec![0; size + 1];
+
+    for (from, to) in pairs {
+        if (from == 0) != (to == 0) {
+            return false;
+        }
+
+        if from == 0 || forward[from as usize] == to {
+            continue;
+        }
+
+        if forward[from as usize] != 0 || backward[to as usize] != 0 {
+            return false;
+        }
+
+        forward[from as usize] = to;
+        backward[to as usize] = from;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use rand::Rng;
+
+    const FLAT_GRID: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const SOLVED_GRID: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+    const HEX_UNSOLVED_GRID: &str = "008A03041F05200DF050A0080CD2070BCG00E10500B0890A00400G0209A85F1009B03000F0000200070000000000A0F000EG1F5A703008960F00698B02GE0473G0C000100080000000000A000040700060058037E00FCGD40B780D000659F100000702E180030000B80074DG0A060E2C0560980320C1GD07000000A04D703000";
+    const HEX_SOLVED_GRID: &str = "968AB3741FE52CGDF15EA698GCD2473BCG2DE1F537B4896A734BDGC269A85F1E89B6374DF51AE2CG47D3GC2E986BA5F12CEG1F5A743DB8965FA1698BC2GED473GDC42E1FB38796A51EF25A69DG4C73B86A958B37E12FCGD43B784DGCA659F1E2D4G7C2E18B936A5FB83974DG5AF61E2CA56F98B32EC1GD47E21CF5A64D7G3B89";
+
+    #[test]
+    fn test_parse_flat() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+
+        let blanks = FLAT_GRID.replacen('0', ".", 10).replacen('0', "-", 10);
+        let sudoku = blanks.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let lines = FLAT_GRID
+            .as_bytes()
+            .chunks(9)
+            .map(|row| String::from_utf8(row.to_vec()).unwrap())
+            .collect::<Vec<String>>()
+            .join("\n");
+        let sudoku = lines.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+
+        let spaced = format!("  {}\r\n", lines.replace('\n', " \r\n\t"));
+        let sudoku = spaced.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+    }
+
+    #[test]
+    fn test_parse_pretty() {
+        let pretty = "\
+            5 3 . | . 7 . | . . .
+            6 . . | 1 9 5 | . . .
+            . 9 8 | . . . | . 6 .
+            ------+-------+------
+            8 . . | . 6 . | . . 3
+            4 . . | 8 . 3 | . . 1
+            7 . . | . 2 . | . . 6
+            ------+-------+------
+            . 6 . | . . . | 2 8 .
+            . . . | 4 1 9 | . . 5
+            . . . | . 8 . | . 7 9";
+        let sudoku = pretty.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+
+        let boxed = pretty.replace('|', "│").replace('-', "─").replace('+', "┼");
+        let sudoku = boxed.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let reparsed = sudoku.grid_to_string().parse::<Sudoku>().unwrap();
+        assert_eq!(reparsed.clone_grid(), sudoku.clone_grid());
+        assert_eq!(
+            Sudoku::new(FLAT_GRID.to_owned()).unwrap().clone_grid(),
+            sudoku.clone_grid()
+        );
+    }
+
+    #[test]
+    fn test_to_string_with() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let options = OutputFormat::default().options();
+        assert_eq!(sudoku.to_string_with(options), sudoku.grid_to_string());
+        assert_eq!(
+            sudoku.to_string_with(OutputFormat::FlatDot.options()),
+            FLAT_GRID.replace('0', ".")
+        );
+
+        let multiline = sudoku.to_string_with(OutputFormat::Multiline.options());
+        assert_eq!(multiline.lines().count(), 9);
+        assert_eq!(multiline.lines().next(), Some("53..7...."));
+        assert!(!multiline.ends_with('\n'));
+
+        let custom = FormatOptions {
+            empty: '-',
+            row_separator: Some(' '),
+        };
+        assert_eq!(&sudoku.to_string_with(custom)[..19], "53--7---- 6--195---");
+
+        // Every format can be parsed back into the same grid, regardless of its size
+        for raw in [FLAT_GRID, HEX_UNSOLVED_GRID, "1030040220404003"] {
+            let sudoku = raw.parse::<Sudoku>().unwrap();
+
+            for format in [
+                OutputFormat::FlatZero,
+                OutputFormat::FlatDot,
+                OutputFormat::Multiline,
+            ] {
+                let formatted = sudoku.to_string_with(format.options());
+                assert_eq!(formatted.parse::<Sudoku>(), Ok(sudoku.clone()));
+            }
+
+            assert_eq!(sudoku.to_string_with(custom).parse(), Ok(sudoku));
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let expected = "\
+5 3 . │ . 7 . │ . . .
+6 . . │ 1 9 5 │ . . .
+. 9 8 │ . . . │ . 6 .
+──────┼───────┼──────
+8 . . │ . 6 . │ . . 3
+4 . . │ 8 . 3 │ . . 1
+7 . . │ . 2 . │ . . 6
+──────┼───────┼──────
+. 6 . │ . . . │ 2 8 .
+. . . │ 4 1 9 │ . . 5
+. . . │ . 8 . │ . 7 9";
+
+        assert_eq!(sudoku.to_string(), expected);
+        assert_eq!(sudoku.to_pretty_string(), expected);
+
+        let reparsed = expected.parse::<Sudoku>().unwrap();
+        assert_eq!(reparsed.grid_to_string(), FLAT_GRID);
+    }
+
+    #[test]
+    fn test_display_alternate() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        sudoku.set_grid_value(Pos::from_row_col(0, 2), 4);
+        sudoku.set_grid_value(Pos::from_row_col(8, 0), 3);
+
+        let rendered = format!("{:#}", sudoku);
+        let lines = rendered.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "[5][3] 4 │ . [7] . │ .  .  . ");
+        assert_eq!(lines[3], "─────────┼─────────┼─────────");
+        assert_eq!(lines[10], " 3  .  . │ . [8] . │ . [7][9]");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = FLAT_GRID
+            .replacen('0', "x", 1)
+            .parse::<Sudoku>()
+            .unwrap_err();
+        assert_eq!(err, SudokuError::InvalidCharacter { index: 2, ch: 'x' });
+        assert!(err.to_string().contains("'x' at index 2"));
+
+        let err = "53007│0000\n".parse::<Sudoku>().unwrap_err();
+        assert_eq!(err, SudokuError::WrongCellCount { cells: 9 });
+        assert!(err
+            .to_string()
+            .ends_with("9 cells instead of 16, 81, 256, or 625"));
+        let err = "5300é70000".parse::<Sudoku>().unwrap_err();
+        assert_eq!(err, SudokuError::InvalidCharacter { index: 4, ch: 'é' });
+    }
+
+    #[test]
+    fn test_parse_random_bytes() {
+        let mut rng = rand::thread_rng();
+        // Biased towards the accepted characters to also exercise the later validation steps
+        let alphabet = "0123456789.-|+\n \t│─┼x".chars().collect::<Vec<_>>();
+
+        for _ in 0..10_000 {
+            let len = rng.gen_range(0..200);
+            let raw = if rng.gen_bool(0.5) {
+                let bytes = (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+                String::from_utf8_lossy(&bytes).into_owned()
+            } else {
+                (0..len)
+                    .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                    .collect()
+            };
+
+            if let Ok(sudoku) = raw.parse::<Sudoku>() {
+                let grid = sudoku.clone_grid();
+                assert_eq!(grid.len(), 9);
+                assert!(grid.iter().all(|row| row.len() == 9));
+                assert!(grid.iter().flatten().all(|&value| value <= 9));
+            }
+        }
+    }
+
+    #[test]
+    fn test_as_flat() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+
+        assert_eq!(sudoku.as_flat(), sudoku.clone_grid().concat());
+        assert_eq!(
+            sudoku.as_flat()[1],
+            sudoku.get_grid_value(Pos::from_row_col(0, 1))
+        );
+
+        sudoku.set_grid_value(Pos::from_row_col(4, 7), 3);
+        assert_eq!(sudoku.as_flat()[4 * 9 + 7], 3);
+        assert_eq!(sudoku.clone_grid()[4][7], 3);
+    }
+
+    #[test]
+    fn test_try_from_arrays() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let mut cells: [u8; 81] = sudoku.as_flat().try_into().unwrap();
+        let mut grid = [[0; 9]; 9];
+        for (row, values) in grid.iter_mut().zip(sudoku.clone_grid()) {
+            row.copy_from_slice(&values);
+        }
+
+        for converted in [Sudoku::try_from(&cells), Sudoku::try_from(grid)] {
+            let converted = converted.unwrap();
+            assert_eq!(converted.clone_grid(), sudoku.clone_grid());
+            assert_eq!(converted.clue_count(), 30);
+        }
+
+        cells[40] = 10;
+        grid[8][2] = 255;
+        assert_eq!(
+            Sudoku::try_from(&cells).unwrap_err(),
+            SudokuError::ValueOutOfRange {
+                row: 4,
+                col: 4,
+                value: 10,
+                max: 9
+            }
+        );
+        assert_eq!(
+            Sudoku::try_from(grid).unwrap_err(),
+            SudokuError::ValueOutOfRange {
+                row: 8,
+                col: 2,
+                value: 255,
+                max: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_iterators() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let values = |unit: &[Cell]| unit.iter().map(|&(_, v)| v).collect::<Vec<_>>();
+
+        let rows = sudoku
+            .rows()
+            .map(Iterator::collect)
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(rows.len(), 9);
+        assert_eq!(rows[1][4], (Pos::from_row_col(1, 4), 9));
+        assert_eq!(values(&rows[1]), vec![6, 0, 0, 1, 9, 5, 0, 0, 0]);
+
+        let cols = sudoku
+            .cols()
+            .map(Iterator::collect)
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(cols[8][3], (Pos::from_row_col(3, 8), 3));
+        assert_eq!(values(&cols[8]), vec![0, 0, 0, 3, 1, 6, 0, 5, 9]);
+
+        let boxes = sudoku
+            .boxes()
+            .map(Iterator::collect)
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(boxes[5][0], (Pos::from_row_col(3, 6), 0));
+        assert_eq!(boxes[5][8], (Pos::from_row_col(5, 8), 6));
+        assert_eq!(values(&boxes[5]), vec![0, 0, 3, 0, 0, 1, 0, 0, 6]);
+
+        let empty = sudoku.empty_positions().collect::<Vec<_>>();
+        assert_eq!(empty.len(), 81 - sudoku.clue_count());
+        assert_eq!(
+            empty[..3],
+            [
+                Pos::from_row_col(0, 2),
+                Pos::from_row_col(0, 3),
+                Pos::from_row_col(0, 5)
+            ]
+        );
+        assert!(!sudoku.is_solved());
+
+        let mut solved = sudoku.clone();
+        for pos in empty {
+            solved.set_grid_value(pos, 1);
+        }
+        assert!(solved.is_solved());
+        assert_eq!(solved.empty_positions().next(), None);
+    }
+
+    #[test]
+    fn test_apply_move() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+
+        sudoku.apply_move(Pos::from_row_col(0, 2), 4).unwrap();
+        assert_eq!(sudoku.get_grid_value(Pos::from_row_col(0, 2)), 4);
+        // Moves can replace the earlier moves
+        sudoku.apply_move(Pos::from_row_col(0, 2), 1).unwrap();
+        assert_eq!(sudoku.get_grid_value(Pos::from_row_col(0, 2)), 1);
+        sudoku.clear_move(Pos::from_row_col(0, 2)).unwrap();
+        assert_eq!(sudoku.get_grid_value(Pos::from_row_col(0, 2)), 0);
+
+        assert_eq!(
+            sudoku.apply_move(Pos::from_row_col(9, 0), 1),
+            Err(SudokuError::PositionOutOfBounds { row: 9, col: 0 })
+        );
+        assert_eq!(
+            sudoku.clear_move(Pos::from_row_col(0, 9)),
+            Err(SudokuError::PositionOutOfBounds { row: 0, col: 9 })
+        );
+        assert_eq!(
+            sudoku.apply_move(Pos::from_row_col(0, 2), 12),
+            Err(SudokuError::InvalidDigit { value: 12, max: 9 })
+        );
+        assert_eq!(
+            sudoku.apply_move(Pos::from_row_col(0, 2), 0),
+            Err(SudokuError::InvalidDigit { value: 0, max: 9 })
+        );
+        assert_eq!(
+            sudoku.apply_move(Pos::from_row_col(0, 0), 1),
+            Err(SudokuError::GivenCell { row: 0, col: 0 })
+        );
+        assert_eq!(
+            sudoku.clear_move(Pos::from_row_col(0, 1)),
+            Err(SudokuError::GivenCell { row: 0, col: 1 })
+        );
+        assert_eq!(
+            sudoku.apply_move(Pos::from_row_col(0, 2), 3),
+            Err(SudokuError::ConstraintViolation(vec![
+                Conflict {
+                    unit: Unit::Row,
+                    index: 0,
+                    value: 3,
+                    cells: vec![Pos::from_row_col(0, 1), Pos::from_row_col(0, 2)],
+                },
+                Conflict {
+                    unit: Unit::Square,
+                    index: 0,
+                    value: 3,
+                    cells: vec![Pos::from_row_col(0, 1), Pos::from_row_col(0, 2)],
+                },
+            ]))
+        );
+
+        // The rejected moves leave the grid unchanged
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let digits = |mask: u32| {
+            (1..=9)
+                .filter(|d| mask & (1 << d) != 0)
+                .collect::<Vec<u8>>()
+        };
+
+        // Row 0 has 3, 5, 7, column 2 has 8, and square 0 has 3, 5, 6, 8, 9
+        assert_eq!(
+            digits(sudoku.candidates_for(Pos::from_row_col(0, 2))),
+            vec![1, 2, 4]
+        );
+        // Row 4 has 1, 3, 4, 8, column 4 has 1, 2, 6, 7, 8, 9, and square 4 has 2, 3, 6, 8
+        assert_eq!(
+            digits(sudoku.candidates_for(Pos::from_row_col(4, 4))),
+            vec![5]
+        );
+        // The value of the cell itself is ignored, i.e. the given 5 remains legal
+        assert_eq!(
+            digits(sudoku.candidates_for(Pos::from_row_col(0, 0))),
+            vec![1, 2, 5]
+        );
+
+        let all = sudoku.all_candidates();
+        assert_eq!(all.len(), 81 - 30);
+        assert!(!all.contains_key(&Pos::from_row_col(0, 0)));
+        assert_eq!(
+            all[&Pos::from_row_col(0, 2)],
+            sudoku.candidates_for(Pos::from_row_col(0, 2))
+        );
+        assert!(all.values().all(|&mask| mask & 1 == 0 && mask != 0));
+    }
+
+    #[test]
+    fn test_candidate_masks() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let naive = |sudoku: &Sudoku, pos: Pos| {
+            sudoku
+                .peers(pos)
+                .iter()
+                .fold(sudoku.all_digits(), |mask, &peer| {
+                    mask & !(1 << sudoku.get_grid_value(peer))
+                })
+        };
+        let masks = sudoku.candidate_masks();
+
+        assert_eq!(masks[0], 1 << 5);
+        assert_eq!(masks[2], sudoku.candidates_for(Pos::from_row_col(0, 2)));
+
+        // The tally follows the setters, including the ones leaving duplicates behind
+        let solution = SOLVED_GRID.parse::<Sudoku>().unwrap();
+        for (i, pos) in sudoku
+            .empty_positions()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .enumerate()
+        {
+            sudoku.set_grid_value(pos, solution.get_grid_value(pos));
+
+            if i % 3 == 0 {
+                sudoku.set_grid_value(pos, 0);
+            }
+            if i % 7 == 0 {
+                sudoku.set_grid_value(pos, 5);
+            }
+
+            let reparsed = sudoku.grid_to_string().parse::<Sudoku>().unwrap();
+            assert_eq!(sudoku.candidate_masks(), reparsed.candidate_masks());
+
+            for pos in Pos::all(9) {
+                assert_eq!(sudoku.candidates_for(pos), naive(&sudoku, pos), "{}", pos);
+            }
+        }
+
+        let transformed = sudoku
+            .transpose()
+            .relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9])
+            .unwrap();
+        for pos in Pos::all(9) {
+            assert_eq!(transformed.candidates_for(pos), naive(&transformed, pos));
+        }
+    }
+
+    #[test]
+    fn test_check_constraints() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.check_constraints(), Ok(()));
+        assert!(sudoku.find_conflicts().is_empty());
+
+        for (pos, value, unit, index) in
+            [((8, 0), 5, Unit::Column, 0), ((3, 3), 2, Unit::Square, 4)].map(
+                |((row, col), value, unit, index)| {
+                    (Pos::from_row_col(row, col), value, unit, index)
+                },
+            )
+        {
+            let mut invalid = sudoku.clone();
+            invalid.set_grid_value(pos, value);
+            let SudokuError::ConstraintViolation(conflicts) =
+                invalid.check_constraints().unwrap_err()
+            else {
+                panic!("Expected a constraint violation");
+            };
+            assert_eq!((conflicts[0].unit, conflicts[0].index), (unit, index));
+            assert_eq!(invalid.find_violation(None), Some((unit, index)));
+            assert_eq!(invalid.find_violation(Some(pos)), Some((unit, index)));
+            assert!(!invalid.is_valid(None));
+        }
+
+        assert_eq!(sudoku.find_violation(None), None);
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let x_solution =
+            "254367198376189425189542673567491382491238567823756914715924836642813759938675241"
+                .parse::<Sudoku>()
+                .unwrap();
+        assert!(x_solution.is_valid(None));
+        assert!(x_solution.clone().with_variant(Variant::X).is_valid(None));
+
+        // A classic solution of the same X-Sudoku, repeating the 3 of (0, 0) at (7, 7) on the main
+        // diagonal and the 6 of (2, 6) at (4, 4) on the anti-diagonal
+        let puzzle =
+            "000000098006089400100500000000000080400200007000000000000904800600000000030075000"
+                .parse::<Sudoku>()
+                .unwrap();
+        let classic =
+            "347126598526389471189547623973451286418263957265798314751934862694812735832675149"
+                .parse::<Sudoku>()
+                .unwrap();
+        assert!(classic.is_solution_of(&puzzle));
+        assert!(!classic.is_solution_of(&puzzle.clone().with_variant(Variant::X)));
+        assert!(x_solution.is_solution_of(&puzzle.with_variant(Variant::X)));
+
+        let classic = classic.with_variant(Variant::X);
+        assert_eq!(classic.variant(), Variant::X);
+        assert!(!classic.is_valid(None));
+        assert_eq!(classic.find_violation(None), Some((Unit::Diagonal, 0)));
+        assert_eq!(
+            classic.find_violation(Some(Pos::from_row_col(2, 6))),
+            Some((Unit::Diagonal, 1))
+        );
+        // Neither of the diagonals passes through (0, 1)
+        assert_eq!(classic.find_violation(Some(Pos::from_row_col(0, 1))), None);
+
+        let diagonal = |pos| classic.unit_index(Unit::Diagonal, pos);
+        assert_eq!(diagonal(Pos::from_row_col(7, 7)), Some(0));
+        assert_eq!(diagonal(Pos::from_row_col(4, 4)), Some(0));
+        assert_eq!(diagonal(Pos::from_row_col(2, 6)), Some(1));
+        assert_eq!(diagonal(Pos::from_row_col(0, 1)), None);
+        for (unit, index) in classic.all_units(Variant::X) {
+            assert!(classic
+                .unit_cells(unit, index)
+                .all(|pos| classic.unit_index(unit, pos).is_some()));
+        }
+
+        let conflicts = classic.find_conflicts();
+        assert_eq!(conflicts.len(), 7);
+        assert_eq!(
+            conflicts[0],
+            Conflict {
+                unit: Unit::Diagonal,
+                index: 0,
+                value: 3,
+                cells: vec![Pos::from_row_col(0, 0), Pos::from_row_col(7, 7)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        // Repeats the 5 of (0, 0) on row 0, which also repeats the 5 of (7, 8) on column 8
+        sudoku.set_grid_value(Pos::from_row_col(0, 8), 5);
+        // Repeats the 7 of (0, 4) on square 1
+        sudoku.set_grid_value(Pos::from_row_col(2, 5), 7);
+
+        assert_eq!(
+            sudoku.find_conflicts(),
+            vec![
+                Conflict {
+                    unit: Unit::Row,
+                    index: 0,
+                    value: 5,
+                    cells: vec![Pos::from_row_col(0, 0), Pos::from_row_col(0, 8)],
+                },
+                Conflict {
+                    unit: Unit::Column,
+                    index: 8,
+                    value: 5,
+                    cells: vec![Pos::from_row_col(0, 8), Pos::from_row_col(7, 8)],
+                },
+                Conflict {
+                    unit: Unit::Square,
+                    index: 1,
+                    value: 7,
+                    cells: vec![Pos::from_row_col(0, 4), Pos::from_row_col(2, 5)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hex_grid() {
+        let unsolved = HEX_UNSOLVED_GRID.parse::<Sudoku>().unwrap();
+        assert_eq!(unsolved.size(), 16);
+        assert_eq!(unsolved.grid_to_string(), HEX_UNSOLVED_GRID);
+        assert_eq!(unsolved.clue_count(), 256 - 130);
+        assert_eq!(unsolved.get_grid_value(Pos::from_row_col(0, 3)), 10);
+        assert!(unsolved.is_valid(None));
+        assert!(!unsolved.is_solved());
+
+        let solved = HEX_SOLVED_GRID.to_lowercase().parse::<Sudoku>().unwrap();
+        assert_eq!(solved.grid_to_string(), HEX_SOLVED_GRID);
+        assert!(solved.is_valid(None) && solved.is_solved());
+        assert_eq!(Sudoku::from_grid(solved.clone_grid()).unwrap().size(), 16);
+
+        let invalid = format!("G{}", &HEX_SOLVED_GRID[1..])
+            .parse::<Sudoku>()
+            .unwrap();
+        assert!(!invalid.is_valid(None));
+        assert!(!invalid.is_valid(Some(Pos::from_row_col(0, 0))));
+
+        // Letters above `G` and outside of 16x16 grids are invalid
+        let err = HEX_UNSOLVED_GRID.replacen('G', "H", 1).parse::<Sudoku>();
+        assert_eq!(
+            err.unwrap_err(),
+            SudokuError::InvalidCharacter { index: 33, ch: 'H' }
+        );
+        let err = FLAT_GRID.replacen('0', "A", 1).parse::<Sudoku>();
+        assert_eq!(
+            err.unwrap_err(),
+            SudokuError::InvalidCharacter { index: 2, ch: 'A' }
+        );
+    }
+
+    #[test]
+    fn test_small_grid() {
+        let raw = "1.3. .4.2 2.4. 4..3";
+        let sudoku = raw.parse::<Sudoku>().unwrap();
+        assert_eq!(sudoku.size(), 4);
+        assert_eq!(sudoku.grid_to_string(), "1030040220404003");
+        assert_eq!(sudoku.clue_count(), 8);
+        assert!(sudoku.is_valid(None));
+
+        let reparsed = sudoku.to_pretty_string().parse::<Sudoku>().unwrap();
+        assert_eq!(reparsed.clone_grid(), sudoku.clone_grid());
+        assert_eq!(
+            Sudoku::from_grid(sudoku.clone_grid()).unwrap().clone_grid(),
+            sudoku.clone_grid()
+        );
+        assert_eq!(
+            sudoku.to_pretty_string(),
+            "1 . │ 3 .\n. 4 │ . 2\n────┼────\n2 . │ 4 .\n4 . │ . 3"
+        );
+
+        let mut invalid = sudoku.clone();
+        // Conflicts with the 1 of the top left square
+        invalid.set_grid_value(Pos::from_row_col(1, 0), 1);
+        assert_eq!(
+            invalid.check_constraints(),
+            Err(SudokuError::ConstraintViolation(vec![
+                Conflict {
+                    unit: Unit::Column,
+                    index: 0,
+                    value: 1,
+                    cells: vec![Pos::from_row_col(0, 0), Pos::from_row_col(1, 0)],
+                },
+                Conflict {
+                    unit: Unit::Square,
+                    index: 0,
+                    value: 1,
+                    cells: vec![Pos::from_row_col(0, 0), Pos::from_row_col(1, 0)],
+                },
+            ]))
+        );
+        assert_eq!(
+            sudoku.clone().apply_move(Pos::from_row_col(0, 1), 5),
+            Err(SudokuError::InvalidDigit { value: 5, max: 4 })
+        );
+
+        // Digits above the size of the grid are invalid
+        assert_eq!(
+            "1530040220404003".parse::<Sudoku>().unwrap_err(),
+            SudokuError::InvalidCharacter { index: 1, ch: '5' }
+        );
+        assert_eq!(
+            Sudoku::from_grid(vec![vec![0; 5]; 5]).unwrap_err(),
+            SudokuError::WrongRowCount { rows: 5 }
+        );
+        assert!(Sudoku::from_grid(vec![vec![0; 25]; 25]).is_ok());
+    }
+
+    #[test]
+    fn test_serde() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let json = serde_json::to_string(&sudoku).unwrap();
+        assert_eq!(json, format!("\"{}\"", FLAT_GRID));
+
+        let deserialized: Sudoku = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.clone_grid(), sudoku.clone_grid());
+        assert_eq!(deserialized.to_string(), sudoku.to_string());
+        assert_eq!(format!("{:#}", deserialized), format!("{:#}", sudoku));
+
+        let err = serde_json::from_str::<Sudoku>("\"123\"").unwrap_err();
+        assert!(err.to_string().contains("3 cells instead of"));
+
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Nested {
+            #[serde(with = "nested")]
+            grid: Sudoku,
+        }
+
+        let json = serde_json::to_value(Nested {
+            grid: sudoku.clone(),
+        })
+        .unwrap();
+        assert_eq!(
+            json["grid"][0],
+            serde_json::json!([5, 3, 0, 0, 7, 0, 0, 0, 0])
+        );
+        assert_eq!(json["grid"].as_array().unwrap().len(), 9);
+
+        let deserialized: Nested = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.grid.clone_grid(), sudoku.clone_grid());
+
+        let ragged = serde_json::json!({ "grid": vec![vec![0; 9]; 8] });
+        let err = serde_json::from_value::<Nested>(ragged).unwrap_err();
+        assert!(err.to_string().contains("8 rows instead of"));
+    }
+
+    fn random_transformation(rng: &mut impl Rng, dim_sqr: usize) -> Transformation {
+        let (x, a, b) = (
+            rng.gen_range(0..dim_sqr),
+            rng.gen_range(0..dim_sqr),
+            rng.gen_range(0..dim_sqr),
+        );
+
+        match rng.gen_range(0..8) {
+            0 => Transformation::Transpose,
+            1 => Transformation::Rotate90,
+            2 => Transformation::Rotate270,
+            3 => Transformation::MirrorHorizontal,
+            4 => Transformation::SwapRowsWithinBand { band: x, a, b },
+            5 => Transformation::SwapBands { a, b },
+            6 => Transformation::SwapColsWithinStack { stack: x, a, b },
+            _ => Transformation::SwapStacks { a, b },
+        }
+    }
+
+    #[test]
+    fn test_transformations() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let value =
+            |sudoku: &Sudoku, (row, col)| sudoku.get_grid_value(Pos::from_row_col(row, col));
+
+        assert_eq!(value(&sudoku.transpose(), (1, 0)), value(&sudoku, (0, 1)));
+        assert_eq!(value(&sudoku.rotate90(), (0, 0)), value(&sudoku, (8, 0)));
+        assert_eq!(value(&sudoku.rotate90(), (0, 8)), value(&sudoku, (0, 0)));
+        assert_eq!(
+            value(&sudoku.mirror_horizontal(), (2, 0)),
+            value(&sudoku, (2, 8))
+        );
+        assert_eq!(
+            value(&sudoku.swap_rows_within_band(1, 0, 2), (3, 4)),
+            value(&sudoku, (5, 4))
+        );
+        assert_eq!(
+            value(&sudoku.swap_bands(0, 2), (7, 1)),
+            value(&sudoku, (1, 1))
+        );
+        assert_eq!(
+            value(&sudoku.swap_cols_within_stack(2, 1, 0), (4, 6)),
+            value(&sudoku, (4, 7))
+        );
+        assert_eq!(
+            value(&sudoku.swap_stacks(1, 0), (0, 4)),
+            value(&sudoku, (0, 1))
+        );
+
+        // The givens move along with their cells
+        assert_eq!(
+            format!("{:#}", sudoku.transpose().transpose()),
+            format!("{:#}", sudoku)
+        );
+        assert_eq!(
+            sudoku
+                .transform_all(&[Transformation::Rotate90; 4])
+                .grid_to_string(),
+            FLAT_GRID
+        );
+    }
+
+    #[test]
+    fn test_transformations_random() {
+        let mut rng = rand::thread_rng();
+
+        for raw in [SOLVED_GRID, HEX_SOLVED_GRID, FLAT_GRID] {
+            let sudoku = raw.parse::<Sudoku>().unwrap();
+
+            for _ in 0..100 {
+                let sequence = (0..rng.gen_range(1..10))
+                    .map(|_| random_transformation(&mut rng, sudoku.dim_sqr))
+                    .collect::<Vec<_>>();
+                let transformed = sudoku.transform_all(&sequence);
+
+                assert!(transformed.is_valid(None), "{:?}", sequence);
+                assert_eq!(transformed.is_solved(), sudoku.is_solved());
+                assert_eq!(transformed.clue_count(), sudoku.clue_count());
+
+                let restored = transformed.transform_all(&Transformation::inverse_of(&sequence));
+                assert_eq!(restored.grid_to_string(), raw, "{:?}", sequence);
+                assert_eq!(format!("{:#}", restored), format!("{:#}", sudoku));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shuffle() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        use crate::{dlx::DlxSolver, solver::SudokuSolver};
+
+        let puzzle = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let solution = SOLVED_GRID.parse::<Sudoku>().unwrap();
+
+        for seed in 0..20 {
+            let shuffled = puzzle.shuffle(&mut StdRng::seed_from_u64(seed));
+            assert_eq!(shuffled, puzzle.shuffle(&mut StdRng::seed_from_u64(seed)));
+            assert!(shuffled.is_valid(None));
+            assert_eq!(shuffled.clue_count(), puzzle.clue_count());
+            assert!(shuffled.is_equivalent_to(&puzzle));
+
+            let mut solver = DlxSolver::new(shuffled).unwrap();
+            assert!(solver.solve().0);
+            assert_eq!(
+                solver.get_sudoku().grid_to_string(),
+                solution
+                    .shuffle(&mut StdRng::seed_from_u64(seed))
+                    .grid_to_string()
+            );
+        }
+
+        let hex = HEX_SOLVED_GRID.parse::<Sudoku>().unwrap();
+        let shuffled = hex.shuffle(&mut StdRng::seed_from_u64(7));
+        assert!(shuffled.is_valid(None) && shuffled.is_solved());
+        assert_ne!(shuffled, hex);
+    }
+
+    #[test]
+    fn test_pos() {
+        let pos = Pos::from_row_col(4, 7);
+        assert_eq!((pos.row(), pos.col()), (4, 7));
+        assert_eq!(pos.index(9), 43);
+        assert_eq!(Pos::from_index(43, 9), pos);
+        assert_eq!(pos.box_index(3), 5);
+        assert_eq!(Pos::from_row_col(8, 0).box_index(3), 6);
+        assert_eq!(Pos::from_row_col(5, 11).box_index(4), 6);
+
+        // Squares enumerate their cells in row-major order
+        assert_eq!(Pos::from_box(5, 0, 3), Pos::from_row_col(3, 6));
+        assert_eq!(Pos::from_box(5, 4, 3), pos);
+        for pos in Pos::all(16) {
+            let k = (pos.row() % 4) * 4 + pos.col() % 4;
+            assert_eq!(Pos::from_box(pos.box_index(4), k, 4), pos);
+        }
+
+        let all = Pos::all(9).collect::<Vec<_>>();
+        assert_eq!(all.len(), 81);
+        assert!(all.is_sorted());
+        assert!(all.iter().enumerate().all(|(k, pos)| pos.index(9) == k));
+
+        assert_eq!(pos.to_string(), "(4, 7)");
+        assert_eq!(serde_json::to_string(&pos).unwrap(), "[4,7]");
+        assert_eq!(serde_json::from_str::<Pos>("[4,7]").unwrap(), pos);
+    }
+
+    #[test]
+    fn test_peers() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+
+        for pos in [(0, 0), (4, 4), (8, 3)].map(Pos::from) {
+            let peers = sudoku.peers(pos);
+
+            assert_eq!(peers.len(), 20);
+            assert!(!peers.contains(&pos));
+            assert!(peers.is_sorted());
+        }
+
+        assert_eq!(
+            &sudoku.peers(Pos::from_row_col(4, 4))[..6],
+            &[(0, 4), (1, 4), (2, 4), (3, 3), (3, 4), (3, 5)].map(Pos::from)
+        );
+        assert!(std::ptr::eq(
+            sudoku.peers(Pos::from_row_col(0, 0)),
+            SOLVED_GRID
+                .parse::<Sudoku>()
+                .unwrap()
+                .peers(Pos::from_row_col(0, 0))
+        ));
+
+        let hex = HEX_UNSOLVED_GRID.parse::<Sudoku>().unwrap();
+        assert_eq!(hex.peers(Pos::from_row_col(15, 15)).len(), 39);
+        let small = "1030040220404003".parse::<Sudoku>().unwrap();
+        assert_eq!(
+            small.peers(Pos::from_row_col(1, 2)),
+            &[(0, 2), (0, 3), (1, 0), (1, 1), (1, 3), (2, 2), (3, 2)].map(Pos::from)
+        );
+
+        // The cells on the diagonals of an X-Sudoku also see the rest of their diagonals
+        let x = sudoku.with_variant(Variant::X);
+        assert_eq!(x.peers(Pos::from_row_col(4, 4)).len(), 32);
+        assert_eq!(x.peers(Pos::from_row_col(0, 0)).len(), 26);
+        assert!(x
+            .peers(Pos::from_row_col(0, 0))
+            .contains(&Pos::from_row_col(8, 8)));
+        assert_eq!(x.peers(Pos::from_row_col(0, 1)).len(), 20);
+    }
+
+    #[test]
+    fn test_given_protection() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+
+        assert!(
+            sudoku.is_given(Pos::from_row_col(0, 0)) && !sudoku.is_given(Pos::from_row_col(0, 2))
+        );
+        assert!(!sudoku.is_given(Pos::from_row_col(9, 0)));
+
+        // Rewriting a given with its own value is allowed
+        assert_eq!(
+            sudoku.try_set_grid_value(Pos::from_row_col(0, 0), 5),
+            Ok(())
+        );
+        assert_eq!(
+            sudoku.try_set_grid_value(Pos::from_row_col(0, 1), 4),
+            Err(SudokuError::GivenCell { row: 0, col: 1 })
+        );
+        assert_eq!(
+            sudoku.try_set_grid_value(Pos::from_row_col(0, 2), 4),
+            Ok(())
+        );
+        assert_eq!(sudoku.get_grid_value(Pos::from_row_col(0, 1)), 3);
+
+        let solved = SOLVED_GRID.parse::<Sudoku>().unwrap();
+        let mut altered = solved.as_flat().to_vec();
+        altered.swap(0, 1);
+
+        assert_eq!(
+            sudoku.try_set_solution(&altered),
+            Err(SudokuError::GivenCell { row: 0, col: 0 })
+        );
+        assert_eq!(sudoku.get_grid_value(Pos::from_row_col(0, 2)), 4);
+        assert_eq!(sudoku.try_set_solution(solved.as_flat()), Ok(()));
+        assert_eq!(sudoku, solved);
+    }
+
+    #[test]
+    #[should_panic(expected = "given cell")]
+    #[cfg(debug_assertions)]
+    fn test_given_overwrite_debug_assert() {
+        let mut sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        sudoku.set_grid_value(Pos::from_row_col(0, 0), 1);
+    }
+
+    #[test]
+    fn test_diff() {
+        let solution = SOLVED_GRID.parse::<Sudoku>().unwrap();
+        let mut raw = SOLVED_GRID.as_bytes().to_vec();
+        for k in [0, 40, 80] {
+            raw[k] = b'0';
+        }
+        let puzzle = String::from_utf8(raw.clone())
+            .unwrap()
+            .parse::<Sudoku>()
+            .unwrap();
+
+        let filled = vec![
+            CellDiff {
+                pos: Pos::from_row_col(0, 0),
+                from: 0,
+                to: 5,
+            },
+            CellDiff {
+                pos: Pos::from_row_col(4, 4),
+                from: 0,
+                to: 5,
+            },
+            CellDiff {
+                pos: Pos::from_row_col(8, 8),
+                from: 0,
+                to: 9,
+            },
+        ];
+        assert_eq!(puzzle.diff(&solution), filled);
+        assert_eq!(Sudoku::filled_by_solver(&solution, &puzzle), filled);
+        assert!(solution.diff(&solution).is_empty());
+
+        // Altered givens are only listed by the plain diff
+        raw[1] = b'4';
+        let puzzle = String::from_utf8(raw).unwrap().parse::<Sudoku>().unwrap();
+        let diff = puzzle.diff(&solution);
+        assert_eq!(diff.len(), 4);
+        assert_eq!(
+            diff[1],
+            CellDiff {
+                pos: Pos::from_row_col(0, 1),
+                from: 4,
+                to: 3
+            }
+        );
+        assert_eq!(Sudoku::filled_by_solver(&solution, &puzzle), filled);
+    }
+
+    #[test]
+    fn test_digest() {
+        let sudoku = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let dotted = FLAT_GRID.replace('0', ".").parse::<Sudoku>().unwrap();
+        let pretty = sudoku.to_pretty_string().parse::<Sudoku>().unwrap();
+
+        assert_eq!(dotted.digest(), sudoku.digest());
+        assert_eq!(pretty.digest(), sudoku.digest());
+        assert_eq!(sudoku.clone().digest(), sudoku.digest());
+        // Pinned to catch accidental changes of the stable hash
+        assert_eq!(
+            "0".repeat(16).parse::<Sudoku>().unwrap().digest(),
+            0x129d_3d1e_1e96_bdd3
+        );
+
+        assert_ne!(sudoku.transpose().digest(), sudoku.digest());
+        assert_ne!(
+            SOLVED_GRID.parse::<Sudoku>().unwrap().digest(),
+            sudoku.digest()
+        );
+        // The size is hashed as well, even if the cells of the grids are of the same length
+        assert_ne!(
+            "0".repeat(16).parse::<Sudoku>().unwrap().digest(),
+            "0".repeat(81).parse::<Sudoku>().unwrap().digest()
+        );
+    }
+
+    #[test]
+    fn test_eq_hash() {
+        let puzzle = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let solved = SOLVED_GRID.parse::<Sudoku>().unwrap();
+        let mut filled = puzzle.clone();
+        filled.set_solution(solved.as_flat());
+
+        // Equal despite the differing givens
+        assert_eq!(filled, solved);
+        assert_ne!(puzzle, solved);
+        assert_eq!(
+            HashSet::from([puzzle.clone(), filled, solved.clone()]).len(),
+            2
+        );
+
+        assert!(solved.is_solution_of(&puzzle));
+        assert!(solved.is_solution_of(&solved));
+        assert!(!puzzle.is_solution_of(&puzzle));
+        assert!(!solved.is_solution_of(&"1030040220404003".parse().unwrap()));
+
+        let relabeled = solved.relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        assert!(relabeled.is_valid(None) && relabeled.is_solved());
+        assert!(!relabeled.is_solution_of(&puzzle));
+
+        let invalid = format!("3{}", &SOLVED_GRID[1..]).parse::<Sudoku>().unwrap();
+        assert!(!invalid.is_solution_of(&FLAT_GRID.replacen('5', "0", 1).parse().unwrap()));
+    }
+
+    #[test]
+    fn test_relabel() {
+        let sudoku = SOLVED_GRID.parse::<Sudoku>().unwrap();
+        let relabeled = sudoku.relabel(&[2, 3, 4, 5, 6, 7, 8, 9, 1]).unwrap();
+
+        assert!(relabeled.is_valid(None) && relabeled.is_solved());
+        assert_eq!(&relabeled.grid_to_string()[..9], "645789123");
+        assert_eq!(
+            relabeled
+                .relabel(&[9, 1, 2, 3, 4, 5, 6, 7, 8])
+                .unwrap()
+                .grid_to_string(),
+            SOLVED_GRID
+        );
+
+        let unsolved = FLAT_GRID.parse::<Sudoku>().unwrap();
+        let relabeled = unsolved.relabel(&[9, 8, 7, 6, 5, 4, 3, 2, 1]).unwrap();
+        assert_eq!(&relabeled.grid_to_string()[..9], "570030000");
+        assert_eq!(
+            format!("{:#}", relabeled).matches('[').count(),
+            unsolved.clue_count()
+        );
+
+        for perm in [[1, 2, 3, 4, 5, 6, 7, 8, 8], [0, 1, 2, 3, 4, 5, 6, 7, 8]] {
+            assert_eq!(
+                sudoku.relabel(&perm).unwrap_err(),
+                SudokuError::InvalidPermutation {
+                    perm: perm.to_vec(),
+                    max: 9
+                }
+            );
+        }
+
+        let small = "1030040220404003".parse::<Sudoku>().unwrap();
+        assert!(small.relabel(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).is_err());
+    }
+
+    #[test]
+    fn test_is_equivalent_to() {
+        // Consecutive puzzles of the 'sudoku17' collection, which only contains essentially
+        // different puzzles, differing in 3 of the clues
+        const SUDOKU17: [&str; 2] = [
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+            "000000010400000000020000000000050604008000300001090000300400200050100000000807000",
+        ];
+        let mut rng = rand::thread_rng();
+        let puzzle = SUDOKU17[0].parse::<Sudoku>().unwrap();
+        let near_miss = SUDOKU17[1].parse::<Sudoku>().unwrap();
+
+        assert!(puzzle.is_equivalent_to(&puzzle));
+        assert!(!puzzle.is_equivalent_to(&near_miss));
+        assert!(!near_miss.is_equivalent_to(&puzzle));
+
+        for _ in 0..10 {
+            let mut perm = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+            rand::seq::SliceRandom::shuffle(&mut perm[..], &mut rng);
+            let sequence = (0..rng.gen_range(1..10))
+                .map(|_| random_transformation(&mut rng, 3))
+                .collect::<Vec<_>>();
+            let equivalent = puzzle.relabel(&perm).unwrap().transform_all(&sequence);
+
+            assert!(
+                puzzle.is_equivalent_to(&equivalent),
+                "{:?} {:?}",
+                perm,
+                sequence
+            );
+            assert!(equivalent.is_equivalent_to(&puzzle));
+            assert!(!near_miss.is_equivalent_to(&equivalent));
+        }
+
+        let solved = SOLVED_GRID.parse::<Sudoku>().unwrap();
+        assert!(!solved.is_equivalent_to(&puzzle));
+        assert!(solved.is_equivalent_to(
+            &solved
+                .rotate90()
+                .relabel(&[2, 1, 3, 4, 5, 6, 7, 9, 8])
+                .unwrap()
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transformation_out_of_range() {
+        FLAT_GRID.parse::<Sudoku>().unwrap().swap_bands(0, 3);
+    }
+
+    #[test]
+    fn test_parse_invalid_length() {
+        for (raw, cells) in [
+            (FLAT_GRID[..80].to_owned(), 80),
+            (format!("{}0", FLAT_GRID), 82),
+            (format!("{}1234", FLAT_GRID), 85),
+        ] {
+            assert_eq!(
+                raw.parse::<Sudoku>().unwrap_err(),
+                SudokuError::WrongCellCount { cells }
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let grid = FLAT_GRID.parse::<Sudoku>().unwrap().clone_grid();
+        let sudoku = Sudoku::from_grid(grid.clone()).unwrap();
+        assert_eq!(sudoku.clone_grid(), grid);
+        assert_eq!(sudoku.grid_to_string(), FLAT_GRID);
+        assert_eq!(sudoku.clue_count(), 30);
+
+        let err = Sudoku::from_grid(grid[..8].to_vec()).unwrap_err();
+        assert_eq!(err, SudokuError::WrongRowCount { rows: 8 });
+
+        let mut ragged = grid.clone();
+        ragged[4].push(0);
+        let err = Sudoku::from_grid(ragged).unwrap_err();
+        assert_eq!(
+            err,
+            SudokuError::WrongRowLength {
+                row: 4,
+                cells: 10,
+                expected: 9
+            }
+        );
+
+        let mut out_of_range = grid.clone();
+        out_of_range[2][7] = 10;
+        let err = Sudoku::from_grid(out_of_range).unwrap_err();
+        assert_eq!(
+            err,
+            SudokuError::ValueOutOfRange {
+                row: 2,
+                col: 7,
+                value: 10,
+                max: 9
+            }
+        );
+    }
 }
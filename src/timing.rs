@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+/// Measures both the elapsed wall-clock time and the CPU time consumed by the current thread.
+/// On platforms where the thread CPU time is unavailable the wall-clock time is used instead.
+#[derive(Debug)]
+pub struct Stopwatch {
+    wall_start: Instant,
+    cpu_start: Option<Duration>,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Self {
+            wall_start: Instant::now(),
+            cpu_start: thread_cpu_time(),
+        }
+    }
+
+    /// Returns the elapsed `(wall, cpu)` times since the stopwatch was started.
+    pub fn elapsed(&self) -> (Duration, Duration) {
+        let wall = self.wall_start.elapsed();
+        let cpu = match (self.cpu_start, thread_cpu_time()) {
+            (Some(start), Some(end)) => end.saturating_sub(start),
+            _ => wall,
+        };
+
+        (wall, cpu)
+    }
+}
+
+/// Returns the CPU time consumed by the current thread, or `None` if it can't be measured.
+#[cfg(unix)]
+pub fn thread_cpu_time() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // SAFETY: `ts` is a valid pointer to a `timespec` for the duration of the call
+    match unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } {
+        0 => Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)),
+        _ => None,
+    }
+}
+
+/// Returns the CPU time consumed by the current thread, or `None` if it can't be measured.
+#[cfg(not(unix))]
+pub fn thread_cpu_time() -> Option<Duration> {
+    None
+}
@@ -0,0 +1,50 @@
+use std::{
+    io::{Error, ErrorKind, Result},
+    path::Path,
+    sync::Arc,
+};
+
+use rustls::{
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+
+/// Loads the PEM encoded certificate chain and private key into a rustls `ServerConfig` for the
+/// TLS listener. Failures to read or parse the files are returned as readable errors naming the
+/// offending file.
+pub fn server_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+        .map_err(|e| {
+            invalid_data(format!(
+                "Failed to load the TLS certificate chain from {}: {}",
+                cert_path.display(),
+                e
+            ))
+        })?;
+
+    if certs.is_empty() {
+        return Err(invalid_data(format!(
+            "No TLS certificates found in {}",
+            cert_path.display()
+        )));
+    }
+
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+        invalid_data(format!(
+            "Failed to load the TLS private key from {}: {}",
+            key_path.display(),
+            e
+        ))
+    })?;
+
+    ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+        .map_err(|e| invalid_data(format!("Invalid TLS certificate or private key: {}", e)))
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
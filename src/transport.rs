@@ -0,0 +1,132 @@
+use std::{
+    env, fmt,
+    io::{Error, ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+/// Transport of the listening socket, resolved from the `MODE`, `PORT`, and `UNIX_SOCKET`
+/// environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transport {
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    /// Unix domain socket at the given path, e.g. for a local reverse proxy.
+    Unix(PathBuf),
+}
+
+impl Transport {
+    pub fn from_env() -> Result<Self> {
+        let var = |key: &str| env::var(key).ok().filter(|value| !value.is_empty());
+
+        Self::new(var("MODE"), var("PORT"), var("UNIX_SOCKET"))
+    }
+
+    /// Resolves the transport, the TCP socket defaulting to `127.0.0.1:8080`. Returns an
+    /// `ErrorKind::InvalidInput` error if both a port and a Unix socket are configured, or if
+    /// the chosen transport isn't usable.
+    pub fn new(
+        mode: Option<String>,
+        port: Option<String>,
+        unix_socket: Option<String>,
+    ) -> Result<Self> {
+        match (port, unix_socket) {
+            (Some(_), Some(_)) => Err(invalid_input(
+                "Both PORT and UNIX_SOCKET are set, only one transport can be used at a time",
+            )),
+            (None, Some(path)) if cfg!(unix) => Ok(Self::Unix(PathBuf::from(path))),
+            (None, Some(_)) => Err(invalid_input(
+                "UNIX_SOCKET is set, but Unix domain sockets aren't supported on this platform",
+            )),
+            (port, None) => {
+                let host = match mode.as_deref() {
+                    Some("prod") => String::from("0.0.0.0"),
+                    _ => String::from("127.0.0.1"),
+                };
+                let port = port
+                    .as_deref()
+                    .unwrap_or("8080")
+                    .parse::<u16>()
+                    .map_err(|e| {
+                        invalid_input(&format!("Failed to parse the host port number: {}", e))
+                    })?;
+
+                Ok(Self::Tcp { host, port })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transport::Tcp { host, port } => write!(f, "TCP socket {}:{}", host, port),
+            Transport::Unix(path) => write!(f, "Unix domain socket {}", path.display()),
+        }
+    }
+}
+
+fn invalid_input(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+/// Removes the socket file left behind by a previous run, which would otherwise make the binding
+/// fail. Refuses to remove anything else than a socket at the path.
+#[cfg(unix)]
+pub fn remove_stale_socket(path: &Path) -> Result<()> {
+    use std::{fs, os::unix::fs::FileTypeExt};
+
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(invalid_input(&format!(
+            "UNIX_SOCKET path {} already exists and isn't a socket",
+            path.display()
+        ))),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tcp_transport() {
+        assert_eq!(
+            Transport::new(None, None, None).unwrap(),
+            Transport::Tcp {
+                host: String::from("127.0.0.1"),
+                port: 8080
+            }
+        );
+        assert_eq!(
+            Transport::new(Some(String::from("prod")), Some(String::from("80")), None).unwrap(),
+            Transport::Tcp {
+                host: String::from("0.0.0.0"),
+                port: 80
+            }
+        );
+
+        let err = Transport::new(None, Some(String::from("port")), None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_transport() {
+        assert_eq!(
+            Transport::new(None, None, Some(String::from("/tmp/pure-be.sock"))).unwrap(),
+            Transport::Unix(PathBuf::from("/tmp/pure-be.sock"))
+        );
+
+        let err = Transport::new(
+            None,
+            Some(String::from("8080")),
+            Some(String::from("/tmp/pure-be.sock")),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use actix_web::{get, rt, web, HttpRequest, HttpResponse};
+use actix_ws::{Message, MessageStream, Session};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::{debug, error, field, info, Instrument, Span};
+
+use crate::{
+    controller::{self, Entry, EntryResult, ErrorResponse},
+    error::ApiError,
+    quota::Quotas,
+    solver::{Outcome, SolveOptions, Solver, SolverType},
+};
+
+// Number of visited nodes between the progress frames
+const PROGRESS_INTERVAL_NODES: u64 = 5_000;
+
+/// Frames streamed to the client of the WebSocket endpoint, tagged with their `type`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Frame {
+    /// Periodic snapshot of the running solver process.
+    Progress {
+        visited_nodes: u64,
+        depth: usize,
+        elapsed_us: u128,
+    },
+    /// Final frame of a solved entry, boxed as the result is far larger than the other frames.
+    Result(Box<EntryResult>),
+    /// Final frame of a failed entry.
+    Error(ErrorResponse),
+}
+
+/// WebSocket endpoint solving a single `Entry` sent by the client as a text message, streaming
+/// the progress of the solver process followed by a final result or error frame.
+#[get("/solve/ws")]
+pub async fn solve_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    quotas: Option<web::Data<Quotas>>,
+) -> actix_web::Result<HttpResponse> {
+    // Each session solves a single entry, consumed before the upgrade
+    Quotas::consume_if_enabled(quotas.as_ref(), &req, 1)?;
+
+    let (response, session, stream) = actix_ws::handle(&req, body)?;
+    // The session outlives the handler, so the request span is attached to it explicitly
+    rt::spawn(run_session(session, stream).instrument(Span::current()));
+
+    Ok(response)
+}
+
+async fn run_session(mut session: Session, mut stream: MessageStream) {
+    let Some(entry) = receive_entry(&mut session, &mut stream).await else {
+        debug!("WebSocket client disconnected before sending an entry");
+        return;
+    };
+
+    let final_frame = match entry {
+        Ok(entry) => match entry.to_solver(SolverType::default(), SolveOptions::default()) {
+            Ok(solver) => solve(&mut session, &mut stream, solver, &entry).await,
+            Err(e) => Some(Frame::Error(ErrorResponse::from(&e))),
+        },
+        Err(e) => Some(Frame::Error(ErrorResponse::from(&ApiError::InvalidFormat(
+            format!("Malformed entry: {}", e),
+        )))),
+    };
+
+    if let Some(frame) = final_frame {
+        let _ = send_frame(&mut session, &frame).await;
+        let _ = session.close(None).await;
+    }
+}
+
+/// Waits for the first text message and parses it into an `Entry`. Returns `None` if the client
+/// disconnects before sending one.
+async fn receive_entry(
+    session: &mut Session,
+    stream: &mut MessageStream,
+) -> Option<Result<Entry, serde_json::Error>> {
+    while let Some(Ok(msg)) = stream.recv().await {
+        match msg {
+            Message::Text(text) => return Some(serde_json::from_str::<Entry>(&text)),
+            Message::Ping(bytes) => {
+                let _ = session.pong(&bytes).await;
+            }
+            Message::Close(_) => return None,
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Runs the solver on a blocking thread while streaming its progress to the client. The solving
+/// process is cancelled if the client disconnects, in which case no final frame is returned.
+async fn solve(
+    session: &mut Session,
+    stream: &mut MessageStream,
+    mut solver: Solver,
+    entry: &Entry,
+) -> Option<Frame> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let start = Instant::now();
+
+    let span = controller::solve_span(0, &solver);
+    let hook_cancelled = cancelled.clone();
+    solver.set_progress_hook(
+        Box::new(move |progress| {
+            let frame = Frame::Progress {
+                visited_nodes: progress.visited_nodes,
+                depth: progress.depth,
+                elapsed_us: start.elapsed().as_micros(),
+            };
+
+            tx.send(frame).is_ok() && !hook_cancelled.load(Ordering::Relaxed)
+        }),
+        PROGRESS_INTERVAL_NODES,
+    );
+
+    let solver_span = span.clone();
+    let mut task = rt::task::spawn_blocking(move || {
+        let outcome = solver_span.in_scope(|| solver.solve());
+        (outcome, solver)
+    });
+    let mut connected = true;
+
+    let joined = loop {
+        tokio::select! {
+            Some(frame) = rx.recv() => {
+                if send_frame(session, &frame).await.is_err() {
+                    cancelled.store(true, Ordering::Relaxed);
+                    connected = false;
+                }
+            }
+            msg = stream.recv(), if connected => match msg {
+                Some(Ok(Message::Ping(bytes))) => {
+                    let _ = session.pong(&bytes).await;
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                    info!("WebSocket client disconnected, cancelling the solver");
+                    cancelled.store(true, Ordering::Relaxed);
+                    connected = false;
+                }
+                _ => (),
+            },
+            joined = &mut task => break joined,
+        }
+    };
+
+    // Flush the progress reported right before the solver finished
+    while let Ok(frame) = rx.try_recv() {
+        if connected && send_frame(session, &frame).await.is_err() {
+            connected = false;
+        }
+    }
+
+    let (outcome, solver) = match joined {
+        Ok(joined) => joined,
+        Err(e) => {
+            error!(
+                "Internal error: Solver thread of the WebSocket session failed: {}",
+                e
+            );
+            return Some(Frame::Error(ErrorResponse::from(&ApiError::Internal(
+                String::from("Internal error while solving the puzzle"),
+            ))));
+        }
+    };
+
+    span.record("outcome", field::debug(outcome));
+
+    if !connected {
+        return None;
+    }
+
+    match outcome {
+        Outcome::Solved | Outcome::Incomplete => Some(Frame::Result(Box::new(EntryResult::new(
+            0,
+            entry.grid().clone(),
+            &solver,
+        )))),
+        Outcome::Unsolvable => Some(Frame::Error(ErrorResponse::from(&ApiError::Unsolvable {
+            index: 0,
+        }))),
+        Outcome::InternalError => Some(Frame::Error(ErrorResponse::from(&ApiError::Internal(
+            String::from("Internal error while solving the puzzle"),
+        )))),
+        Outcome::Cancelled => Some(Frame::Error(ErrorResponse::from(&ApiError::Cancelled {
+            index: 0,
+        }))),
+        Outcome::LimitReached(limit) => {
+            Some(Frame::Error(ErrorResponse::from(&ApiError::LimitReached {
+                index: 0,
+                limit,
+            })))
+        }
+    }
+}
+
+async fn send_frame(session: &mut Session, frame: &Frame) -> Result<(), actix_ws::Closed> {
+    // Serializing the frames can't fail as they only contain string keys
+    session
+        .text(serde_json::to_string(frame).unwrap_or_default())
+        .await
+}
@@ -1,14 +1,46 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     fs::File,
     io::{BufRead, BufReader},
+    sync::Mutex,
+    time::Duration,
 };
 
-use actix_web::{http::StatusCode, test, App};
+use actix_governor::{Governor, GovernorConfigBuilder};
+use actix_web::{
+    http::{
+        header::{ContentType, AUTHORIZATION, RETRY_AFTER},
+        StatusCode,
+    },
+    middleware::from_fn,
+    test, web, App,
+};
+use futures_util::{SinkExt, StreamExt};
 use pure_be::{
-    controller::{self, Entry, ErrorResponse, SuccessResponse},
-    sudoku::Sudoku,
+    admin::{self, AdminToken},
+    controller::{
+        self, Entry, EntryOptions, ErrorResponse, GridInput, GridString, Response, SuccessResponse,
+        V1Response, ValidationResponse,
+    },
+    dlx::ColumnSelection,
+    limiter::{self, RateLimitExtractor, RateLimitState, RateLimitStats, RateLimits},
+    quota::{self, MemoryQuotaStore, QuotaUsage, Quotas, API_KEY_HEADER},
+    shutdown::Shutdown,
+    solver::{has_unique_items, SolveOptions, SolverType, Uniqueness},
+    sudoku::{OutputFormat, Pos, Sudoku, Unit, Variant},
+    ws,
 };
 use rand::Rng;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer, Registry,
+};
 
 /// Sends a POST request with randomly picked Sudokus to the `/solve` endpoint with the
 /// `solver_type` parameter set to `dfs` to test the AC-3 + enhanced DFS implementation.
@@ -16,7 +48,7 @@ use rand::Rng;
 async fn test_dfs_solver() {
     let test_app = test::init_service(App::new().service(controller::solve)).await;
     let unsolved = get_unsolved();
-    let payload = into_payload(unsolved, Some(String::from("dfs")));
+    let payload = into_payload(unsolved.clone(), Some(String::from("dfs")));
 
     let req = test::TestRequest::post()
         .uri("/solve")
@@ -24,11 +56,7 @@ async fn test_dfs_solver() {
         .to_request();
     let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
 
-    for grid_str in res.get_solved() {
-        let sudoku = Sudoku::new(grid_str).unwrap();
-        assert!(sudoku.is_valid(None));
-        assert!(sudoku.is_solved());
-    }
+    assert_solved(&res, &unsolved, SolverType::Dfs);
 }
 
 /// Sends a POST request with randomly picked Sudokus to the `/solve` endpoint with the
@@ -37,7 +65,7 @@ async fn test_dfs_solver() {
 async fn test_dlx_solver() {
     let test_app = test::init_service(App::new().service(controller::solve)).await;
     let unsolved = get_unsolved();
-    let payload = into_payload(unsolved, Some(String::from("dlx")));
+    let payload = into_payload(unsolved.clone(), Some(String::from("dlx")));
 
     let req = test::TestRequest::post()
         .uri("/solve")
@@ -45,17 +73,41 @@ async fn test_dlx_solver() {
         .to_request();
     let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
 
-    for grid_str in res.get_solved() {
-        let sudoku = Sudoku::new(grid_str).unwrap();
-        assert!(sudoku.is_valid(None));
-        assert!(sudoku.is_solved());
+    assert_solved(&res, &unsolved, SolverType::Dlx);
+}
+
+/// Sends a POST request with randomly picked Sudokus to the `/solve` endpoint with the
+/// `solver_type` parameter set to `auto` to test that the chosen backend is reported. The sparse
+/// 17-clue puzzles of the collection are expected to be solved with DLX.
+#[actix_web::test]
+async fn test_auto_solver() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let unsolved = get_unsolved();
+    let payload = into_payload(unsolved.clone(), Some(String::from("auto")));
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+
+    assert_solved(&res, &unsolved, SolverType::Dlx);
+
+    for result in res.get_results() {
+        assert_eq!(result.requested_solver(), SolverType::Auto);
     }
 }
 
-/// Sends a POST request with syntactically malformed contents to test the regex validators.
+/// Sends a POST request with syntactically malformed grid strings to test that they are rejected
+/// while deserializing the payload, naming the index of the offending entry.
 #[actix_web::test]
 async fn test_malformed_data() {
-    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let test_app = test::init_service(
+        App::new()
+            .app_data(controller::json_config(4096))
+            .service(controller::solve),
+    )
+    .await;
 
     let total_raws = vec![
         "00080905160020000C30000000001000003008A90000000000040040003060B000051000000000000", // Invalid contents
@@ -63,7 +115,9 @@ async fn test_malformed_data() {
     ];
 
     for raw in total_raws {
-        let payload = into_payload(vec![raw.to_owned()], None);
+        let valid =
+            "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+        let payload = serde_json::json!([{ "grid": valid }, { "grid": raw }]);
         let req = test::TestRequest::post()
             .uri("/solve")
             .set_json(payload)
@@ -77,13 +131,15 @@ async fn test_malformed_data() {
         );
 
         let res_body: ErrorResponse = test::read_body_json(res).await;
-        let e_status = res_body.status().unwrap();
+        let e_status = res_body.status();
 
         assert_eq!(
             e_status,
             StatusCode::BAD_REQUEST,
             "Invalid HTTP status code received in the error payload"
         );
+        assert_eq!(res_body.kind(), "invalid_format");
+        assert!(res_body.message().contains("invalid entry 1"));
     }
 }
 
@@ -110,34 +166,1833 @@ async fn test_invalid_grid() {
     );
 
     let res_body: ErrorResponse = test::read_body_json(res).await;
-    let e_status = res_body.status().unwrap();
+    let e_status = res_body.status();
 
     assert_eq!(
         e_status,
         StatusCode::BAD_REQUEST,
         "Invalid HTTP status code received in the error payload"
     );
+    assert_eq!(res_body.kind(), "constraint_violation");
+    assert!(res_body
+        .message()
+        .contains("value 8 repeated in column 0 at (0, 0), (3, 0)"));
+    assert_eq!(res_body.conflicts().len(), 2);
+    assert_eq!(res_body.conflicts()[1].unit, Unit::Square);
+    assert_eq!(
+        res_body.conflicts()[1].cells,
+        vec![Pos::from_row_col(0, 0), Pos::from_row_col(2, 2)]
+    );
 }
 
-fn get_unsolved() -> Vec<String> {
-    static COLLECTION_SIZE: usize = 49150;
-    let mut rng = rand::thread_rng();
+/// Sends a POST request with a Sudoku grid which fulfills the puzzle constraints but contains a
+/// contradiction to test that both solvers report the missing solution with status 422.
+#[actix_web::test]
+async fn test_unsolvable_grid() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
 
-    let file = File::open("./tests/sudoku17")
-        .expect("Failed to open the 'sudoku17' collection file for reading");
-    let lines: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
-    let mut unsolved = Vec::new();
+    let unsolvable_raw =
+        "500020410620000000400010062007230604006000379030090005098006001015300940004100738";
 
-    for _ in 0..3 {
-        let ln = rng.gen_range(0..COLLECTION_SIZE);
-        unsolved.push(lines[ln].to_owned());
+    for solver in ["dfs", "dlx"] {
+        let payload = into_payload(vec![unsolvable_raw.to_owned()], Some(String::from(solver)));
+        let req = test::TestRequest::post()
+            .uri("/solve")
+            .set_json(payload)
+            .to_request();
+        let res = test::call_service(&test_app, req).await;
+
+        assert_eq!(
+            res.status(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Invalid HTTP status code received in the error response"
+        );
+
+        let res_body: ErrorResponse = test::read_body_json(res).await;
+
+        assert_eq!(
+            res_body.status(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Invalid HTTP status code received in the error payload"
+        );
+        assert_eq!(res_body.kind(), "unsolvable");
     }
+}
 
-    unsolved
+/// Sends a POST request with a DFS entry bounded by a tiny node limit to test that the limit is
+/// reported as an error instead of presenting the partially filled grid.
+#[actix_web::test]
+async fn test_node_limit_reached() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let hard = "000002300100500000007000000400000017030800000000000600006470000020000900000010000";
+    let options = EntryOptions {
+        node_limit: Some(10),
+        ..Default::default()
+    };
+    let payload = vec![Entry::with_options(
+        flat(hard),
+        Some(String::from("dfs")),
+        options,
+    )];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    assert_eq!(res_body.kind(), "limit_reached");
+    assert!(res_body.message().contains("node limit"));
 }
 
-fn into_payload(raws: Vec<String>, s_type: Option<String>) -> Vec<Entry> {
-    raws.into_iter()
-        .map(|raw| Entry::new(raw.to_owned(), s_type.clone()))
-        .collect()
+/// Sends a POST request with `propagate_only` set to test that the puzzle resolved by the
+/// propagation is solved as usual, while the other one is returned partially filled with `200`.
+#[actix_web::test]
+async fn test_propagate_only() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let resolved =
+        "000000012008030000000000040120500000000004700060000000507000300000620000000100000";
+    let unresolved =
+        "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+
+    let options = EntryOptions {
+        propagate_only: Some(true),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(resolved), None, options.clone()),
+        Entry::with_options(flat(unresolved), None, options),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(res).await;
+    assert!(body["results"][0]["solved"].is_string());
+    assert_eq!(body["results"][1]["solved"], false);
+
+    let res: SuccessResponse = serde_json::from_value(body).unwrap();
+    let results = res.get_results();
+    assert_eq!(res.get_solved(), [results[0].solved()]);
+    assert_eq!(results[0].solver(), SolverType::Dfs);
+    assert!(results[0].partial().is_none());
+
+    let partial = results[1].partial().unwrap().parse::<Sudoku>().unwrap();
+    let puzzle = unresolved.parse::<Sudoku>().unwrap();
+    assert!(!partial.is_solved());
+    assert!(partial.is_valid(None));
+    assert!(partial.clue_count() > puzzle.clue_count());
+    assert!(Pos::all(9)
+        .filter(|&pos| puzzle.is_given(pos))
+        .all(|pos| partial.get_grid_value(pos) == puzzle.get_grid_value(pos)));
+}
+
+/// Sends a POST request with an empty array of entries to test that the empty batch is rejected
+/// instead of failing inside the solver loop.
+#[actix_web::test]
+async fn test_empty_batch() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(Vec::<Entry>::new())
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(
+        res.status(),
+        StatusCode::BAD_REQUEST,
+        "Invalid HTTP status code received in the error response"
+    );
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+
+    assert_eq!(res_body.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res_body.kind(), "empty_batch");
+    assert!(res_body.message().contains("Empty batch"));
+}
+
+/// Sends a POST request where every entry is individually invalid to test that the batch is
+/// rejected with a structured error instead of reaching the response aggregation.
+#[actix_web::test]
+async fn test_all_invalid_batch() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+
+    let payload = into_payload(
+        vec![
+            "880070000600195000098000060800060003400803001700020006060000280000419005000080079"
+                .to_owned(),
+            "830070000600195000098000060800060003400803001700020006060000280000419005000080079"
+                .to_owned(),
+        ],
+        None,
+    );
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(
+        res.status(),
+        StatusCode::BAD_REQUEST,
+        "Invalid HTTP status code received in the error response"
+    );
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+
+    assert_eq!(res_body.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Sends a POST request mixing a valid puzzle, an invalid grid, and an unsolvable grid in one
+/// payload to test that the failing entries are reported alongside the successful one.
+#[actix_web::test]
+async fn test_partial_success() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+
+    let raws = vec![
+        "000000037002000050010000000000200104000001600300400000700063000000000200000080000"
+            .to_owned(),
+        "830070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .to_owned(),
+        "500020410620000000400010062007230604006000379030090005098006001015300940004100738"
+            .to_owned(),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(into_payload(raws.clone(), None))
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res_body: SuccessResponse = test::read_body_json(res).await;
+
+    assert_solved(&res_body, &raws[..1], SolverType::Dlx);
+
+    let errors = res_body.get_errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].index(), 1);
+    assert_eq!(errors[0].grid(), &GridInput::Flat(flat(&raws[1])));
+    assert_eq!(errors[0].error().status(), StatusCode::BAD_REQUEST);
+    assert_eq!(errors[1].index(), 2);
+    assert_eq!(errors[1].error().status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Without the valid puzzle the whole batch fails, and client errors take precedence
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(into_payload(raws[1..].to_vec(), None))
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    let indices = res_body
+        .errors()
+        .iter()
+        .map(|e| e.index())
+        .collect::<Vec<usize>>();
+
+    assert_eq!(indices, vec![0, 1]);
+    assert_eq!(res_body.kind(), "batch_failed");
+}
+
+/// Sends a POST request with per-entry solve options to test that the applied options are echoed
+/// in the results and that options outside of the allowed ranges are rejected.
+#[actix_web::test]
+async fn test_entry_options() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+
+    let options = EntryOptions {
+        node_limit: Some(100_000),
+        timeout_ms: Some(5000),
+        seed: Some(42),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(raw), None, options),
+        Entry::new(flat(raw), None),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(
+        results[0].options(),
+        SolveOptions {
+            max_solutions: 1,
+            node_limit: Some(100_000),
+            timeout_ms: Some(5000),
+            include_propagated: false,
+            include_candidates: false,
+            check_unique: false,
+            pretty: false,
+            include_diff: false,
+            output_format: OutputFormat::FlatZero,
+            require_unique: false,
+            include_stats: false,
+            seed: Some(42),
+            propagate_only: false,
+            detect_multiple: false,
+            parallel: false,
+            column_selection: ColumnSelection::MinCount,
+        }
+    );
+    assert_eq!(results[1].options(), SolveOptions::default());
+
+    let options = EntryOptions {
+        max_solutions: Some(17),
+        ..Default::default()
+    };
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(vec![Entry::with_options(flat(raw), None, options)])
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+
+    assert_eq!(res_body.kind(), "invalid_options");
+    assert!(res_body.message().contains("max_solutions"));
+}
+
+/// Sends a POST request with a grid known to have exactly two completions and `max_solutions`
+/// above 1 to test that the distinct solutions are returned in place of the single one.
+#[actix_web::test]
+async fn test_multiple_solutions() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+
+    let options = EntryOptions {
+        max_solutions: Some(5),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(raw), None, options),
+        Entry::new(flat(raw), None),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&test_app, req).await;
+    let multiple = &body["results"][0];
+    let single = &body["results"][1];
+
+    assert!(multiple.get("solved").is_none());
+    assert!(single.get("solutions").is_none());
+    assert!(single["solved"].is_string());
+
+    let solutions: Vec<String> = serde_json::from_value(multiple["solutions"].clone()).unwrap();
+    assert_eq!(solutions.len(), 2);
+    assert_ne!(solutions[0], solutions[1]);
+
+    for solution in solutions.iter() {
+        let sudoku = Sudoku::new(solution.clone()).unwrap();
+        assert!(sudoku.is_valid(None));
+        assert!(sudoku.is_solved());
+
+        for (s, r) in solution.chars().zip(raw.chars()) {
+            assert!(r == '0' || s == r);
+        }
+    }
+
+    let res: SuccessResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(res.get_results()[0].solutions(), solutions.as_slice());
+    assert_eq!(res.get_solved()[0], solutions[0]);
+}
+
+/// Sends a POST request with the pencil marks of an improper puzzle ruling out its first solution
+/// to test that the DLX search only tries the marked digits, and that marks leaving no solution or
+/// outside of the grid are reported as errors of their entries.
+#[actix_web::test]
+async fn test_pencil_marks() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+
+    let options = EntryOptions {
+        max_solutions: Some(2),
+        ..Default::default()
+    };
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(vec![Entry::with_options(flat(raw), None, options)])
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let solutions = res.get_results()[0].solutions();
+    let (first, second) = (solutions[0].as_bytes(), solutions[1].as_bytes());
+
+    // The cells the two solutions disagree on are marked with the digits of the second one
+    let marks: BTreeMap<String, Vec<u8>> = (0..81)
+        .filter(|&k| first[k] != second[k])
+        .map(|k| {
+            (
+                format!("r{}c{}", k / 9 + 1, k % 9 + 1),
+                vec![second[k] - b'0'],
+            )
+        })
+        .collect();
+    assert!(!marks.is_empty());
+
+    let marked = |cell: &str, digits: Vec<u8>| {
+        Entry::new(flat(raw), None).with_candidates(BTreeMap::from([(cell.to_owned(), digits)]))
+    };
+    let options = EntryOptions {
+        check_unique: Some(true),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(raw), None, options).with_candidates(marks.clone()),
+        marked("r1c1", vec![]),
+        marked("r10c1", vec![1]),
+        marked("r1c1", vec![0]),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].solved(), solutions[1]);
+    assert_eq!(results[0].solver(), SolverType::Dlx);
+    assert_eq!(results[0].unique(), Uniqueness::Unique);
+
+    let errors = res.get_errors();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].error().kind(), "unsolvable");
+    assert_eq!(errors[1].error().kind(), "invalid_format");
+    assert_eq!(errors[2].error().kind(), "invalid_format");
+
+    // Honored by DLX even if DFS, which can't restrict its search to them, was requested
+    let payload = vec![Entry::new(flat(raw), Some(String::from("dfs"))).with_candidates(marks)];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].solved(), solutions[1]);
+    assert_eq!(results[0].solver(), SolverType::Dlx);
+    assert_eq!(results[0].requested_solver(), SolverType::Dfs);
+}
+
+/// Sends a POST request with an X-Sudoku, none of the classic solutions of which satisfies the
+/// diagonals, as both an X-Sudoku and a classic one, and with a repeated digit on a diagonal only.
+#[actix_web::test]
+async fn test_x_sudoku() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000098006089400100500000000000080400200007000000000000904800600000000030075000";
+    let x_solution =
+        "254367198376189425189542673567491382491238567823756914715924836642813759938675241";
+    // Repeats the 8 of (0, 8) at (8, 0) on the anti-diagonal, which is a valid classic puzzle
+    let conflicting = format!("{}8{}", &raw[..72], &raw[73..]);
+
+    let options = EntryOptions {
+        check_unique: Some(true),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(raw), Some(String::from("dfs")), options).with_variant(Variant::X),
+        Entry::new(flat(raw), None),
+        Entry::new(flat(&conflicting), None).with_variant(Variant::X),
+        Entry::new(flat(&conflicting), None),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results.len(), 3);
+    // Only DLX encodes the diagonals, so it's used even if DFS was requested
+    assert_eq!(results[0].solved(), x_solution);
+    assert_eq!(results[0].solver(), SolverType::Dlx);
+    assert_eq!(results[0].unique(), Uniqueness::Unique);
+    assert_ne!(results[1].solved(), x_solution);
+    assert_eq!(results[2].index(), 3);
+
+    let errors = res.get_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].index(), 2);
+    assert_eq!(errors[0].error().kind(), "constraint_violation");
+    assert_eq!(errors[0].error().conflicts().len(), 1);
+    assert_eq!(errors[0].error().conflicts()[0].unit, Unit::Diagonal);
+    assert_eq!(
+        errors[0].error().conflicts()[0].cells,
+        vec![Pos::from_row_col(0, 8), Pos::from_row_col(8, 0)]
+    );
+}
+
+/// Sends a POST request with already solved grids, which are returned as is without searching
+/// unless the pencil marks rule out any of their digits.
+#[actix_web::test]
+async fn test_already_solved() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let solved =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+    let unsolved =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    let options = EntryOptions {
+        check_unique: Some(true),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(solved), Some(String::from("dlx")), options),
+        Entry::new(flat(solved), Some(String::from("dfs"))),
+        Entry::new(flat(unsolved), None),
+        Entry::new(flat(solved), None)
+            .with_candidates(BTreeMap::from([(String::from("r1c1"), vec![1, 2, 3])])),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results.len(), 3);
+    for result in &results[..2] {
+        assert_eq!(result.solved(), solved);
+        assert!(result.already_solved());
+        assert_eq!(result.visited_nodes(), 0);
+    }
+    assert_eq!(results[0].solver(), SolverType::Dlx);
+    assert_eq!(results[0].unique(), Uniqueness::Unique);
+    assert_eq!(results[1].solver(), SolverType::Dfs);
+    assert_eq!(results[1].unique(), Uniqueness::Unknown);
+    assert_eq!(results[2].solved(), solved);
+    assert!(!results[2].already_solved());
+    assert!(results[2].visited_nodes() > 0);
+
+    // The marks of the client rule out the 5 of (0, 0)
+    let errors = res.get_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].index(), 3);
+    assert_eq!(errors[0].error().kind(), "unsolvable");
+}
+
+/// Sends a POST request with a proper and an improper puzzle to test the uniqueness flag, which
+/// is reported as `unknown` unless the entry asks for it with `check_unique`.
+#[actix_web::test]
+async fn test_unique_flag() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let proper =
+        "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let improper =
+        "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+
+    let options = EntryOptions {
+        check_unique: Some(true),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(proper), None, options.clone()),
+        Entry::with_options(flat(improper), Some(String::from("dfs")), options),
+        Entry::new(flat(proper), None),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&test_app, req).await;
+
+    assert_eq!(body["results"][0]["unique"], serde_json::json!(true));
+    assert_eq!(body["results"][0]["solver"], serde_json::json!("dlx"));
+    assert_eq!(body["results"][1]["unique"], serde_json::json!(false));
+    assert_eq!(body["results"][1]["solver"], serde_json::json!("dfs"));
+    assert_eq!(body["results"][2]["unique"], serde_json::json!("unknown"));
+
+    let res: SuccessResponse = serde_json::from_value(body).unwrap();
+    let results = res.get_results();
+
+    assert_eq!(results[0].unique(), Uniqueness::Unique);
+    assert_eq!(results[1].unique(), Uniqueness::Multiple);
+    assert_eq!(results[2].unique(), Uniqueness::Unknown);
+}
+
+/// Sends a POST request with a proper and an improper puzzle to test that `detect_multiple` makes
+/// DFS report the uniqueness by itself, while keeping the first solution it found.
+#[actix_web::test]
+async fn test_detect_multiple() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let proper =
+        "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+    let improper =
+        "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+
+    let options = EntryOptions {
+        detect_multiple: Some(true),
+        ..Default::default()
+    };
+    let dfs = || Some(String::from("dfs"));
+    let payload = vec![
+        Entry::with_options(flat(proper), dfs(), options.clone()),
+        Entry::with_options(flat(improper), dfs(), options),
+        Entry::new(flat(improper), dfs()),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&test_app, req).await;
+
+    assert_eq!(body["results"][0]["unique"], serde_json::json!(true));
+    assert_eq!(body["results"][1]["unique"], serde_json::json!(false));
+    assert_eq!(body["results"][2]["unique"], serde_json::json!("unknown"));
+
+    let res: SuccessResponse = serde_json::from_value(body).unwrap();
+    let results = res.get_results();
+
+    assert!(results[0].solved().parse::<Sudoku>().unwrap().is_solved());
+    assert_eq!(results[1].solved(), results[2].solved());
+    assert!(results[1].options().detect_multiple);
+}
+
+/// Sends a POST request with the `require_unique` query parameter to test that the puzzles with
+/// too few clues and the improper ones are rejected with their own errors before solving, while
+/// the entries opting out are still solved.
+#[actix_web::test]
+async fn test_require_unique() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let proper =
+        "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let sparse =
+        "000000037002000050010000000000200104000001600300400000700063000000000200000000000";
+    let improper =
+        "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+
+    let options = EntryOptions {
+        require_unique: Some(false),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::new(flat(proper), None),
+        Entry::new(flat(sparse), None),
+        Entry::new(flat(improper), None),
+        Entry::with_options(flat(improper), None, options),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve?require_unique=true")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+
+    let indices = res
+        .get_results()
+        .iter()
+        .map(|r| r.index())
+        .collect::<Vec<_>>();
+    assert_eq!(indices, [0, 3]);
+
+    let errors = res.get_errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].index(), 1);
+    assert_eq!(errors[0].error().kind(), "too_few_clues");
+    assert_eq!(errors[0].error().status(), StatusCode::BAD_REQUEST);
+    assert_eq!(errors[1].index(), 2);
+    assert_eq!(errors[1].error().kind(), "not_unique");
+    assert_eq!(errors[1].error().status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Sends a POST request with the `pretty` query parameter to test that the pretty-printed
+/// solutions are included and can be parsed back into the solved grids.
+#[actix_web::test]
+async fn test_pretty_output() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let req = test::TestRequest::post()
+        .uri("/solve?pretty=true")
+        .set_json(into_payload(vec![raw.to_owned()], None))
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let result = &res.get_results()[0];
+
+    let pretty = result.pretty().unwrap();
+    assert_eq!(pretty.lines().count(), 11);
+    assert_eq!(
+        pretty.parse::<Sudoku>().unwrap().grid_to_string(),
+        result.solved()
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(into_payload(vec![raw.to_owned()], None))
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    assert!(res.get_results()[0].pretty().is_none());
+}
+
+/// Sends a POST request with the `include_diff` option set on one of the entries to test that
+/// exactly the empty cells of the puzzle are listed with their solved values.
+#[actix_web::test]
+async fn test_include_diff() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let options = EntryOptions {
+        include_diff: Some(true),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::with_options(flat(raw), None, options),
+        Entry::new(flat(raw), None),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    let diff = results[0].diff().unwrap();
+    assert_eq!(diff.len(), raw.matches('0').count());
+
+    for cell in diff {
+        let k = cell.pos.index(9);
+
+        assert_eq!(cell.from, 0);
+        assert_eq!(raw.as_bytes()[k], b'0');
+        assert_eq!(results[0].solved().as_bytes()[k] - b'0', cell.to);
+    }
+
+    assert!(results[1].diff().is_none());
+}
+
+/// Sends a POST request with the `output_format` set on the query and overridden by one of the
+/// entries, checking that the solved and propagated grids parse back into the flat ones.
+#[actix_web::test]
+async fn test_output_format() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+    let options = EntryOptions {
+        output_format: Some(OutputFormat::FlatZero),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::new(flat(raw), None),
+        Entry::with_options(flat(raw), None, options),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve?output_format=multiline&include_propagated=true")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results[0].options().output_format, OutputFormat::Multiline);
+    assert_eq!(results[0].solved().lines().count(), 9);
+    assert_eq!(
+        results[0].solved().parse::<Sudoku>(),
+        results[1].solved().parse::<Sudoku>()
+    );
+    assert_eq!(results[1].solved().len(), 81);
+
+    let propagated = results[0].propagated().unwrap();
+    assert!(propagated.contains('.') && !propagated.contains('0'));
+    assert_eq!(
+        propagated.parse::<Sudoku>(),
+        results[1].propagated().unwrap().parse::<Sudoku>()
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/solve?output_format=unknown")
+        .set_json(vec![Entry::new(flat(raw), None)])
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Sends a POST request with the same puzzle in the zero, dot, and nested formats alongside a
+/// different one to test that the digest only depends on the contents of the grid.
+#[actix_web::test]
+async fn test_digest() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let nested = raw
+        .as_bytes()
+        .chunks(9)
+        .map(|row| row.iter().map(|&b| b - b'0').collect())
+        .collect::<Vec<Vec<u8>>>();
+    let payload = serde_json::json!([
+        { "grid": raw, "solver": null },
+        { "grid": raw.replace('0', "."), "solver": null },
+        { "grid": nested, "solver": null },
+        { "grid": "000000010400000000020000000000050407008000300001090000300400200050100000000806000", "solver": null },
+    ]);
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].digest().len(), 16);
+    assert_eq!(
+        results[0].digest(),
+        format!("{:016x}", raw.parse::<Sudoku>().unwrap().digest())
+    );
+    assert_eq!(results[1].digest(), results[0].digest());
+    assert_eq!(results[2].digest(), results[0].digest());
+    assert_ne!(results[3].digest(), results[0].digest());
+}
+
+/// Sends a POST request with the `include_stats` query parameter to test that the statistics of
+/// the original puzzle are included unless an entry opts out.
+#[actix_web::test]
+async fn test_include_stats() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let options = EntryOptions {
+        include_stats: Some(false),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::new(flat(raw), None),
+        Entry::with_options(flat(raw), None, options),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve?include_stats=true")
+        .set_json(payload)
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&test_app, req).await;
+
+    assert_eq!(body["results"][0]["stats"]["clues"], serde_json::json!(17));
+    assert!(body["results"][1].get("stats").is_none());
+
+    let res: SuccessResponse = serde_json::from_value(body).unwrap();
+    let results = res.get_results();
+    assert_eq!(
+        results[0].stats(),
+        Some(&raw.parse::<Sudoku>().unwrap().statistics())
+    );
+}
+
+/// Sends POST requests with the `include_propagated` and `include_candidates` flags set on the
+/// query and entry levels, checking that the fields are omitted entirely if not requested.
+#[actix_web::test]
+async fn test_propagated_and_candidates() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000012700060000000000050080200000600000400000109000019000000000030800502000000";
+
+    let options = EntryOptions {
+        include_propagated: Some(true),
+        include_candidates: Some(false),
+        ..Default::default()
+    };
+    let payload = vec![
+        Entry::new(flat(raw), None),
+        Entry::with_options(flat(raw), None, options),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve?include_candidates=true")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    // Batch level flag applied to the first entry
+    let candidates = results[0].candidates().unwrap();
+    assert!(results[0].propagated().is_none());
+    assert!(!candidates.is_empty());
+
+    for (cell, values) in candidates {
+        let (row, col) = cell.trim_start_matches('r').split_once('c').unwrap();
+        let (row, col): (usize, usize) = (row.parse().unwrap(), col.parse().unwrap());
+        let solved = results[0].solved().as_bytes()[(row - 1) * 9 + col - 1] - b'0';
+
+        assert_eq!(raw.as_bytes()[(row - 1) * 9 + col - 1], b'0');
+        assert!(values.contains(&solved));
+    }
+
+    // Overridden by the entry options
+    let propagated = results[1].propagated().unwrap();
+    assert!(results[1].candidates().is_none());
+    assert_eq!(propagated.len(), 81);
+
+    for ((p, r), s) in propagated
+        .chars()
+        .zip(raw.chars())
+        .zip(results[1].solved().chars())
+    {
+        assert!(r == '0' || p == r);
+        assert!(p == '0' || p == s);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(vec![Entry::new(flat(raw), None)])
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&test_app, req).await;
+    let result = &body["results"][0];
+
+    assert!(result.get("propagated").is_none());
+    assert!(result.get("candidates").is_none());
+}
+
+/// Sends a POST request mixing the flat string and the 2D array grid formats in one payload to
+/// test that both are accepted and that malformed arrays are rejected.
+#[actix_web::test]
+async fn test_mixed_grid_formats() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let nested = Sudoku::new(raw.to_owned()).unwrap().clone_grid();
+    let mut ragged = nested.clone();
+    ragged[4].pop();
+
+    let payload = vec![
+        Entry::new(flat(raw), None),
+        Entry::new(nested.clone(), None),
+        Entry::new(ragged, None),
+    ];
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    let results = res.get_results();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1].grid(), &GridInput::Nested(nested));
+    assert_eq!(results[0].solved(), results[1].solved());
+
+    let errors = res.get_errors();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].index(), 2);
+    assert_eq!(errors[0].error().kind(), "invalid_dimensions");
+}
+
+/// Sends POST requests with the batch level solver set via the query parameter to test the
+/// precedence of entry > query > server default, and the rejection of invalid query values.
+#[actix_web::test]
+async fn test_query_solver() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let payload = || {
+        vec![
+            Entry::new(flat(raw), None),
+            Entry::new(flat(raw), Some(String::from("dfs"))),
+            Entry::new(flat(raw), Some(String::from("dlx"))),
+        ]
+    };
+
+    let cases = [
+        (
+            "/solve",
+            [SolverType::Dlx, SolverType::Dfs, SolverType::Dlx],
+        ),
+        (
+            "/solve?solver=dfs",
+            [SolverType::Dfs, SolverType::Dfs, SolverType::Dlx],
+        ),
+        (
+            "/solve?solver=DLX",
+            [SolverType::Dlx, SolverType::Dfs, SolverType::Dlx],
+        ),
+    ];
+
+    for (uri, expected) in cases {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .set_json(payload())
+            .to_request();
+        let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+        let solvers = res
+            .get_results()
+            .iter()
+            .map(|r| r.solver())
+            .collect::<Vec<SolverType>>();
+
+        assert_eq!(solvers, expected, "Invalid solver precedence for '{}'", uri);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/solve?solver=dsf")
+        .set_json(payload())
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+
+    assert_eq!(res_body.kind(), "unknown_solver");
+    assert!(res_body.errors().is_empty());
+}
+
+/// Sends the same payload to both the legacy `/solve` and the versioned `/v1/solve` endpoints to
+/// test that they share the results and validation, while differing in the response schema.
+#[actix_web::test]
+async fn test_api_versions() {
+    let test_app = test::init_service(
+        App::new()
+            .service(controller::solve)
+            .service(web::scope("/v1").service(controller::solve_v1)),
+    )
+    .await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let mut out_of_range = vec![vec![0; 9]; 9];
+    out_of_range[0][0] = 10;
+    let payload = vec![Entry::new(flat(raw), None), Entry::new(out_of_range, None)];
+
+    let mut bodies = Vec::new();
+
+    for uri in ["/solve", "/v1/solve"] {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .set_json(&payload)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&test_app, req).await;
+        bodies.push(body);
+    }
+
+    let (legacy, v1) = (&bodies[0], &bodies[1]);
+
+    for field in ["solved", "total_cpu_ms", "avg_cpu_ms", "avg_visited_nodes"] {
+        assert!(legacy.get(field).is_some());
+        assert!(v1.get(field).is_none());
+    }
+
+    for field in ["results", "errors", "stats"] {
+        assert!(legacy.get(field).is_some());
+        assert!(v1.get(field).is_some());
+    }
+
+    let legacy: SuccessResponse = serde_json::from_value(legacy.clone()).unwrap();
+    let v1: V1Response = serde_json::from_value(v1.clone()).unwrap();
+
+    assert_eq!(legacy.get_results().len(), v1.get_results().len());
+    assert_eq!(
+        legacy.get_results()[0].solved(),
+        v1.get_results()[0].solved()
+    );
+    assert_eq!(v1.get_errors()[0].error().kind(), "invalid_format");
+    assert_eq!(
+        legacy.get_errors()[0].error().kind(),
+        v1.get_errors()[0].error().kind()
+    );
+
+    // Shared validation rejects the invalid query parameter on both versions
+    for uri in ["/solve?solver=nonexistent", "/v1/solve?solver=nonexistent"] {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .set_json(&payload)
+            .to_request();
+        let res = test::call_service(&test_app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Sends a POST request with a misspelled solver name to test that unknown solvers are rejected
+/// instead of silently falling back to the default solver.
+#[actix_web::test]
+async fn test_unknown_solver() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let payload = into_payload(get_unsolved(), Some(String::from("dsf")));
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(payload)
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(
+        res.status(),
+        StatusCode::BAD_REQUEST,
+        "Invalid HTTP status code received in the error response"
+    );
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    let errors = res_body.errors();
+
+    assert_eq!(res_body.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(errors.len(), 3);
+
+    for (i, e) in errors.iter().enumerate() {
+        assert_eq!(e.index(), i);
+        assert_eq!(e.error().kind(), "unknown_solver");
+        assert_eq!(
+            e.error().message(),
+            "Unknown solver 'dsf', valid options are: 'dfs', 'dlx', 'auto'"
+        );
+    }
+}
+
+/// Sends a POST request without any solver to the legacy `/sdfs` endpoint to test that the
+/// batch is solved with DFS and returned in the structured `Response` schema.
+#[actix_web::test]
+async fn test_legacy_dfs_route() {
+    let test_app = test::init_service(App::new().service(controller::solve_dfs)).await;
+    let unsolved = get_unsolved();
+    let payload = into_payload(unsolved.clone(), None);
+
+    let req = test::TestRequest::post()
+        .uri("/sdfs")
+        .set_json(payload)
+        .to_request();
+    let res: Response = test::call_and_read_body_json(&test_app, req).await;
+
+    assert_eq!(res.data().len(), unsolved.len());
+
+    for (i, (puzzle, raw)) in res.data().iter().zip(&unsolved).enumerate() {
+        let solution = puzzle.solved().parse::<Sudoku>().unwrap();
+
+        assert_eq!(puzzle.index(), i);
+        assert!(solution.is_solution_of(&raw.parse().unwrap()));
+        assert_eq!(puzzle.grid(), solution.clone_grid());
+        assert!(puzzle.grid().iter().all(has_unique_items));
+    }
+}
+
+/// Sends a POST request with a valid, an invalid, and an improper Sudoku to the `/validate`
+/// endpoint to test that the statistics of the valid puzzles are returned without solving them,
+/// and the others are rejected with the same errors as by the solver endpoints.
+#[actix_web::test]
+async fn test_validate() {
+    let test_app = test::init_service(App::new().service(controller::validate)).await;
+    let valid = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let invalid =
+        "830070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let improper =
+        "089023417021047893473918562957231684142865379836794125398476251715382946264159738";
+    let payload = into_payload([valid, invalid, improper].map(String::from).to_vec(), None);
+
+    let req = test::TestRequest::post()
+        .uri("/validate?require_unique=true")
+        .set_json(payload)
+        .to_request();
+    let res: ValidationResponse = test::call_and_read_body_json(&test_app, req).await;
+
+    let sudoku = valid.parse::<Sudoku>().unwrap();
+    assert_eq!(res.results().len(), 1);
+    assert_eq!(res.results()[0].index(), 0);
+    assert_eq!(
+        res.results()[0].digest(),
+        format!("{:016x}", sudoku.digest())
+    );
+    assert_eq!(res.results()[0].stats(), &sudoku.statistics());
+    assert_eq!(res.results()[0].stats().clues, 30);
+
+    let errors = res.errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].index(), 1);
+    assert_eq!(errors[0].error().kind(), "constraint_violation");
+    assert_eq!(errors[1].index(), 2);
+    assert_eq!(errors[1].error().kind(), "not_unique");
+
+    // Rejected as a whole only if the batch is empty
+    let req = test::TestRequest::post()
+        .uri("/validate")
+        .set_json(Vec::<Entry>::new())
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Sends a single entry to the WebSocket endpoint to test that the progress frames are streamed
+/// before the final result frame, after which the server closes the connection.
+#[actix_web::test]
+async fn test_ws_progress() {
+    let mut srv = actix_test::start(|| App::new().service(ws::solve_ws));
+    // Among the hardest for DLX in the 'sudoku17' collection, i.e. over 17000 nodes
+    let raw = "000801000000000205000000700507620000030000010000000000600000040000250000490000000";
+
+    let frames = ws_frames(&mut srv, &Entry::new(flat(raw), None)).await;
+    let (last, progress) = frames.split_last().unwrap();
+
+    assert_eq!(progress.len(), 3);
+    assert!(progress.iter().all(|f| f["type"] == "progress"));
+    assert!(progress
+        .windows(2)
+        .all(|w| w[0]["visited_nodes"].as_u64() < w[1]["visited_nodes"].as_u64()));
+    assert!(progress
+        .iter()
+        .all(|f| f["depth"].is_u64() && f["elapsed_us"].is_u64()));
+
+    assert_eq!(last["type"], "result");
+    assert_eq!(last["solver"], "dlx");
+
+    let sudoku = Sudoku::new(last["solved"].as_str().unwrap().to_owned()).unwrap();
+    assert!(sudoku.is_valid(None));
+    assert!(sudoku.is_solved());
+}
+
+/// Sends invalid entries to the WebSocket endpoint to test that a single error frame is sent.
+#[actix_web::test]
+async fn test_ws_invalid_entry() {
+    let mut srv = actix_test::start(|| App::new().service(ws::solve_ws));
+
+    let frames = ws_frames(&mut srv, &serde_json::json!({ "grid": "malformed" })).await;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0]["type"], "error");
+    assert_eq!(frames[0]["kind"], "invalid_format");
+
+    let frames = ws_frames(&mut srv, &serde_json::json!({ "unknown": 1 })).await;
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0]["kind"], "invalid_format");
+}
+
+/// Tracing layer triggering the `shutdown` at the first event of the solve span of the entry at
+/// `index`, i.e. once the propagation of its solver is running, so the shutdown lands in the
+/// middle of that solve regardless of the timing.
+struct ShutdownOnSolve {
+    shutdown: Shutdown,
+    index: u64,
+    span: Mutex<Option<Id>>,
+}
+
+/// Picks the `index` field of the solve span.
+struct IndexVisitor(Option<u64>);
+
+impl Visit for IndexVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "index" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for ShutdownOnSolve {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut visitor = IndexVisitor(None);
+        attrs.record(&mut visitor);
+
+        if attrs.metadata().name() == "solve" && visitor.0 == Some(self.index) {
+            *self.span.lock().unwrap() = Some(id.clone());
+        }
+    }
+
+    fn on_event(&self, _event: &Event<'_>, ctx: Context<'_, S>) {
+        let current = ctx.current_span();
+
+        if current.id().is_some() && current.id() == self.span.lock().unwrap().as_ref() {
+            self.shutdown.trigger();
+        }
+    }
+}
+
+/// Triggers the shutdown while a hard puzzle is being solved with DFS to test that the running
+/// solve is aborted cleanly, keeping the already finished results, and that the batches arriving
+/// after the shutdown are rejected as cancelled.
+#[actix_web::test]
+async fn test_shutdown_cancels_solves() {
+    let shutdown = Shutdown::new();
+    let test_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(shutdown.clone()))
+            .service(controller::solve),
+    )
+    .await;
+    let easy = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let hard = "000002300100500000007000000400000017030800000000000600006470000020000900000010000";
+    let payload = vec![
+        Entry::new(flat(easy), None),
+        Entry::new(flat(hard), Some(String::from("dfs"))),
+        Entry::new(flat(easy), None),
+    ];
+
+    // The handler runs on the thread of the test, which the subscriber is the default of
+    let _guard = tracing::subscriber::set_default(Registry::default().with(ShutdownOnSolve {
+        shutdown: shutdown.clone(),
+        index: 1,
+        span: Mutex::new(None),
+    }));
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(&payload)
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    assert!(shutdown.is_triggered());
+
+    assert_eq!(res.get_results().len(), 1);
+    assert_eq!(res.get_results()[0].index(), 0);
+
+    let errors = res.get_errors();
+    assert_eq!(errors.len(), 2);
+
+    for (error, index) in errors.iter().zip([1, 2]) {
+        assert_eq!(error.index(), index);
+        assert_eq!(error.error().kind(), "cancelled");
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(&payload[..1])
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    assert_eq!(res_body.kind(), "cancelled");
+}
+
+/// Sends POST requests until the tiny burst size of the rate limiter is exhausted to test that
+/// the blocked request is answered with a structured `ErrorResponse` and a `Retry-After` header.
+#[actix_web::test]
+async fn test_rate_limit_exceeded() {
+    let governor_conf = GovernorConfigBuilder::default()
+        .key_extractor(RateLimitExtractor)
+        .per_second(60)
+        .burst_size(1)
+        .use_headers()
+        .finish()
+        .unwrap();
+    let test_app = test::init_service(
+        App::new()
+            .wrap(Governor::new(&governor_conf))
+            .service(controller::solve),
+    )
+    .await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let res = test::call_service(&test_app, rate_limited_request(raw).to_request()).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = test::call_service(&test_app, rate_limited_request(raw).to_request()).await;
+
+    assert_eq!(
+        res.status(),
+        StatusCode::TOO_MANY_REQUESTS,
+        "Invalid HTTP status code received in the error response"
+    );
+
+    let retry_after = res
+        .headers()
+        .get(RETRY_AFTER)
+        .expect("Missing Retry-After header in the error response")
+        .to_str()
+        .unwrap()
+        .parse::<u64>()
+        .unwrap();
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+
+    assert_eq!(res_body.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res_body.kind(), "rate_limited");
+    assert_eq!(res_body.retry_after_s(), Some(retry_after));
+    assert!(retry_after > 0 && retry_after <= 60);
+}
+
+/// Changes the limits of the rate limiter via the admin endpoint at runtime to test that the new
+/// quota takes effect on the subsequent requests, and that the admin token is enforced.
+#[actix_web::test]
+async fn test_admin_rate_limit() {
+    let limits = RateLimits {
+        interval_s: 60,
+        burst_size: 1,
+    };
+    let test_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(RateLimitState::new(limits).unwrap()))
+            .app_data(web::Data::new(AdminToken::new("secret")))
+            .service(admin::get_rate_limit)
+            .service(admin::put_rate_limit)
+            .service(
+                web::scope("")
+                    .wrap(from_fn(limiter::rate_limit))
+                    .service(controller::solve),
+            ),
+    )
+    .await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+
+    let res = test::call_service(&test_app, rate_limited_request(raw).to_request()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get("x-ratelimit-remaining").unwrap(), "0");
+
+    let res = test::call_service(&test_app, rate_limited_request(raw).to_request()).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(RETRY_AFTER).is_some());
+
+    let new_limits = RateLimits {
+        interval_s: 60,
+        burst_size: 3,
+    };
+
+    for token in [None, Some("Bearer wrong"), Some("Bearer secreT")] {
+        let mut req = test::TestRequest::put()
+            .uri("/admin/rate-limit")
+            .set_json(new_limits);
+        if let Some(token) = token {
+            req = req.insert_header((AUTHORIZATION, token));
+        }
+
+        let res = test::call_service(&test_app, req.to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res_body: ErrorResponse = test::read_body_json(res).await;
+        assert_eq!(res_body.kind(), "unauthorized");
+    }
+
+    let req = test::TestRequest::put()
+        .uri("/admin/rate-limit")
+        .insert_header((AUTHORIZATION, "Bearer secret"))
+        .set_json(RateLimits {
+            interval_s: 0,
+            burst_size: 3,
+        })
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::put()
+        .uri("/admin/rate-limit")
+        .insert_header((AUTHORIZATION, "Bearer secret"))
+        .set_json(new_limits)
+        .to_request();
+    let stats: RateLimitStats = test::call_and_read_body_json(&test_app, req).await;
+    assert_eq!(stats.limits, new_limits);
+
+    for _ in 0..3 {
+        let res = test::call_service(&test_app, rate_limited_request(raw).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    let res = test::call_service(&test_app, rate_limited_request(raw).to_request()).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let req = test::TestRequest::get()
+        .uri("/admin/rate-limit")
+        .insert_header((AUTHORIZATION, "Bearer secret"))
+        .to_request();
+    let stats: RateLimitStats = test::call_and_read_body_json(&test_app, req).await;
+    assert_eq!(stats.limits, new_limits);
+    assert_eq!(stats.tracked_keys, 1);
+    assert_eq!(stats.allowed_requests, 4);
+    assert_eq!(stats.blocked_requests, 2);
+}
+
+/// Consumes the puzzle quota of an API key with batches of different sizes to test that a batch
+/// exceeding the remaining quota is rejected as a whole, and that the usage is reported.
+#[actix_web::test]
+async fn test_api_key_quota() {
+    let quotas = Quotas::new(
+        MemoryQuotaStore::new(),
+        HashMap::from([(String::from("alpha"), 3)]),
+        Duration::from_secs(60),
+    );
+    let test_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(quotas))
+            .service(controller::solve)
+            .service(quota::get_usage),
+    )
+    .await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let batch = |size: usize, key: Option<&str>| {
+        let mut req = test::TestRequest::post()
+            .uri("/solve")
+            .set_json(into_payload(vec![raw.to_owned(); size], None));
+        if let Some(key) = key {
+            req = req.insert_header((API_KEY_HEADER, key));
+        }
+        req.to_request()
+    };
+
+    let res = test::call_service(&test_app, batch(2, Some("alpha"))).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/usage")
+        .insert_header((API_KEY_HEADER, "alpha"))
+        .to_request();
+    let usage: QuotaUsage = test::call_and_read_body_json(&test_app, req).await;
+    assert_eq!(usage.limit, 3);
+    assert_eq!(usage.consumed, 2);
+    assert_eq!(usage.remaining, 1);
+    assert!(usage.reset_s > 0 && usage.reset_s <= 60);
+
+    let res = test::call_service(&test_app, batch(2, Some("alpha"))).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(res.headers().get(RETRY_AFTER).is_some());
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    assert_eq!(res_body.kind(), "quota_exceeded");
+    assert_eq!(res_body.quota().unwrap().remaining, 1);
+
+    // Neither the rejected entries nor the batches with an unknown solver are charged
+    let conflict = format!("11{}", &raw[2..]);
+    let mut payload = into_payload(vec![raw.to_owned(), conflict], None);
+    payload.push(Entry::new(flat(raw), Some(String::from("unknown"))));
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .insert_header((API_KEY_HEADER, "alpha"))
+        .set_json(&payload[1..])
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/solve?solver=unknown")
+        .insert_header((API_KEY_HEADER, "alpha"))
+        .set_json(&payload[..1])
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .insert_header((API_KEY_HEADER, "alpha"))
+        .set_json(&payload[..2])
+        .to_request();
+    let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+    assert_eq!(res.get_results().len(), 1);
+    assert_eq!(res.get_errors().len(), 1);
+
+    let req = test::TestRequest::get()
+        .uri("/usage")
+        .insert_header((API_KEY_HEADER, "alpha"))
+        .to_request();
+    let usage: QuotaUsage = test::call_and_read_body_json(&test_app, req).await;
+    assert_eq!(usage.remaining, 0);
+
+    for key in [None, Some("unknown")] {
+        let res = test::call_service(&test_app, batch(1, key)).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res_body: ErrorResponse = test::read_body_json(res).await;
+        assert_eq!(res_body.kind(), "invalid_api_key");
+    }
+}
+
+/// Requests the usage from a server without any API keys configured to test that the disabled
+/// quotas are reported with a structured `ErrorResponse`.
+#[actix_web::test]
+async fn test_usage_quotas_disabled() {
+    let test_app = test::init_service(App::new().service(quota::get_usage)).await;
+    let req = test::TestRequest::get()
+        .uri("/usage")
+        .insert_header((API_KEY_HEADER, "alpha"))
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    assert_eq!(res_body.kind(), "quotas_disabled");
+}
+
+/// Starts the server on a Unix domain socket left behind by a previous listener to test that the
+/// stale socket is replaced, and performs a solve over it with a raw HTTP/1.1 request.
+#[cfg(unix)]
+#[actix_web::test]
+async fn test_unix_socket() {
+    use std::os::unix::net::UnixListener;
+
+    use actix_web::HttpServer;
+    use pure_be::transport;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixStream,
+    };
+
+    let path = std::env::temp_dir().join(format!("pure-be-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    drop(UnixListener::bind(&path).unwrap());
+
+    transport::remove_stale_socket(&path).unwrap();
+    let server = HttpServer::new(|| App::new().service(controller::solve))
+        .workers(1)
+        .bind_uds(&path)
+        .unwrap()
+        .run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let body = serde_json::to_string(&into_payload(vec![raw.to_owned()], None)).unwrap();
+    let req = format!(
+        "POST /solve HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+
+    let mut stream = UnixStream::connect(&path).await.unwrap();
+    stream.write_all(req.as_bytes()).await.unwrap();
+    let mut res = String::new();
+    stream.read_to_string(&mut res).await.unwrap();
+
+    handle.stop(true).await;
+    let _ = std::fs::remove_file(&path);
+
+    let (head, body) = res.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+
+    let res: SuccessResponse = serde_json::from_str(body).unwrap();
+    assert_solved(&res, &[raw.to_owned()], SolverType::Dlx);
+}
+
+/// Starts a TLS listener with a freshly generated self-signed certificate and completes a solve
+/// over it, and tests that unreadable certificate files are reported as errors.
+#[actix_web::test]
+async fn test_tls_listener() {
+    use std::sync::Arc;
+
+    use actix_web::HttpServer;
+    use pure_be::tls;
+    use rustls::{crypto::ring, pki_types::CertificateDer, ClientConfig, RootCertStore};
+
+    let certified = rcgen::generate_simple_self_signed(vec![String::from("localhost")]).unwrap();
+    let dir = std::env::temp_dir();
+    let cert_path = dir.join(format!("pure-be-{}-cert.pem", std::process::id()));
+    let key_path = dir.join(format!("pure-be-{}-key.pem", std::process::id()));
+    std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+    std::fs::write(&key_path, certified.signing_key.serialize_pem()).unwrap();
+
+    let err = tls::server_config(&key_path, &key_path).unwrap_err();
+    assert!(err.to_string().contains("No TLS certificates found"));
+    let err = tls::server_config(&cert_path, &dir.join("missing.pem")).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Failed to load the TLS private key"));
+
+    let server_config = tls::server_config(&cert_path, &key_path).unwrap();
+    let _ = std::fs::remove_file(&cert_path);
+    let _ = std::fs::remove_file(&key_path);
+
+    let server = HttpServer::new(|| App::new().service(controller::solve))
+        .workers(1)
+        .bind_rustls_0_23(("127.0.0.1", 0), server_config)
+        .unwrap();
+    let addr = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from(certified.cert.der().to_vec()))
+        .unwrap();
+    let client_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().rustls_0_23(Arc::new(client_config)))
+        .finish();
+
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+    let mut res = client
+        .post(format!("https://localhost:{}/solve", addr.port()))
+        .send_json(&into_payload(vec![raw.to_owned()], None))
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res: SuccessResponse = res.json().await.unwrap();
+    handle.stop(true).await;
+
+    assert_solved(&res, &[raw.to_owned()], SolverType::Dlx);
+}
+
+/// Posts an oversized and a malformed payload against a tiny payload limit to test that both are
+/// answered with structured `ErrorResponse` bodies.
+#[actix_web::test]
+async fn test_payload_errors() {
+    let test_app = test::init_service(
+        App::new()
+            .app_data(controller::json_config(64))
+            .service(controller::solve),
+    )
+    .await;
+    let raw = "000000037002000050010000000000200104000001600300400000700063000000000200000080000";
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .set_json(into_payload(vec![raw.to_owned()], None))
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    assert_eq!(res_body.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(res_body.kind(), "payload_too_large");
+    assert!(res_body.message().contains("64 bytes"));
+
+    let req = test::TestRequest::post()
+        .uri("/solve")
+        .insert_header(ContentType::json())
+        .set_payload("[{\"grid\": ")
+        .to_request();
+    let res = test::call_service(&test_app, req).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res_body: ErrorResponse = test::read_body_json(res).await;
+    assert_eq!(res_body.kind(), "invalid_format");
+}
+
+/// Sends POST requests with a few hundred puzzles of the 'sudoku17' collection to test that both
+/// solvers keep every given cell of the puzzles intact.
+#[actix_web::test]
+async fn test_givens_preserved() {
+    let test_app = test::init_service(App::new().service(controller::solve)).await;
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    let puzzles: Vec<String> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .step_by(163)
+        .collect();
+    assert!(puzzles.len() > 300);
+
+    for solver in ["dfs", "dlx"] {
+        let req = test::TestRequest::post()
+            .uri("/solve")
+            .set_json(into_payload(puzzles.clone(), Some(String::from(solver))))
+            .to_request();
+        let res: SuccessResponse = test::call_and_read_body_json(&test_app, req).await;
+
+        assert!(res.get_errors().is_empty());
+        assert_eq!(res.get_results().len(), puzzles.len());
+
+        for (result, raw) in res.get_results().iter().zip(&puzzles) {
+            assert_eq!(result.solved().len(), raw.len());
+
+            for (i, (s, r)) in result.solved().chars().zip(raw.chars()).enumerate() {
+                assert!(
+                    r == '0' || s == r,
+                    "{} altered the given of cell {} of {}",
+                    solver,
+                    i,
+                    raw
+                );
+            }
+        }
+    }
+}
+
+/// Asserts that every entry of the batch has a valid & solved result matching the input order.
+fn assert_solved(res: &SuccessResponse, unsolved: &[String], solver: SolverType) {
+    assert_eq!(res.get_results().len(), unsolved.len());
+    // A real solve consumes measurable CPU time, even though it may round down to zero ms
+    assert!(res.get_total_cpu_us() > 0);
+    assert!(res.get_total_cpu_ms() <= res.get_total_cpu_us() / 1000);
+
+    for grid_str in res.get_solved() {
+        let sudoku = Sudoku::new(grid_str).unwrap();
+        assert!(sudoku.is_valid(None));
+        assert!(sudoku.is_solved());
+    }
+
+    for (i, (result, raw)) in res.get_results().iter().zip(unsolved).enumerate() {
+        assert_eq!(result.index(), i);
+        assert_eq!(result.grid(), &GridInput::Flat(flat(raw)));
+        assert_eq!(result.solver(), solver);
+        assert_eq!(result.solved(), res.get_solved()[i]);
+        // Every given of the puzzle is preserved by the solution
+        let puzzle = raw.parse::<Sudoku>().unwrap();
+        let solution = result.solved().parse::<Sudoku>().unwrap();
+        assert!(solution.is_solution_of(&puzzle));
+        assert!(Pos::all(9)
+            .filter(|&pos| puzzle.is_given(pos))
+            .all(|pos| solution.get_grid_value(pos) == puzzle.get_grid_value(pos)));
+        assert!(result.cpu_time_us() > 0);
+        // Tolerance for the differing granularities of the thread CPU and wall-clock times
+        assert!(result.cpu_time_us() <= result.wall_time_us() + 1000);
+
+        let difficulty = result.difficulty();
+        assert_eq!(difficulty.visited_nodes, result.visited_nodes());
+        assert_eq!(
+            difficulty.clues,
+            raw.chars().filter(|&ch| ch != '0').count()
+        );
+        assert!((1..=10).contains(&difficulty.grade));
+        assert_eq!(result.search().is_some(), solver == SolverType::Dfs);
+        assert_eq!(result.dlx().is_some(), solver == SolverType::Dlx);
+    }
+
+    let structured = Response::from(res);
+    assert_eq!(structured.data().len(), unsolved.len());
+
+    for (i, puzzle) in structured.data().iter().enumerate() {
+        assert_eq!(puzzle.index(), i);
+        assert_eq!(puzzle.solved(), res.get_solved()[i]);
+        assert_eq!(puzzle.grid().len(), 9);
+
+        for row in puzzle.grid() {
+            let mut sorted = row.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (1..=9).collect::<Vec<u8>>());
+        }
+    }
+}
+
+/// Sends the message to the WebSocket endpoint and collects the text frames until the server
+/// closes the connection.
+async fn ws_frames(
+    srv: &mut actix_test::TestServer,
+    message: &impl serde::Serialize,
+) -> Vec<serde_json::Value> {
+    let mut framed = srv.ws_at("/solve/ws").await.unwrap();
+    let text = serde_json::to_string(message).unwrap();
+    framed
+        .send(awc::ws::Message::Text(text.into()))
+        .await
+        .unwrap();
+
+    let mut frames = Vec::new();
+
+    while let Some(Ok(awc::ws::Frame::Text(bytes))) = framed.next().await {
+        frames.push(serde_json::from_slice(&bytes).unwrap());
+    }
+
+    frames
+}
+
+fn rate_limited_request(raw: &str) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri("/solve")
+        .peer_addr("127.0.0.1:12345".parse().unwrap())
+        .set_json(into_payload(vec![raw.to_owned()], None))
+}
+
+fn get_unsolved() -> Vec<String> {
+    static COLLECTION_SIZE: usize = 49150;
+    let mut rng = rand::thread_rng();
+
+    let file = File::open("./tests/sudoku17")
+        .expect("Failed to open the 'sudoku17' collection file for reading");
+    let lines: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
+    let mut unsolved = Vec::new();
+
+    for _ in 0..3 {
+        let ln = rng.gen_range(0..COLLECTION_SIZE);
+        unsolved.push(lines[ln].to_owned());
+    }
+
+    unsolved
+}
+
+fn into_payload(raws: Vec<String>, s_type: Option<String>) -> Vec<Entry> {
+    raws.into_iter()
+        .map(|raw| Entry::new(flat(&raw), s_type.clone()))
+        .collect()
+}
+
+fn flat(raw: &str) -> GridString {
+    raw.parse().unwrap()
 }